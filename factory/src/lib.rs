@@ -1,5 +1,6 @@
 use aurora_engine_types::types::Address;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
     env, ext_contract, near_bindgen, AccountId, Gas, NearToken, PanicOnDefault, Promise,
@@ -8,10 +9,11 @@ use near_sdk::{
 const FORWARDER_WASM: &[u8] = include_bytes!("../../res/aurora-forwarder.wasm");
 const STORAGE_BALANCE_BOUND: NearToken = NearToken::from_yoctonear(1_250_000_000_000_000_000_000);
 const FORWARDER_NEW_GAS: Gas = Gas::from_tgas(2);
-const FORWARD_TOKENS_GAS: Gas = Gas::from_tgas(150);
+const FORWARD_TOKENS_GAS: Gas = Gas::from_tgas(170);
 
 pub const MAX_NUM_CONTRACTS: usize = 12;
-pub const INIT_BALANCE: NearToken = NearToken::from_millinear(310);
+// Covers the storage staking of the forwarder code of about 210 KB and its state.
+pub const INIT_BALANCE: NearToken = NearToken::from_millinear(2_300);
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
@@ -50,6 +52,12 @@ impl AuroraForwarderFactory {
             parameters.len() <= MAX_NUM_CONTRACTS,
             "Number of contracts can't be greater than {MAX_NUM_CONTRACTS}"
         );
+        assert!(
+            parameters
+                .iter()
+                .all(|params| forwarder_utils::is_valid_address(&params.target_address)),
+            "Invalid target address"
+        );
 
         parameters
             .into_iter()
@@ -99,6 +107,369 @@ impl AuroraForwarderFactory {
             .forward(token_id)
     }
 
+    /// Forward several tokens for a specific forwarder in one transaction. The attached gas
+    /// above the static one is shared between the forwardings.
+    #[private]
+    pub fn forward_tokens_batch(
+        &mut self,
+        forwarder_id: AccountId,
+        token_ids: Vec<AccountId>,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id)
+            .with_static_gas(FORWARD_TOKENS_GAS)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .forward_batch(token_ids)
+    }
+
+    /// Wrap the NEAR held by a specific forwarder into wNEAR and forward it.
+    #[private]
+    pub fn forward_near(&mut self, forwarder_id: AccountId) -> Promise {
+        ext_forwarder::ext(forwarder_id)
+            .with_static_gas(FORWARD_TOKENS_GAS)
+            .forward_near()
+    }
+
+    /// Set the NFT bridge of a specific forwarder, which the received NFTs are transferred to.
+    #[private]
+    pub fn set_forwarder_nft_bridge(
+        &mut self,
+        forwarder_id: AccountId,
+        nft_bridge_id: Option<AccountId>,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_nft_bridge(nft_bridge_id)
+    }
+
+    /// Set the target address of a specific forwarder, e.g. after the rotation of the wallet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target address is invalid.
+    #[private]
+    pub fn set_forwarder_target_address(
+        &mut self,
+        forwarder_id: AccountId,
+        target_address: String,
+    ) -> Promise {
+        assert!(
+            forwarder_utils::is_valid_address(&target_address),
+            "Invalid target address"
+        );
+
+        ext_forwarder::ext(forwarder_id)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .set_target_address(target_address)
+    }
+
+    /// Set the NEAR account, which a specific forwarder transfers the tokens to instead of
+    /// the target address. `None` restores the forwarding to the target address.
+    #[private]
+    pub fn set_forwarder_near_receiver(
+        &mut self,
+        forwarder_id: AccountId,
+        receiver_id: Option<AccountId>,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .set_near_receiver(receiver_id)
+    }
+
+    /// Set the NEAR account, which a specific forwarder pays out the tokens withdrawn from
+    /// the target network to. `None` keeps the withdrawn tokens in the forwarder.
+    #[private]
+    pub fn set_forwarder_withdraw_beneficiary(
+        &mut self,
+        forwarder_id: AccountId,
+        beneficiary_id: Option<AccountId>,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .set_withdraw_beneficiary(beneficiary_id)
+    }
+
+    /// Set the lock contract of the bridge, which a specific forwarder transfers the tokens to,
+    /// so they are forwarded to the target address on Ethereum. `None` restores the forwarding
+    /// to the target network.
+    #[private]
+    pub fn set_forwarder_eth_bridge(
+        &mut self,
+        forwarder_id: AccountId,
+        bridge_id: Option<AccountId>,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .set_eth_bridge(bridge_id)
+    }
+
+    /// Set the target network of a specific forwarder. The network should be supported by
+    /// the fees contract.
+    #[private]
+    pub fn set_forwarder_target_network(
+        &mut self,
+        forwarder_id: AccountId,
+        target_network: AccountId,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .set_target_network(target_network)
+    }
+
+    /// Set the minimum amount of the token, which could be forwarded by a specific forwarder.
+    /// Zero removes the minimum.
+    #[private]
+    pub fn set_forwarder_min_amount(
+        &mut self,
+        forwarder_id: AccountId,
+        token_id: AccountId,
+        amount: U128,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_min_forward_amount(token_id, amount.0)
+    }
+
+    /// Set the maximum amount of the token, which could be forwarded at once by a specific
+    /// forwarder. Zero removes the maximum.
+    #[private]
+    pub fn set_forwarder_max_amount(
+        &mut self,
+        forwarder_id: AccountId,
+        token_id: AccountId,
+        amount: U128,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_max_forward_amount(token_id, amount.0)
+    }
+
+    /// Set the schedule of the recurring forwarding of the token by a specific forwarder,
+    /// which is triggered at most once per the interval. Zero removes the schedule.
+    #[private]
+    pub fn set_forwarder_schedule(
+        &mut self,
+        forwarder_id: AccountId,
+        token_id: AccountId,
+        interval_hours: U64,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_schedule(token_id, interval_hours.0)
+    }
+
+    /// Set the gas of the calls of the fees contract and the token made by a specific
+    /// forwarder. The forwarder rejects the gas out of its bounds.
+    #[private]
+    pub fn set_forwarder_gas_config(
+        &mut self,
+        forwarder_id: AccountId,
+        calculate_fees: Gas,
+        ft_transfer: Gas,
+        ft_transfer_call: Gas,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_gas_config(
+            calculate_fees.as_gas(),
+            ft_transfer.as_gas(),
+            ft_transfer_call.as_gas(),
+        )
+    }
+
+    /// Set the addresses the tokens forwarded by a specific forwarder are split between.
+    /// The weights are in basis points and should sum up to 10000. The empty splits restore
+    /// the forwarding to the target address.
+    #[private]
+    pub fn set_forwarder_splits(
+        &mut self,
+        forwarder_id: AccountId,
+        splits: Vec<SplitParameters>,
+    ) -> Promise {
+        let splits = splits
+            .into_iter()
+            .map(|split| {
+                let address = Address::decode(split.address.trim_start_matches("0x"))
+                    .unwrap_or_else(|_| env::panic_str("Invalid split address"));
+                (address, split.weight)
+            })
+            .collect();
+
+        ext_forwarder::ext(forwarder_id).set_splits(splits)
+    }
+
+    /// Set the fixed fee of a specific forwarder, which is charged without the call of the fees
+    /// contract. `None` restores the fees calculated by the fees contract.
+    #[private]
+    pub fn set_forwarder_static_fee(
+        &mut self,
+        forwarder_id: AccountId,
+        fee: Option<StaticFeeParameters>,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id)
+            .set_static_fee(fee.map(|fee| (fee.fee_bps, fee.fee_recipient)))
+    }
+
+    /// Set the limit of the forwardings of every token per the time window of a specific
+    /// forwarder. `None` removes the limit.
+    #[private]
+    pub fn set_forwarder_rate_limit(
+        &mut self,
+        forwarder_id: AccountId,
+        rate_limit: Option<RateLimitParameters>,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_rate_limit(
+            rate_limit.map(|rate_limit| (rate_limit.max_forwards, rate_limit.window_secs.0)),
+        )
+    }
+
+    /// Set the fees contract, which a specific forwarder calls if the call of its fees contract
+    /// fails. `None` removes it.
+    #[private]
+    pub fn set_forwarder_fallback_fees_contract(
+        &mut self,
+        forwarder_id: AccountId,
+        fees_contract_id: Option<AccountId>,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_fallback_fees_contract(fees_contract_id)
+    }
+
+    /// Set the fee charged by a specific forwarder if none of the fees contracts has calculated
+    /// the fee. `None` aborts the forwarding in this case.
+    #[private]
+    pub fn set_forwarder_fallback_fee(
+        &mut self,
+        forwarder_id: AccountId,
+        fee: Option<StaticFeeParameters>,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id)
+            .set_fallback_fee(fee.map(|fee| (fee.fee_bps, fee.fee_recipient)))
+    }
+
+    /// Set the number of the blocks a specific forwarder caches the fee percent calculated by
+    /// the fees contract for. Zero disables the cache.
+    #[private]
+    pub fn set_forwarder_fee_cache_ttl(&mut self, forwarder_id: AccountId, ttl: U64) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_fee_cache_ttl(ttl.0)
+    }
+
+    /// Set the format of the `msg` used by a specific forwarder for the deposits to the target
+    /// network.
+    #[private]
+    pub fn set_forwarder_msg_format(
+        &mut self,
+        forwarder_id: AccountId,
+        target_network: AccountId,
+        format: MsgFormat,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_msg_format(target_network, format)
+    }
+
+    /// Set the EVM calldata, which the engine calls the target address of a specific forwarder
+    /// with after the deposit. `None` restores the plain deposits.
+    #[private]
+    pub fn set_forwarder_xcc_payload(
+        &mut self,
+        forwarder_id: AccountId,
+        payload: Option<Base64VecU8>,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_xcc_payload(payload.map(|payload| payload.0))
+    }
+
+    /// Set whether the target network of a specific forwarder uses NEAR as its base token, so
+    /// the forwarded wNEAR is unwrapped and deposited as the base token.
+    #[private]
+    pub fn set_forwarder_near_base_token(
+        &mut self,
+        forwarder_id: AccountId,
+        target_network: AccountId,
+        is_enabled: bool,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_near_base_token(target_network, is_enabled)
+    }
+
+    /// Transfer the whole balance of the token held by a specific forwarder to the recovery
+    /// account, e.g. if the token isn't supported or has been sent by mistake.
+    #[private]
+    pub fn sweep_forwarder_tokens(
+        &mut self,
+        forwarder_id: AccountId,
+        token_id: AccountId,
+        receiver_id: AccountId,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .sweep(token_id, receiver_id)
+    }
+
+    /// Transfer the NFT held by a specific forwarder to the receiver, e.g. if it has been sent
+    /// without the call, so the forwarder hasn't transferred it to the NFT bridge.
+    #[private]
+    pub fn sweep_forwarder_nft(
+        &mut self,
+        forwarder_id: AccountId,
+        nft_contract_id: AccountId,
+        token_id: String,
+        receiver_id: AccountId,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .sweep_nft(nft_contract_id, token_id, receiver_id)
+    }
+
+    /// Set the keepers allowed to forward the tokens of a specific forwarder besides
+    /// the factory. The empty list allows anyone to forward.
+    #[private]
+    pub fn set_forwarder_keepers(
+        &mut self,
+        forwarder_id: AccountId,
+        keepers: Vec<AccountId>,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).set_keepers(keepers)
+    }
+
+    /// Blacklist the token on a specific forwarder, so the forwarder refuses and doesn't
+    /// forward it.
+    #[private]
+    pub fn blacklist_forwarder_token(
+        &mut self,
+        forwarder_id: AccountId,
+        token_id: AccountId,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).blacklist_token(token_id)
+    }
+
+    /// Remove the token from the blacklist of a specific forwarder.
+    #[private]
+    pub fn unblacklist_forwarder_token(
+        &mut self,
+        forwarder_id: AccountId,
+        token_id: AccountId,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).unblacklist_token(token_id)
+    }
+
+    /// Track the token on a specific forwarder, so its balance is forwarded by the `forward_all`.
+    #[private]
+    pub fn track_forwarder_token(
+        &mut self,
+        forwarder_id: AccountId,
+        token_id: AccountId,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).track_token(token_id)
+    }
+
+    /// Stop tracking the token on a specific forwarder, which should hold none of it.
+    #[private]
+    pub fn untrack_forwarder_token(
+        &mut self,
+        forwarder_id: AccountId,
+        token_id: AccountId,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id).untrack_token(token_id)
+    }
+
+    /// Pause the forwarding of a specific forwarder. The forwarder still accepts the tokens.
+    #[private]
+    pub fn pause_forwarder(&mut self, forwarder_id: AccountId) -> Promise {
+        ext_forwarder::ext(forwarder_id).pause()
+    }
+
+    /// Resume the forwarding of a specific forwarder.
+    #[private]
+    pub fn unpause_forwarder(&mut self, forwarder_id: AccountId) -> Promise {
+        ext_forwarder::ext(forwarder_id).unpause()
+    }
+
     /// Set new fees contract id.
     #[private]
     pub fn set_fees_contract_id(&mut self, fees_contract_id: AccountId) {
@@ -111,6 +482,42 @@ impl AuroraForwarderFactory {
         &self.fees_contract_id
     }
 
+    /// Close forwarder after unregistering its storage on the tracked tokens, which it should
+    /// hold none of. Every call processes the next page of the tracked tokens, so it's repeated
+    /// until the forwarder is deleted. The remaining NEAR is refunded to the factory.
+    #[private]
+    pub fn close_forwarder(&mut self, account_id: AccountId) -> Promise {
+        ext_forwarder::ext(account_id)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .close()
+    }
+
+    /// Unregister the storage of a specific forwarder on the tokens, so the storage deposits
+    /// are refunded to the forwarder. `force` burns the balances of the forwarder on them.
+    #[private]
+    pub fn unregister_forwarder_storage(
+        &mut self,
+        forwarder_id: AccountId,
+        token_ids: Vec<AccountId>,
+        force: bool,
+    ) -> Promise {
+        ext_forwarder::ext(forwarder_id)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .unregister_storage(token_ids, force)
+    }
+
+    /// Migrate the state of a specific forwarder right after its code has been updated.
+    #[private]
+    pub fn migrate_forwarder(&mut self, forwarder_id: AccountId) -> Promise {
+        ext_forwarder::ext(forwarder_id).migrate()
+    }
+
+    /// Return the NEAR held by a specific forwarder above its required reserve to the factory.
+    #[private]
+    pub fn skim_forwarder_near(&mut self, forwarder_id: AccountId) -> Promise {
+        ext_forwarder::ext(forwarder_id).skim_excess_near()
+    }
+
     /// Destroy forwarder.
     #[private]
     pub fn destroy_forwarder(&mut self, account_id: AccountId) -> Promise {
@@ -126,7 +533,74 @@ pub trait ExtToken {
 #[ext_contract(ext_forwarder)]
 pub trait ExtForwarder {
     fn forward(&self, #[serializer(borsh)] token_id: AccountId);
+    fn forward_near(&self);
+    fn forward_batch(&self, #[serializer(borsh)] token_ids: Vec<AccountId>);
+    fn set_target_address(&self, #[serializer(borsh)] target_address: String);
+    fn set_target_network(&self, #[serializer(borsh)] target_network: AccountId);
+    fn set_min_forward_amount(
+        &self,
+        #[serializer(borsh)] token_id: AccountId,
+        #[serializer(borsh)] amount: u128,
+    );
+    fn set_max_forward_amount(
+        &self,
+        #[serializer(borsh)] token_id: AccountId,
+        #[serializer(borsh)] amount: u128,
+    );
+    fn set_schedule(
+        &self,
+        #[serializer(borsh)] token_id: AccountId,
+        #[serializer(borsh)] interval_hours: u64,
+    );
+    fn set_gas_config(
+        &self,
+        #[serializer(borsh)] calculate_fees: u64,
+        #[serializer(borsh)] ft_transfer: u64,
+        #[serializer(borsh)] ft_transfer_call: u64,
+    );
+    fn set_splits(&self, #[serializer(borsh)] splits: Vec<(Address, u16)>);
+    fn set_static_fee(&self, #[serializer(borsh)] fee: Option<(u16, AccountId)>);
+    fn set_fee_cache_ttl(&self, #[serializer(borsh)] ttl: u64);
+    fn set_fallback_fees_contract(&self, #[serializer(borsh)] fees_contract_id: Option<AccountId>);
+    fn set_fallback_fee(&self, #[serializer(borsh)] fee: Option<(u16, AccountId)>);
+    fn set_rate_limit(&self, #[serializer(borsh)] rate_limit: Option<(u32, u64)>);
+    fn set_near_base_token(
+        &self,
+        #[serializer(borsh)] target_network: AccountId,
+        #[serializer(borsh)] is_enabled: bool,
+    );
+    fn set_msg_format(
+        &self,
+        #[serializer(borsh)] target_network: AccountId,
+        #[serializer(borsh)] format: MsgFormat,
+    );
+    fn sweep(
+        &self,
+        #[serializer(borsh)] token_id: AccountId,
+        #[serializer(borsh)] receiver_id: AccountId,
+    );
+    fn sweep_nft(&self, nft_contract_id: AccountId, token_id: String, receiver_id: AccountId);
+    fn set_near_receiver(&self, #[serializer(borsh)] receiver_id: Option<AccountId>);
+    fn set_eth_bridge(&self, #[serializer(borsh)] bridge_id: Option<AccountId>);
+    fn set_withdraw_beneficiary(&self, #[serializer(borsh)] beneficiary_id: Option<AccountId>);
+    fn set_xcc_payload(&self, #[serializer(borsh)] payload: Option<Vec<u8>>);
+    fn set_keepers(&self, #[serializer(borsh)] keepers: Vec<AccountId>);
+    fn blacklist_token(&self, #[serializer(borsh)] token_id: AccountId);
+    fn unblacklist_token(&self, #[serializer(borsh)] token_id: AccountId);
+    fn track_token(&self, #[serializer(borsh)] token_id: AccountId);
+    fn untrack_token(&self, #[serializer(borsh)] token_id: AccountId);
+    fn pause(&self);
+    fn unpause(&self);
+    fn set_nft_bridge(&self, #[serializer(borsh)] nft_bridge_id: Option<AccountId>);
+    fn close(&self);
+    fn unregister_storage(
+        &self,
+        #[serializer(borsh)] token_ids: Vec<AccountId>,
+        #[serializer(borsh)] force: bool,
+    );
     fn destroy(&self);
+    fn migrate(&self);
+    fn skim_excess_near(&self);
 }
 
 #[derive(Deserialize, Serialize)]
@@ -137,6 +611,45 @@ pub struct DeployParameters {
     pub wnear_contract_id: AccountId,
 }
 
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StaticFeeParameters {
+    /// The fee in basis points of the forwarded amount.
+    pub fee_bps: u16,
+    pub fee_recipient: AccountId,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RateLimitParameters {
+    /// The maximum number of the forwardings of every token per the window.
+    pub max_forwards: u32,
+    pub window_secs: U64,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SplitParameters {
+    pub address: String,
+    /// The weight of the split in basis points.
+    pub weight: u16,
+}
+
+/// The format of the `msg` of the `ft_transfer_call` to the target network.
+#[derive(Deserialize, Serialize, BorshSerialize)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum MsgFormat {
+    /// The hex of the target address.
+    Address,
+    /// The hex of the target address and the fee separated by the colon.
+    AddressFee,
+    /// The JSON object with the hex of the target address in the `recipient` field.
+    Json,
+    /// The message of the eth-connector with the zero relayer fee.
+    EthConnector,
+}
+
 #[derive(BorshSerialize)]
 #[borsh(crate = "near_sdk::borsh")]
 pub struct ForwarderParameters<'a> {
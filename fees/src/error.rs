@@ -0,0 +1,50 @@
+use near_sdk::{AccountId, FunctionError};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::{ParseError, Role};
+
+/// The error of the methods of the fees contract, which return the structured failures
+/// instead of panicking. The transaction fails with the error message.
+#[derive(Debug, PartialEq, Eq, FunctionError)]
+#[allow(clippy::module_name_repetitions)]
+pub enum FeesError {
+    /// The invoker of the transaction is neither the owner nor a member of the role.
+    Unauthorized {
+        role: Role,
+        action: &'static str,
+    },
+    InvalidPercent(ParseError),
+    TokenAlreadyPresent,
+    TokenNotAdded(AccountId),
+    /// The change of the fee percent exceeds the max fee delta.
+    FeeDeltaExceeded(String),
+}
+
+impl Display for FeesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unauthorized { role, action } => {
+                let role = match role {
+                    Role::FeeSetter => "fee setter",
+                    Role::TokenManager => "token manager",
+                    Role::Factory => "factory",
+                };
+                write!(f, "Only owner or {role} can {action}")
+            }
+            Self::InvalidPercent(error) => write!(f, "Couldn't parse percent: {error}"),
+            Self::TokenAlreadyPresent => f.write_str("Token is already present"),
+            Self::TokenNotAdded(token_id) => {
+                write!(f, "Nothing to remove, token: {token_id} hasn't been added")
+            }
+            Self::FeeDeltaExceeded(max_delta) => {
+                write!(
+                    f,
+                    "Fee change exceeds the max delta of {max_delta} percentage points"
+                )
+            }
+        }
+    }
+}
+
+impl Error for FeesError {}
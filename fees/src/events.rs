@@ -0,0 +1,212 @@
+use near_sdk::json_types::{U128, U64};
+use near_sdk::{near_bindgen, AccountId};
+
+use crate::multisig::Multisig;
+use crate::{FeeTier, Role, RoundingMode};
+
+/// NEP-297 events which are emitted on changes of the fees configuration.
+#[near_bindgen(event_json(standard = "aurora-forwarder-fees"))]
+pub enum FeesEvent<'a> {
+    #[event_version("1.0.0")]
+    SetFeePercent { percent: Option<String> },
+    #[event_version("1.0.0")]
+    ScheduleFeeChange {
+        percent: Option<String>,
+        effective_at: U64,
+    },
+    #[event_version("1.0.0")]
+    CancelFeeChange,
+    #[event_version("1.0.0")]
+    SetTokenFee {
+        token_id: &'a AccountId,
+        percent: Option<String>,
+    },
+    #[event_version("1.0.0")]
+    RemoveTokenFee { token_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    SetTokenFeeTiers {
+        token_id: &'a AccountId,
+        tiers: &'a [FeeTier],
+    },
+    #[event_version("1.0.0")]
+    RemoveTokenFeeTiers { token_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    SetTokenFlatFee {
+        token_id: &'a AccountId,
+        fee: Option<U128>,
+    },
+    #[event_version("1.0.0")]
+    SetMinAmount {
+        network_id: &'a AccountId,
+        token_id: &'a AccountId,
+        amount: Option<U128>,
+    },
+    #[event_version("1.0.0")]
+    SetMaxAmount {
+        token_id: &'a AccountId,
+        amount: Option<U128>,
+    },
+    #[event_version("1.0.0")]
+    SetUsdFlatFee { fee: Option<U128> },
+    #[event_version("1.0.0")]
+    SetPriceOracle { oracle_id: &'a Option<AccountId> },
+    #[event_version("1.0.0")]
+    SetMaxPriceAge { max_age: u64 },
+    #[event_version("1.0.0")]
+    SetFeeIncreaseDelay { delay: u64 },
+    #[event_version("1.0.0")]
+    SetMaxFeeDelta { delta: Option<String> },
+    #[event_version("1.0.0")]
+    SetFeeRecipient {
+        token_id: &'a AccountId,
+        recipient_id: &'a Option<AccountId>,
+    },
+    #[event_version("1.0.0")]
+    SetRoundingMode { mode: RoundingMode },
+    #[event_version("1.0.0")]
+    SetDustThreshold {
+        token_id: &'a AccountId,
+        threshold: Option<U128>,
+    },
+    #[event_version("1.0.0")]
+    SetNativeFee { percent: Option<String> },
+    #[event_version("1.0.0")]
+    SetUnlistedTokenFee { percent: Option<String> },
+    #[event_version("1.0.0")]
+    SetNativeFlatFee { fee: Option<U128> },
+    #[event_version("1.0.0")]
+    SetNearFee { fee: Option<U128> },
+    #[event_version("1.0.0")]
+    SetTokenFeeCaps {
+        token_id: &'a AccountId,
+        min: Option<U128>,
+        max: Option<U128>,
+    },
+    #[event_version("1.0.0")]
+    SetUnbridgedSurcharge {
+        token_id: &'a AccountId,
+        surcharge: Option<U128>,
+    },
+    #[event_version("1.0.0")]
+    SetTokenBridged {
+        token_id: &'a AccountId,
+        network_id: &'a AccountId,
+        is_bridged: bool,
+    },
+    #[event_version("1.0.0")]
+    SetPromotion {
+        token_id: &'a AccountId,
+        start: U64,
+        end: U64,
+    },
+    #[event_version("1.0.0")]
+    RemovePromotion { token_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    SetNetworkFee {
+        network_id: &'a AccountId,
+        percent: Option<String>,
+    },
+    #[event_version("1.0.0")]
+    RemoveNetworkFee { network_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    SetAccountFee {
+        account_id: &'a AccountId,
+        percent: Option<String>,
+    },
+    #[event_version("1.0.0")]
+    RemoveAccountFee { account_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    AddSupportedNetwork { network_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    RemoveSupportedNetwork { network_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    AddSupportedToken { token_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    SetListingBond { bond: Option<U128> },
+    #[event_version("1.0.0")]
+    RegisterToken {
+        token_id: &'a AccountId,
+        account_id: &'a AccountId,
+        bond: U128,
+    },
+    #[event_version("1.0.0")]
+    ApproveToken { token_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    RejectToken {
+        token_id: &'a AccountId,
+        slash: bool,
+    },
+    #[event_version("1.0.0")]
+    RemoveSupportedToken { token_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    AddNetworkSupportedToken {
+        network_id: &'a AccountId,
+        token_id: &'a AccountId,
+    },
+    #[event_version("1.0.0")]
+    RemoveNetworkSupportedToken {
+        network_id: &'a AccountId,
+        token_id: &'a AccountId,
+    },
+    #[event_version("1.0.0")]
+    RemoveNetworkSupportedTokens { network_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    SuspendToken { token_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    ResumeToken { token_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    SetFreeForwards { free_forwards: u64 },
+    #[event_version("1.0.0")]
+    AddExemptAddress { address: String },
+    #[event_version("1.0.0")]
+    RemoveExemptAddress { address: String },
+    #[event_version("1.0.0")]
+    SetReferralShare { share: U64 },
+    #[event_version("1.0.0")]
+    AddReferrer { account_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    RemoveReferrer { account_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    Pause,
+    #[event_version("1.0.0")]
+    Unpause,
+    #[event_version("1.0.0")]
+    ProposeOwner { owner_id: Option<AccountId> },
+    #[event_version("1.0.0")]
+    TransferOwnership {
+        old_owner_id: &'a AccountId,
+        new_owner_id: &'a AccountId,
+    },
+    #[event_version("1.0.0")]
+    GrantRole {
+        role: Role,
+        account_id: &'a AccountId,
+    },
+    #[event_version("1.0.0")]
+    RevokeRole {
+        role: Role,
+        account_id: &'a AccountId,
+    },
+    #[event_version("1.0.0")]
+    DelegateFeeSetter {
+        account_id: &'a AccountId,
+        expires_at: U64,
+    },
+    #[event_version("1.0.0")]
+    RevokeFeeDelegation { account_id: &'a AccountId },
+    #[event_version("1.0.0")]
+    SetMultisig { multisig: &'a Option<Multisig> },
+    #[event_version("1.0.0")]
+    Propose {
+        proposal_id: u64,
+        method: &'a str,
+        proposer: &'a AccountId,
+    },
+    #[event_version("1.0.0")]
+    ConfirmProposal {
+        proposal_id: u64,
+        account_id: &'a AccountId,
+    },
+    #[event_version("1.0.0")]
+    ExecuteProposal { proposal_id: u64 },
+}
@@ -1,25 +1,108 @@
 use aurora_engine_types::types::Address;
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk::json_types::{U128, U64};
-use near_sdk::{env, near_bindgen, AccountId, IntoStorageKey, PanicOnDefault};
-use std::collections::BTreeSet;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::{LookupMap, LookupSet, Vector};
+use near_sdk::{
+    env, ext_contract, near_bindgen, serde_json, AccountId, Gas, IntoStorageKey, NearToken,
+    PanicOnDefault, Promise, PromiseError,
+};
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::num::ParseFloatError;
 use std::str::FromStr;
 
-// We multiply percents to 100 here to get rid of the floating numbers.
-const MIN_FEE_PERCENT: u64 = 1; // 0.01 %
-const MAX_FEE_PERCENT: u64 = 1000; // 10 %
-const DEFAULT_PERCENT: U64 = U64(500); // 5%
+use crate::error::FeesError;
+use crate::events::FeesEvent;
+use crate::migration::StateVersion;
+use crate::multisig::{Multisig, Proposal, PROPOSABLE_METHODS};
+use crate::oracle::{ext_oracle, PriceData, TokenPrice};
+use crate::storage::{Statistics, SupportedTokens};
 
-#[near_bindgen]
+mod error;
+mod events;
+mod migration;
+mod multisig;
+mod oracle;
+mod storage;
+
+// We multiply percents to 10000 here to get rid of the floating numbers, so the unit of
+// the percent is 1/100 of a basis point.
+const MIN_FEE_PERCENT: u64 = 1; // 0.0001 %
+const MAX_FEE_PERCENT: u64 = 100_000; // 10 %
+const MAX_PERCENT: u128 = 1_000_000; // 100 %
+const PERCENT_PER_BPS: u64 = 100;
+const MAX_BPS: u64 = 10000; // 100 %
+const DEFAULT_PERCENT: U64 = U64(50_000); // 5%
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+const FT_METADATA_GAS: Gas = Gas::from_tgas(5);
+const ON_FT_METADATA_GAS: Gas = Gas::from_tgas(5);
+const GET_PRICE_DATA_GAS: Gas = Gas::from_tgas(10);
+const ON_PRICE_DATA_GAS: Gas = Gas::from_tgas(10);
+const DEFAULT_MAX_PRICE_AGE: u64 = 90; // seconds
+const EXECUTE_PROPOSAL_GAS: Gas = Gas::from_tgas(50);
+
+// The `contract_source_metadata` of the NEP-330 is generated by the SDK with the version and
+// the repository of the package.
+#[near_bindgen(contract_metadata(
+    standard(standard = "nep330", version = "1.1.0"),
+    standard(standard = "nep297", version = "1.0.0"),
+))]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 #[borsh(crate = "near_sdk::borsh")]
 pub struct FeesCalculator {
+    version: StateVersion,
     percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    fee_increase_delay: u64,
+    max_fee_delta: Option<U64>,
+    paused: bool,
     owner: AccountId,
-    supported_tokens: BTreeSet<AccountId>,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    fee_delegations: BTreeMap<AccountId, U64>,
+    multisig: Option<Multisig>,
+    proposals: BTreeMap<u64, Proposal>,
+    next_proposal_id: u64,
+    supported_tokens: SupportedTokens,
+    token_metadata: LookupMap<AccountId, TokenMetadata>,
+    listing_bond: Option<U128>,
+    token_registrations: BTreeMap<AccountId, TokenRegistration>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    supported_networks: BTreeSet<AccountId>,
+    token_fees: LookupMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    account_fees: BTreeMap<AccountId, Option<U64>>,
+    unlisted_token_percent: Option<U64>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    near_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    max_amounts: BTreeMap<AccountId, U128>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: LookupMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: LookupMap<AccountId, Vec<Tier>>,
+    token_flat_fees: LookupMap<AccountId, U128>,
+    token_fee_caps: LookupMap<AccountId, FeeCaps>,
+    dust_thresholds: LookupMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: LookupMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
 }
 
 #[near_bindgen]
@@ -33,18 +116,88 @@ impl FeesCalculator {
     #[must_use]
     #[allow(clippy::use_self)]
     pub fn new(tokens: Vec<AccountId>) -> Self {
+        let mut supported_tokens = SupportedTokens::new();
+        supported_tokens.extend(tokens);
+
         Self {
+            version: StateVersion::LATEST,
             percent: Some(DEFAULT_PERCENT),
+            pending_fee_change: None,
+            fee_increase_delay: 0,
+            max_fee_delta: None,
+            paused: false,
             owner: env::predecessor_account_id(),
-            supported_tokens: tokens.into_iter().collect(),
+            proposed_owner: None,
+            roles: BTreeMap::new(),
+            fee_delegations: BTreeMap::new(),
+            multisig: None,
+            proposals: BTreeMap::new(),
+            next_proposal_id: 0,
+            supported_tokens,
+            token_metadata: LookupMap::new(KeyPrefix::TokenMetadata),
+            listing_bond: None,
+            token_registrations: BTreeMap::new(),
+            suspended_tokens: BTreeSet::new(),
+            network_supported_tokens: BTreeMap::new(),
+            supported_networks: BTreeSet::new(),
+            token_fees: LookupMap::new(KeyPrefix::TokenFees),
+            network_fees: BTreeMap::new(),
+            account_fees: BTreeMap::new(),
+            unlisted_token_percent: None,
+            native_percent: Some(DEFAULT_PERCENT),
+            native_flat_fee: None,
+            near_fee: None,
+            min_amounts: BTreeMap::new(),
+            max_amounts: BTreeMap::new(),
+            price_oracle: None,
+            max_price_age: DEFAULT_MAX_PRICE_AGE,
+            token_prices: LookupMap::new(KeyPrefix::TokenPrices),
+            usd_flat_fee: None,
+            rounding_mode: RoundingMode::Floor,
+            fee_recipients: BTreeMap::new(),
+            token_stats: Statistics::new(KeyPrefix::TokenStats, KeyPrefix::TokenStatsList),
+            network_stats: Statistics::new(KeyPrefix::NetworkStats, KeyPrefix::NetworkStatsList),
+            token_fee_tiers: LookupMap::new(KeyPrefix::TokenFeeTiers),
+            token_flat_fees: LookupMap::new(KeyPrefix::TokenFlatFees),
+            token_fee_caps: LookupMap::new(KeyPrefix::TokenFeeCaps),
+            dust_thresholds: LookupMap::new(KeyPrefix::DustThresholds),
+            promotions: BTreeMap::new(),
+            unbridged_surcharges: LookupMap::new(KeyPrefix::UnbridgedSurcharges),
+            charged_surcharges: LookupSet::new(KeyPrefix::ChargedSurcharges),
+            exempt_addresses: BTreeSet::new(),
+            free_forwards: 0,
+            forwards_count: LookupMap::new(KeyPrefix::ForwardsCount),
+            referrers: BTreeSet::new(),
+            referral_share: 0,
+            fee_history: Vector::new(KeyPrefix::FeeHistory),
         }
     }
 
-    /// Calculate and return the fee for the corresponding token and Aurora Network.
+    /// Migrate the state of the contract from any of the previous versions to the latest one.
+    /// The state of the latest version is left as is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state doesn't exist or has an unknown layout.
+    #[private]
+    #[init(ignore_state)]
+    #[must_use]
+    #[allow(clippy::use_self)]
+    pub fn migrate() -> Self {
+        migration::migrate()
+    }
+
+    /// Calculate and return the fee for the corresponding token and Aurora Network along with
+    /// the amount left after the fee, the recipient of the fee and the applied rule.
+    /// The fee consists of the percentage part, clamped by the caps of the token,
+    /// and the flat part of the token and never exceeds the amount. The fee is zero for
+    /// the exempt target addresses and while the forwarder has free forwards left. The free
+    /// forwards are consumed only by the `record_and_calculate_fees`, so the calculation
+    /// doesn't change the state.
     ///
     /// # Panics
     ///
-    /// There is a safe unwrap because we return 0 if the percent is None.
+    /// Panics if the contract is paused, so the forwarding doesn't proceed.
     #[must_use]
     #[result_serializer(borsh)]
     pub fn calculate_fees(
@@ -53,290 +206,6399 @@ impl FeesCalculator {
         #[serializer(borsh)] token_id: &AccountId,
         #[serializer(borsh)] target_network: &AccountId,
         #[serializer(borsh)] target_address: Address,
-    ) -> U128 {
-        let _ = (target_network, target_address);
+    ) -> FeeResult {
+        assert!(!self.paused, "Fees contract is paused");
 
-        if self.percent.is_none() || !self.supported_tokens.contains(token_id) {
-            0.into()
-        } else {
-            let fee = u128::from(self.percent.unwrap().0)
-                .checked_mul(amount.0)
-                .unwrap_or_default()
-                .saturating_div(10000);
-
-            // if the fee was computed to `0`
-            // i.e. because the amount was too small
-            // we substitute it with our minimal possible fee, which is `1`
-            if fee == 0 {
-                1.into()
-            } else {
-                fee.into()
-            }
-        }
+        let account_id = env::predecessor_account_id();
+        let result = self.quote(
+            amount,
+            token_id,
+            target_network,
+            target_address,
+            Some(&account_id),
+        );
+
+        self.free_forward_result(result, amount, &account_id)
     }
 
-    /// Set the percent of the fee.
+    /// Calculate the fee the same way as `calculate_fees` does and add the forwarded amount and
+    /// the fee to the statistics of the token and the network. The rejected amounts aren't
+    /// recorded. The unbridged surcharge of the token is added to the fee of the first forward
+    /// to every network, which the token hasn't been marked as bridged to by the
+    /// `set_token_bridged`.
+    ///
+    /// If the NEAR fee is set and the attached deposit covers it, the fee is paid by
+    /// the deposit instead of the forwarded tokens, so the whole amount is forwarded.
+    /// The rest of the deposit is refunded to the forwarder.
     ///
     /// # Panics
     ///
-    /// Panics if the invoker of the transaction is not owner.
-    #[allow(clippy::needless_pass_by_value)]
-    pub fn set_fee_percent(&mut self, percent: Option<String>) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.owner,
-            "Only owner can set fee percent"
-        );
+    /// Panics if the contract is paused, so the forwarding doesn't proceed, or the invoker
+    /// isn't a forwarder deployed by a factory with the `Factory` role.
+    #[must_use]
+    #[payable]
+    #[result_serializer(borsh)]
+    pub fn record_and_calculate_fees(
+        &mut self,
+        #[serializer(borsh)] amount: U128,
+        #[serializer(borsh)] token_id: &AccountId,
+        #[serializer(borsh)] target_network: &AccountId,
+        #[serializer(borsh)] target_address: Address,
+    ) -> FeeResult {
+        self.assert_known_forwarder();
+        let result = self.fee_result(amount, token_id, target_network, target_address);
+        let mut refund = env::attached_deposit().as_yoctonear();
 
-        match parse_percent(percent.as_deref()) {
-            Ok(value) => self.percent = value,
-            Err(e) => env::panic_str(&format!("Couldn't parse percent: {e}")),
+        let mut result = match self.near_fee {
+            Some(U128(near_fee)) if !result.rejected && result.fee.0 > 0 && refund >= near_fee => {
+                refund -= near_fee;
+                FeeResult::new(0, amount, FeeRule::NearFee, result.fee_recipient)
+            }
+            _ => result,
+        };
+
+        if !result.rejected {
+            self.token_stats.record(token_id, amount.0, result.fee.0);
+            self.network_stats
+                .record(target_network, amount.0, result.fee.0);
+            result.percent = self.cacheable_percent(&result, amount, token_id, target_network);
         }
+
+        if refund > 0 {
+            let _ = Promise::new(env::predecessor_account_id())
+                .transfer(NearToken::from_yoctonear(refund));
+        }
+
+        result
     }
 
-    /// Returns current fee percent.
+    /// Return a page of the forwarding statistics of the tokens.
     #[must_use]
-    #[allow(clippy::cast_precision_loss)]
-    pub fn get_fee_percent(&self) -> Option<String> {
-        self.percent
-            .map(|U64(v)| format!("{:.2}", v as f64 / 100.0))
+    pub fn token_stats(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(&AccountId, &Stats)> {
+        paginate(self.token_stats.iter(), from_index, limit).collect()
     }
 
-    /// Return a list of supported tokens.
+    /// Return the number of the tokens with the forwarding statistics.
     #[must_use]
-    pub fn supported_tokens(&self) -> Vec<&AccountId> {
-        self.supported_tokens.iter().collect()
+    pub fn token_stats_count(&self) -> u64 {
+        self.token_stats.len()
+    }
+
+    /// Returns the forwarding statistics of the token.
+    #[must_use]
+    pub fn get_token_stats(&self, token_id: &AccountId) -> Option<&Stats> {
+        self.token_stats.get(token_id)
     }
 
-    /// Add an account id of a new supported NEP-141 token.
+    /// Return a page of the forwarding statistics of the networks.
+    #[must_use]
+    pub fn network_stats(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(&AccountId, &Stats)> {
+        paginate(self.network_stats.iter(), from_index, limit).collect()
+    }
+
+    /// Return the number of the networks with the forwarding statistics.
+    #[must_use]
+    pub fn network_stats_count(&self) -> u64 {
+        self.network_stats.len()
+    }
+
+    /// Returns the forwarding statistics of the network.
+    #[must_use]
+    pub fn get_network_stats(&self, network_id: &AccountId) -> Option<&Stats> {
+        self.network_stats.get(network_id)
+    }
+
+    /// Calculate and return the fee for the native NEAR amount in yoctoNEAR. The fee consists
+    /// of the native percentage part and the native flat part and never exceeds the amount.
+    /// The fee is zero for the exempt target addresses and for the free forwards the same way
+    /// as in `calculate_fees`, so the calculation doesn't change the state.
     ///
     /// # Panics
     ///
-    /// Panic if the added token is already exist.
-    pub fn add_supported_token(&mut self, token_id: AccountId) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.owner,
-            "Only owner can add token"
-        );
-        assert!(
-            self.supported_tokens.insert(token_id),
-            "Token is already present"
-        );
+    /// Panics if the contract is paused, so the forwarding doesn't proceed.
+    #[must_use]
+    #[result_serializer(borsh)]
+    pub fn calculate_native_fees(
+        &self,
+        #[serializer(borsh)] amount: U128,
+        #[serializer(borsh)] target_address: Address,
+    ) -> FeeResult {
+        assert!(!self.paused, "Fees contract is paused");
+
+        if !is_valid_address(target_address) {
+            return FeeResult::invalid_address(amount);
+        }
+
+        let (fee, rule) = self.native_fee(amount, target_address);
+        let result = FeeResult::new(fee, amount, rule, env::current_account_id());
+
+        self.free_forward_result(result, amount, &env::predecessor_account_id())
     }
 
-    /// Remove the token from the list of supported.
+    /// Calculate the fee for the native NEAR the same way as `calculate_native_fees` does and
+    /// consume the free forward of the forwarder if the fee has been waived by it.
     ///
     /// # Panics
     ///
-    /// Panics if the removed token is not exists.
-    pub fn remove_supported_token(&mut self, token_id: &AccountId) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.owner,
-            "Only owner can remove token"
-        );
-        assert!(
-            self.supported_tokens.remove(token_id),
-            "Nothing to remove, token: {token_id} hasn't been added"
-        );
-    }
-}
-
-#[derive(BorshDeserialize, BorshSerialize)]
-#[borsh(crate = "near_sdk::borsh")]
-enum KeyPrefix {
-    SupportedTokens,
-}
+    /// Panics if the contract is paused, so the forwarding doesn't proceed, or the invoker
+    /// isn't a forwarder deployed by a factory with the `Factory` role.
+    #[must_use]
+    #[result_serializer(borsh)]
+    pub fn record_and_calculate_native_fees(
+        &mut self,
+        #[serializer(borsh)] amount: U128,
+        #[serializer(borsh)] target_address: Address,
+    ) -> FeeResult {
+        assert!(!self.paused, "Fees contract is paused");
+        self.assert_known_forwarder();
 
-impl IntoStorageKey for KeyPrefix {
-    fn into_storage_key(self) -> Vec<u8> {
-        match self {
-            Self::SupportedTokens => b"supported_tokens".to_vec(),
+        if !is_valid_address(target_address) {
+            return FeeResult::invalid_address(amount);
         }
-    }
-}
 
-#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-fn parse_percent(percent: Option<&str>) -> Result<Option<U64>, ParseError> {
-    let Some(percent) = percent else {
-        return Ok(None);
-    };
+        let (fee, rule) = self.native_fee(amount, target_address);
+        let (fee, rule) = if fee > 0 && self.use_free_forward() {
+            (0, FeeRule::FreeForward)
+        } else {
+            (fee, rule)
+        };
 
-    validate_decimal_part(percent)?;
+        FeeResult::new(fee, amount, rule, env::current_account_id())
+    }
 
-    let result = f64::from_str(percent)
-        .map(|p| (p * 100.0) as u64) // as conversion is safe here because we validate the number of decimals
-        .map_err(ParseError::ParseFloat)?;
+    /// Set the default fee percent for the tokens, which aren't supported. `None` or `"0"`
+    /// disables the fee, so the unsupported tokens are forwarded without fee.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter, or the percent is
+    /// invalid.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_unlisted_token_fee(&mut self, percent: Option<String>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set unlisted token fee"
+        );
 
-    if result == 0 {
-        Ok(None)
-    } else if result < MIN_FEE_PERCENT {
-        Err(ParseError::TooLowPercent)
-    } else if result > MAX_FEE_PERCENT {
-        Err(ParseError::TooHighPercent)
-    } else {
-        Ok(Some(U64(result)))
+        match parse_percent(percent.as_deref()) {
+            Ok(value) => {
+                self.log_fee_change(
+                    FeeParameter::UnlistedTokenFee,
+                    None,
+                    Some(percent_value(self.unlisted_token_percent)),
+                    Some(percent_value(value)),
+                );
+                self.unlisted_token_percent = value;
+                FeesEvent::SetUnlistedTokenFee {
+                    percent: value.map(format_percent),
+                }
+                .emit();
+            }
+            Err(e) => env::panic_str(&format!("Couldn't parse percent: {e}")),
+        }
     }
-}
 
-#[derive(Debug)]
-enum ParseError {
-    ParseFloat(ParseFloatError),
-    TooLowPercent,
-    TooHighPercent,
-    TooManyDecimals,
-}
+    /// Returns the default fee percent for the tokens, which aren't supported.
+    #[must_use]
+    pub fn get_unlisted_token_fee(&self) -> Option<String> {
+        self.unlisted_token_percent.map(format_percent)
+    }
 
-impl Display for ParseError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        #[allow(deprecated)]
-        let msg = match self {
-            Self::ParseFloat(error) => error.description(),
-            Self::TooLowPercent => "provided percent is less than 0.01%",
-            Self::TooHighPercent => "provided percent is more than 10%",
-            Self::TooManyDecimals => "provided percent could contain only 2 decimals",
-        };
+    /// Set the fee percent for the native NEAR. `None` or `"0"` disables the percentage fee
+    /// for the native NEAR.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter, or the percent is
+    /// invalid.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_native_fee(&mut self, percent: Option<String>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set native fee"
+        );
 
-        f.write_str(msg)
+        match parse_percent(percent.as_deref()) {
+            Ok(value) => {
+                self.log_fee_change(
+                    FeeParameter::NativeFee,
+                    None,
+                    Some(percent_value(self.native_percent)),
+                    Some(percent_value(value)),
+                );
+                self.native_percent = value;
+                FeesEvent::SetNativeFee {
+                    percent: value.map(format_percent),
+                }
+                .emit();
+            }
+            Err(e) => env::panic_str(&format!("Couldn't parse percent: {e}")),
+        }
     }
-}
 
-fn validate_decimal_part(percent: &str) -> Result<(), ParseError> {
-    match percent.split_once('.') {
-        Some((_, decimal)) if decimal.len() > 2 => Err(ParseError::TooManyDecimals),
-        _ => Ok(()), // no decimals or the number of decimals is less or equal 2.
+    /// Returns the fee percent for the native NEAR.
+    #[must_use]
+    pub fn get_native_fee(&self) -> Option<String> {
+        self.native_percent.map(format_percent)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::{parse_percent, FeesCalculator, ParseError};
-    use aurora_engine_types::types::Address;
-    use near_sdk::AccountId;
 
-    #[test]
-    fn test_parse_percent() {
-        assert_eq!(parse_percent(None).unwrap(), None);
-        assert_eq!(parse_percent(Some("0")).unwrap(), None);
-        assert_eq!(parse_percent(Some("10")).unwrap(), Some(1000.into()));
-        assert_eq!(parse_percent(Some("2")).unwrap(), Some(200.into()));
-        assert_eq!(parse_percent(Some("0.25")).unwrap(), Some(25.into()));
-        assert_eq!(parse_percent(Some("0.01")).unwrap(), Some(1.into()));
-        assert!(matches!(
-            parse_percent(Some("0.015")).err(),
-            Some(ParseError::TooManyDecimals)
-        ));
-        assert!(matches!(
-            parse_percent(Some("0.009")).err(),
-            Some(ParseError::TooManyDecimals)
-        ));
-        assert!(matches!(
-            parse_percent(Some("10.1")).err(),
-            Some(ParseError::TooHighPercent)
-        ));
-        assert!(matches!(
-            parse_percent(Some("hello")).err(),
-            Some(ParseError::ParseFloat(_))
-        ));
-    }
+    /// Set the flat fee for the native NEAR in yoctoNEAR. The flat fee is added on top of
+    /// the percentage fee. `None` removes the flat fee.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter.
+    pub fn set_native_flat_fee(&mut self, fee: Option<U128>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set native flat fee"
+        );
 
-    #[test]
-    fn test_check_supported_tokens() {
-        let aurora = "aurora".parse().unwrap();
-        let target_address = Address::default();
-        let usdt = "usdt.near".parse().unwrap();
-        let mut contract = FeesCalculator::new(vec![]);
+        let fee = fee.filter(|fee| fee.0 > 0);
 
-        assert_eq!(
-            contract.calculate_fees(1000.into(), &usdt, &aurora, target_address),
-            0.into() // we don't support the `usdt.near` yet, so we get 0 here
+        self.log_fee_change(
+            FeeParameter::NativeFlatFee,
+            None,
+            self.native_flat_fee.map(|U128(fee)| fee.to_string()),
+            fee.map(|U128(fee)| fee.to_string()),
         );
+        self.native_flat_fee = fee;
+
+        FeesEvent::SetNativeFlatFee { fee }.emit();
+    }
 
-        contract.add_supported_token(usdt.clone());
+    /// Returns the flat fee for the native NEAR in yoctoNEAR.
+    #[must_use]
+    pub const fn get_native_flat_fee(&self) -> Option<U128> {
+        self.native_flat_fee
+    }
 
-        assert_eq!(
-            contract.calculate_fees(1000.into(), &usdt, &aurora, target_address),
-            50.into()
+    /// Set the fee in yoctoNEAR, which could be attached to the `forward` of the NEP-141 tokens
+    /// to pay the fee instead of deducting it from the forwarded amount. `None` disables
+    /// the payment of the fee in NEAR.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter.
+    pub fn set_near_fee(&mut self, fee: Option<U128>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set near fee"
         );
 
-        contract.remove_supported_token(&usdt);
+        let fee = fee.filter(|fee| fee.0 > 0);
 
-        assert_eq!(
-            contract.calculate_fees(1000.into(), &usdt, &aurora, target_address),
-            0.into()
+        self.log_fee_change(
+            FeeParameter::NearFee,
+            None,
+            self.near_fee.map(|U128(fee)| fee.to_string()),
+            fee.map(|U128(fee)| fee.to_string()),
         );
+        self.near_fee = fee;
+
+        FeesEvent::SetNearFee { fee }.emit();
     }
 
-    #[test]
-    fn test_fee_is_never_rounded_to_zero_for_small_numbers() {
-        let aurora = "aurora".parse().unwrap();
-        let target_address = Address::default();
-        let usdt: AccountId = "usdt.near".parse().unwrap();
-        let mut contract = FeesCalculator::new(vec![]);
-        contract.set_fee_percent(Some("5".to_string()));
+    /// Returns the fee in yoctoNEAR, which could be paid instead of the token fee.
+    #[must_use]
+    pub const fn get_near_fee(&self) -> Option<U128> {
+        self.near_fee
+    }
 
-        contract.add_supported_token(usdt.clone());
+    /// Calculate the fees for a list of `(amount, token_id, target_network, target_address)`
+    /// without changing the state, so the UIs could quote several transfers at once.
+    /// The free forwards and the unbridged surcharges aren't taken into account.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the contract is paused or any of the target addresses is invalid.
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn calculate_fees_batch(
+        &self,
+        quotes: Vec<(U128, AccountId, AccountId, String)>,
+    ) -> Vec<FeeResult> {
+        assert!(!self.paused, "Fees contract is paused");
 
-        assert_eq!(
-            contract.calculate_fees(1.into(), &usdt, &aurora, target_address),
-            1.into()
-        );
+        quotes
+            .iter()
+            .map(|(amount, token_id, target_network, target_address)| {
+                self.quote(
+                    *amount,
+                    token_id,
+                    target_network,
+                    parse_address(target_address),
+                    None,
+                )
+            })
+            .collect()
     }
 
-    #[test]
-    fn test_check_set_fee() {
-        let aurora = "aurora".parse().unwrap();
-        let target_address = Address::default();
-        let usdt: AccountId = "usdt.near".parse().unwrap();
-        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+    /// Calculate the fee the same way as `calculate_fees_batch` does for a single transfer and
+    /// return it along with the amounts formatted with the decimals of the token, e.g. `1.5`,
+    /// so the UIs don't need to request the metadata of the token. The amounts of the tokens
+    /// without the metadata are formatted as is. The fee percent of the `account_id`, which
+    /// requests the fees, e.g. the forwarder, is taken into account if it's provided.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the contract is paused or the target address is invalid.
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn quote_fees(
+        &self,
+        amount: U128,
+        token_id: &AccountId,
+        target_network: &AccountId,
+        target_address: String,
+        account_id: Option<AccountId>,
+    ) -> FeeQuote {
+        assert!(!self.paused, "Fees contract is paused");
 
-        assert_eq!(
-            contract.calculate_fees(1000.into(), &usdt, &aurora, target_address),
-            50.into()
+        let result = self.quote(
+            amount,
+            token_id,
+            target_network,
+            parse_address(&target_address),
+            account_id.as_ref(),
         );
+        let metadata = self.token_metadata.get(token_id);
+        let decimals = metadata.map_or(0, |metadata| metadata.decimals);
 
-        contract.set_fee_percent(Some("0".to_string()));
+        FeeQuote {
+            amount: format_amount(amount.0, decimals),
+            fee: format_amount(result.fee.0, decimals),
+            net_amount: format_amount(result.net_amount.0, decimals),
+            symbol: metadata.map(|metadata| metadata.symbol.clone()),
+            decimals: metadata.map(|metadata| metadata.decimals),
+            result,
+        }
+    }
 
-        assert_eq!(
-            contract.calculate_fees(1000.into(), &usdt, &aurora, target_address),
-            0.into()
+    /// Set the one-time surcharge for a specific token in the token units, which is added to
+    /// the fee of the first forward of the token to a network without the corresponding ERC-20.
+    /// `None` removes the surcharge of the token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter.
+    pub fn set_unbridged_surcharge(&mut self, token_id: AccountId, surcharge: Option<U128>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set unbridged surcharge"
         );
 
-        contract.set_fee_percent(Some("2.5".to_string()));
+        let surcharge = surcharge.filter(|surcharge| surcharge.0 > 0);
 
-        assert_eq!(
-            contract.calculate_fees(1000.into(), &usdt, &aurora, target_address),
-            25.into()
+        self.log_fee_change(
+            FeeParameter::UnbridgedSurcharge,
+            Some(&token_id),
+            self.unbridged_surcharges
+                .get(&token_id)
+                .map(|U128(surcharge)| surcharge.to_string()),
+            surcharge.map(|U128(surcharge)| surcharge.to_string()),
         );
-    }
+        FeesEvent::SetUnbridgedSurcharge {
+            token_id: &token_id,
+            surcharge,
+        }
+        .emit();
 
-    #[test]
-    fn test_set_percent() {
-        let mut contract = FeesCalculator::new(vec![]);
+        if let Some(surcharge) = surcharge {
+            self.unbridged_surcharges.insert(token_id, surcharge);
+        } else {
+            self.unbridged_surcharges.remove(&token_id);
+        }
+    }
 
-        assert_eq!(contract.get_fee_percent(), Some("5.00".to_string()));
-        contract.set_fee_percent(Some("6".to_string()));
-        assert_eq!(contract.get_fee_percent(), Some("6.00".to_string()));
-        contract.set_fee_percent(Some("7.5".to_string()));
-        assert_eq!(contract.get_fee_percent(), Some("7.50".to_string()));
-        contract.set_fee_percent(Some("0".to_string()));
-        assert_eq!(contract.get_fee_percent(), None);
+    /// Returns the one-time unbridged surcharge of the token.
+    #[must_use]
+    pub fn get_unbridged_surcharge(&self, token_id: &AccountId) -> Option<U128> {
+        self.unbridged_surcharges.get(token_id).copied()
+    }
+
+    /// Returns `true` if the unbridged surcharge of the token has already been charged for
+    /// the network or the token has been marked as bridged to it, so the surcharge isn't
+    /// charged anymore.
+    #[must_use]
+    pub fn is_unbridged_surcharge_charged(
+        &self,
+        token_id: &AccountId,
+        network_id: &AccountId,
+    ) -> bool {
+        self.charged_surcharges
+            .contains(&(network_id.clone(), token_id.clone()))
+    }
+
+    /// Mark the token as bridged to the network, i.e. it has the corresponding ERC-20 there,
+    /// so the unbridged surcharge isn't charged for the network. `false` makes the surcharge
+    /// chargeable again, e.g. if it has been charged by mistake.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter.
+    pub fn set_token_bridged(
+        &mut self,
+        token_id: AccountId,
+        network_id: AccountId,
+        is_bridged: bool,
+    ) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set token bridged"
+        );
+
+        FeesEvent::SetTokenBridged {
+            token_id: &token_id,
+            network_id: &network_id,
+            is_bridged,
+        }
+        .emit();
+
+        if is_bridged {
+            self.charged_surcharges.insert((network_id, token_id));
+        } else {
+            self.charged_surcharges.remove(&(network_id, token_id));
+        }
+    }
+
+    /// Set the number of the first fee calculations requested by every forwarder, which are
+    /// free of charge.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter.
+    pub fn set_free_forwards(&mut self, free_forwards: u64) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set free forwards"
+        );
+
+        self.free_forwards = free_forwards;
+        FeesEvent::SetFreeForwards { free_forwards }.emit();
+    }
+
+    /// Returns the number of the free fee calculations for every forwarder.
+    #[must_use]
+    pub const fn get_free_forwards(&self) -> u64 {
+        self.free_forwards
+    }
+
+    /// Returns the number of the free fee calculations left for the forwarder.
+    #[must_use]
+    pub fn remaining_free_forwards(&self, forwarder_id: &AccountId) -> u64 {
+        let used = self
+            .forwards_count
+            .get(forwarder_id)
+            .copied()
+            .unwrap_or_default();
+        self.free_forwards.saturating_sub(used)
+    }
+
+    /// Calculate the fee the same way as `calculate_fees` does and split it between
+    /// the treasury and the referrer. The referrer gets nothing if the `referral_id`
+    /// isn't registered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the contract is paused, so the forwarding doesn't proceed.
+    #[must_use]
+    #[result_serializer(borsh)]
+    pub fn calculate_fees_with_referral(
+        &mut self,
+        #[serializer(borsh)] amount: U128,
+        #[serializer(borsh)] token_id: &AccountId,
+        #[serializer(borsh)] target_network: &AccountId,
+        #[serializer(borsh)] target_address: Address,
+        #[serializer(borsh)] referral_id: Option<AccountId>,
+    ) -> FeeSplit {
+        let U128(fee) = self
+            .calculate_fees(amount, token_id, target_network, target_address)
+            .fee;
+        let referrer_id = referral_id.filter(|id| self.referrers.contains(id));
+        let referrer_fee = if referrer_id.is_some() {
+            calculate_share(fee, self.referral_share)
+        } else {
+            0
+        };
+
+        FeeSplit {
+            treasury: fee.saturating_sub(referrer_fee).into(),
+            referrer: referrer_fee.into(),
+            referrer_id,
+        }
+    }
+
+    /// Set the share of the fee in basis points which goes to the referrer,
+    /// e.g. 2000 means 20%.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter or if the share
+    /// is more than 100%.
+    pub fn set_referral_share(&mut self, share: U64) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set referral share"
+        );
+        assert!(share.0 <= MAX_BPS, "Referral share can't be more than 100%");
+
+        FeesEvent::SetReferralShare { share }.emit();
+        self.referral_share = share.0;
+    }
+
+    /// Return the share of the fee in basis points which goes to the referrer.
+    #[must_use]
+    pub const fn get_referral_share(&self) -> U64 {
+        U64(self.referral_share)
+    }
+
+    /// Register the referrer account. The account id is used as a referral id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the referrer is already
+    /// registered.
+    pub fn add_referrer(&mut self, account_id: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can add referrer"
+        );
+        assert!(
+            !self.referrers.contains(&account_id),
+            "Referrer is already registered"
+        );
+
+        FeesEvent::AddReferrer {
+            account_id: &account_id,
+        }
+        .emit();
+        self.referrers.insert(account_id);
+    }
+
+    /// Unregister the referrer account.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the referrer isn't registered.
+    pub fn remove_referrer(&mut self, account_id: &AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can remove referrer"
+        );
+        assert!(
+            self.referrers.remove(account_id),
+            "Nothing to remove, referrer: {account_id} hasn't been registered"
+        );
+
+        FeesEvent::RemoveReferrer { account_id }.emit();
+    }
+
+    /// Return a list of registered referrers.
+    #[must_use]
+    pub fn referrers(&self) -> Vec<&AccountId> {
+        self.referrers.iter().collect()
+    }
+
+    /// Pause the contract. While the contract is paused, `calculate_fees` panics.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the contract is already paused.
+    pub fn pause(&mut self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can pause the contract"
+        );
+        assert!(!self.paused, "Contract is already paused");
+
+        self.paused = true;
+        FeesEvent::Pause.emit();
+    }
+
+    /// Unpause the contract.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the contract is not paused.
+    pub fn unpause(&mut self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can unpause the contract"
+        );
+        assert!(self.paused, "Contract is not paused");
+
+        self.paused = false;
+        FeesEvent::Unpause.emit();
+    }
+
+    /// Returns `true` if the contract is paused.
+    #[must_use]
+    pub const fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Set the account which receives the fees of the token instead of the fees contract.
+    /// `None` removes the recipient of the token, so the fees go to the fees contract again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner.
+    pub fn set_fee_recipient(&mut self, token_id: AccountId, recipient_id: Option<AccountId>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set fee recipient"
+        );
+
+        FeesEvent::SetFeeRecipient {
+            token_id: &token_id,
+            recipient_id: &recipient_id,
+        }
+        .emit();
+
+        if let Some(recipient_id) = recipient_id {
+            self.fee_recipients.insert(token_id, recipient_id);
+        } else {
+            self.fee_recipients.remove(&token_id);
+        }
+    }
+
+    /// Returns the account which receives the fees of the token.
+    #[must_use]
+    pub fn get_fee_recipient(&self, token_id: &AccountId) -> AccountId {
+        self.fee_recipients
+            .get(token_id)
+            .cloned()
+            .unwrap_or_else(env::current_account_id)
+    }
+
+    /// Return a list of tokens with the overridden fee recipients.
+    #[must_use]
+    pub fn fee_recipients(&self) -> Vec<(&AccountId, &AccountId)> {
+        self.fee_recipients.iter().collect()
+    }
+
+    /// Set the rounding of the fee divisions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner.
+    pub fn set_rounding_mode(&mut self, mode: RoundingMode) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set rounding mode"
+        );
+
+        self.log_fee_change(
+            FeeParameter::RoundingMode,
+            None,
+            Some(self.rounding_mode.as_str().to_string()),
+            Some(mode.as_str().to_string()),
+        );
+        self.rounding_mode = mode;
+        FeesEvent::SetRoundingMode { mode }.emit();
+    }
+
+    /// Returns the rounding of the fee divisions.
+    #[must_use]
+    pub const fn get_rounding_mode(&self) -> RoundingMode {
+        self.rounding_mode
+    }
+
+    /// Propose a new owner of the contract. The ownership is transferred after the proposed
+    /// account accepts it. `None` cancels the current proposal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner.
+    pub fn propose_owner(&mut self, owner_id: Option<AccountId>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can propose a new owner"
+        );
+
+        FeesEvent::ProposeOwner {
+            owner_id: owner_id.clone(),
+        }
+        .emit();
+        self.proposed_owner = owner_id;
+    }
+
+    /// Accept the ownership of the contract.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not the proposed owner.
+    pub fn accept_ownership(&mut self) {
+        let predecessor_id = env::predecessor_account_id();
+
+        assert_eq!(
+            self.proposed_owner.as_ref(),
+            Some(&predecessor_id),
+            "Only proposed owner can accept ownership"
+        );
+
+        FeesEvent::TransferOwnership {
+            old_owner_id: &self.owner,
+            new_owner_id: &predecessor_id,
+        }
+        .emit();
+        self.owner = predecessor_id;
+        self.proposed_owner = None;
+    }
+
+    /// Returns the owner of the contract.
+    #[must_use]
+    pub const fn get_owner(&self) -> &AccountId {
+        &self.owner
+    }
+
+    /// Returns the proposed owner of the contract.
+    #[must_use]
+    pub const fn get_proposed_owner(&self) -> Option<&AccountId> {
+        self.proposed_owner.as_ref()
+    }
+
+    /// Grant the role to the account.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner.
+    pub fn grant_role(&mut self, role: Role, account_id: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can grant role"
+        );
+
+        FeesEvent::GrantRole {
+            role,
+            account_id: &account_id,
+        }
+        .emit();
+        self.roles.entry(role).or_default().insert(account_id);
+    }
+
+    /// Revoke the role from the account.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the account doesn't have the role.
+    pub fn revoke_role(&mut self, role: Role, account_id: &AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can revoke role"
+        );
+        assert!(
+            self.roles
+                .get_mut(&role)
+                .is_some_and(|members| members.remove(account_id)),
+            "Nothing to revoke, account: {account_id} doesn't have the role"
+        );
+
+        FeesEvent::RevokeRole { role, account_id }.emit();
+    }
+
+    /// Return a list of accounts which have the role.
+    #[must_use]
+    pub fn role_members(&self, role: Role) -> Vec<&AccountId> {
+        self.roles
+            .get(&role)
+            .map(|members| members.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Allow the account to set the fee percent until the `expires_at` block timestamp
+    /// in nanoseconds. The delegation lapses automatically after it expires. The new delegation
+    /// replaces the existing one for the account.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the `expires_at` is not
+    /// in the future.
+    pub fn delegate_fee_setter(&mut self, account_id: AccountId, expires_at: U64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can delegate fee setter"
+        );
+        assert!(
+            expires_at.0 > env::block_timestamp(),
+            "Delegation must expire in the future"
+        );
+
+        FeesEvent::DelegateFeeSetter {
+            account_id: &account_id,
+            expires_at,
+        }
+        .emit();
+        self.fee_delegations
+            .retain(|_, expires_at| expires_at.0 > env::block_timestamp());
+        self.fee_delegations.insert(account_id, expires_at);
+    }
+
+    /// Revoke the delegation of the fee setting from the account before it expires.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the account has no delegation.
+    pub fn revoke_fee_delegation(&mut self, account_id: &AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can revoke fee delegation"
+        );
+        assert!(
+            self.fee_delegations.remove(account_id).is_some(),
+            "Nothing to revoke, account: {account_id} has no fee delegation"
+        );
+
+        FeesEvent::RevokeFeeDelegation { account_id }.emit();
+    }
+
+    /// Returns the expiration timestamp of the account's delegation, which hasn't expired yet.
+    #[must_use]
+    pub fn get_fee_delegation(&self, account_id: &AccountId) -> Option<U64> {
+        self.fee_delegations
+            .get(account_id)
+            .copied()
+            .filter(|expires_at| expires_at.0 > env::block_timestamp())
+    }
+
+    /// Return a list of the accounts with the active delegations and their expiration
+    /// timestamps.
+    #[must_use]
+    pub fn active_fee_delegations(&self) -> Vec<(&AccountId, U64)> {
+        self.fee_delegations
+            .iter()
+            .filter(|(_, expires_at)| expires_at.0 > env::block_timestamp())
+            .map(|(account_id, expires_at)| (account_id, *expires_at))
+            .collect()
+    }
+
+    /// Set the signers of the multisig and the number of their confirmations required to
+    /// execute a proposal. While the multisig is enabled, the fees and the supported tokens
+    /// could be changed only by the executed proposals, so neither the owner nor the roles
+    /// could change them alone. `None` disables the multisig and drops the proposals.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner while the multisig is disabled or
+    /// not the executed proposal while it's enabled, or the threshold is zero or exceeds
+    /// the number of signers.
+    pub fn set_multisig(&mut self, multisig: Option<Multisig>) {
+        assert!(
+            self.is_owner_or_multisig(),
+            "Only owner or multisig can set multisig"
+        );
+        assert!(
+            multisig.as_ref().map_or(true, Multisig::is_valid),
+            "Multisig threshold must be between 1 and the number of signers"
+        );
+
+        FeesEvent::SetMultisig {
+            multisig: &multisig,
+        }
+        .emit();
+
+        if multisig.is_none() {
+            self.proposals.clear();
+        }
+
+        self.multisig = multisig;
+    }
+
+    /// Returns the signers of the multisig and the threshold, `None` if it's disabled.
+    #[must_use]
+    pub const fn get_multisig(&self) -> Option<&Multisig> {
+        self.multisig.as_ref()
+    }
+
+    /// Propose the call of the method changing the fees or the supported tokens with
+    /// the JSON arguments. The proposal is confirmed by the proposer. Returns the id of
+    /// the proposal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the multisig is disabled, the invoker of the transaction is not a signer or
+    /// the method couldn't be proposed.
+    pub fn propose(&mut self, method: String, args: String) -> u64 {
+        let signer_id = self.assert_multisig_signer();
+        assert!(
+            PROPOSABLE_METHODS.contains(&method.as_str()),
+            "Method: {method} couldn't be proposed"
+        );
+
+        let proposal_id = self.next_proposal_id;
+        self.next_proposal_id += 1;
+
+        FeesEvent::Propose {
+            proposal_id,
+            method: &method,
+            proposer: &signer_id,
+        }
+        .emit();
+        self.proposals.insert(
+            proposal_id,
+            Proposal {
+                method,
+                args,
+                proposer: signer_id.clone(),
+                confirmations: BTreeSet::from([signer_id]),
+            },
+        );
+
+        proposal_id
+    }
+
+    /// Confirm the proposal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the multisig is disabled, the invoker of the transaction is not a signer,
+    /// the proposal doesn't exist or has already been confirmed by the signer.
+    pub fn confirm(&mut self, proposal_id: u64) {
+        let signer_id = self.assert_multisig_signer();
+        let proposal = self
+            .proposals
+            .get_mut(&proposal_id)
+            .unwrap_or_else(|| env::panic_str(&format!("Proposal: {proposal_id} doesn't exist")));
+        assert!(
+            proposal.confirmations.insert(signer_id.clone()),
+            "Proposal: {proposal_id} has already been confirmed by the signer"
+        );
+
+        FeesEvent::ConfirmProposal {
+            proposal_id,
+            account_id: &signer_id,
+        }
+        .emit();
+    }
+
+    /// Execute the proposal, which has been confirmed by the required number of signers.
+    /// The proposed method is called by the contract itself and the proposal is removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the multisig is disabled, the invoker of the transaction is not a signer,
+    /// the proposal doesn't exist or hasn't been confirmed by the required number of signers.
+    pub fn execute(&mut self, proposal_id: u64) -> Promise {
+        self.assert_multisig_signer();
+        let proposal = self
+            .proposals
+            .remove(&proposal_id)
+            .unwrap_or_else(|| env::panic_str(&format!("Proposal: {proposal_id} doesn't exist")));
+        assert!(
+            self.multisig
+                .as_ref()
+                .is_some_and(|multisig| proposal.is_approved(multisig)),
+            "Proposal: {proposal_id} hasn't been confirmed by the required number of signers"
+        );
+
+        FeesEvent::ExecuteProposal { proposal_id }.emit();
+
+        Promise::new(env::current_account_id()).function_call(
+            proposal.method,
+            proposal.args.into_bytes(),
+            NearToken::from_yoctonear(0),
+            EXECUTE_PROPOSAL_GAS,
+        )
+    }
+
+    /// Returns the proposal, which hasn't been executed yet.
+    #[must_use]
+    pub fn get_proposal(&self, proposal_id: u64) -> Option<&Proposal> {
+        self.proposals.get(&proposal_id)
+    }
+
+    /// Return a list of the proposals, which haven't been executed yet.
+    #[must_use]
+    pub fn proposals(&self) -> Vec<(u64, &Proposal)> {
+        self.proposals
+            .iter()
+            .map(|(proposal_id, proposal)| (*proposal_id, proposal))
+            .collect()
+    }
+
+    /// Set the percent of the fee. The increase of the fee is scheduled to take effect after
+    /// the fee increase delay, while the decrease is applied immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the invoker of the transaction is not owner, fee setter or delegated
+    /// fee setter, the percent is invalid or the change exceeds the max fee delta.
+    #[handle_result]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_fee_percent(&mut self, percent: Option<String>) -> Result<(), FeesError> {
+        if !self.can_set_fee_percent() {
+            return Err(FeesError::Unauthorized {
+                role: Role::FeeSetter,
+                action: "set fee percent",
+            });
+        }
+
+        let value = parse_percent(percent.as_deref()).map_err(FeesError::InvalidPercent)?;
+
+        self.update_fee_percent(value)
+    }
+
+    /// Set the fee in basis points, e.g. 250 means 2.5%. Zero disables the fee. The increase
+    /// of the fee is delayed the same way as in `set_fee_percent`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner, fee setter or delegated
+    /// fee setter, if the fee is out of the allowed range or the change exceeds the max
+    /// fee delta.
+    pub fn set_fee_bps(&mut self, bps: U64) {
+        assert!(
+            self.can_set_fee_percent(),
+            "Only owner or fee setter can set fee percent"
+        );
+
+        let value = validate_percent(bps.0.saturating_mul(PERCENT_PER_BPS))
+            .unwrap_or_else(|e| env::panic_str(&format!("Invalid fee bps: {e}")));
+
+        if let Err(e) = self.update_fee_percent(value) {
+            env::panic_str(&e.to_string());
+        }
+    }
+
+    /// Return the fee in basis points, rounded down.
+    #[must_use]
+    pub fn get_fee_bps(&self) -> Option<U64> {
+        self.current_percent()
+            .map(|U64(percent)| U64(percent / PERCENT_PER_BPS))
+    }
+
+    /// Schedule the change of the fee percent, which takes effect at the `effective_at`
+    /// block timestamp in nanoseconds. The new schedule replaces the pending one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the executed proposal while
+    /// the multisig is enabled, the timestamp is not in the future or the fee increase takes
+    /// effect before the fee increase delay passes.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn schedule_fee_change(&mut self, percent: Option<String>, effective_at: U64) {
+        assert!(
+            self.is_owner_or_multisig(),
+            "Only owner can schedule fee change"
+        );
+        assert!(
+            effective_at.0 > env::block_timestamp(),
+            "Effective timestamp must be in the future"
+        );
+        self.apply_pending_fee_change();
+
+        match parse_percent(percent.as_deref()) {
+            Ok(value) => {
+                assert!(
+                    !is_fee_increase(self.percent, value)
+                        || effective_at.0 >= self.fee_increase_effective_at().0,
+                    "Fee increase must take effect after the fee increase delay"
+                );
+                FeesEvent::ScheduleFeeChange {
+                    percent: value.map(format_percent),
+                    effective_at,
+                }
+                .emit();
+                self.pending_fee_change = Some(PendingFeeChange {
+                    percent: value,
+                    effective_at,
+                });
+            }
+            Err(e) => env::panic_str(&format!("Couldn't parse percent: {e}")),
+        }
+    }
+
+    /// Cancel the pending fee change before it takes effect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the executed proposal while
+    /// the multisig is enabled, or there is no pending change.
+    pub fn cancel_fee_change(&mut self) {
+        assert!(
+            self.is_owner_or_multisig(),
+            "Only owner can cancel fee change"
+        );
+        self.apply_pending_fee_change();
+        assert!(
+            self.pending_fee_change.take().is_some(),
+            "Nothing to cancel, there is no pending fee change"
+        );
+
+        FeesEvent::CancelFeeChange.emit();
+    }
+
+    /// Set the delay in seconds between the increase of the fee percent and the moment
+    /// it takes effect, so the integrators have time to react. Zero applies the increases
+    /// immediately.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the executed proposal while
+    /// the multisig is enabled.
+    pub fn set_fee_increase_delay(&mut self, delay: u64) {
+        assert!(
+            self.is_owner_or_multisig(),
+            "Only owner can set fee increase delay"
+        );
+
+        self.fee_increase_delay = delay;
+        FeesEvent::SetFeeIncreaseDelay { delay }.emit();
+    }
+
+    /// Returns the delay in seconds of the fee percent increases.
+    #[must_use]
+    pub const fn get_fee_increase_delay(&self) -> u64 {
+        self.fee_increase_delay
+    }
+
+    /// Set the max change of the fee percent in percentage points, which could be made by
+    /// a single `set_fee_percent` or `set_fee_bps` call. The larger changes should be made
+    /// in multiple steps, each of them waiting for the previous one to take effect.
+    /// `None` removes the limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the executed proposal while
+    /// the multisig is enabled, or the delta is invalid.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_max_fee_delta(&mut self, delta: Option<String>) {
+        assert!(
+            self.is_owner_or_multisig(),
+            "Only owner can set max fee delta"
+        );
+
+        match parse_percent(delta.as_deref()) {
+            Ok(value) => {
+                self.max_fee_delta = value;
+                FeesEvent::SetMaxFeeDelta {
+                    delta: value.map(format_percent),
+                }
+                .emit();
+            }
+            Err(e) => env::panic_str(&format!("Couldn't parse percent: {e}")),
+        }
+    }
+
+    /// Returns the max change of the fee percent in percentage points.
+    #[must_use]
+    pub fn get_max_fee_delta(&self) -> Option<String> {
+        self.max_fee_delta.map(format_percent)
+    }
+
+    /// Return the scheduled fee change, which hasn't taken effect yet.
+    #[must_use]
+    pub fn get_pending_fee_change(&self) -> Option<FeeChange> {
+        self.pending_fee_change
+            .filter(|change| !change.is_effective())
+            .map(|change| FeeChange {
+                percent: change.percent.map(format_percent),
+                effective_at: change.effective_at,
+            })
+    }
+
+    /// Add the target address to the list of addresses which are exempt from fees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner, the address is invalid or
+    /// already exempt.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn add_exempt_address(&mut self, address: String) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can add exempt address"
+        );
+
+        let address = parse_address(&address);
+
+        assert!(
+            self.exempt_addresses.insert(address),
+            "Address is already exempt"
+        );
+
+        FeesEvent::AddExemptAddress {
+            address: format_address(&address),
+        }
+        .emit();
+    }
+
+    /// Remove the target address from the list of addresses which are exempt from fees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner, the address is invalid or
+    /// not exempt.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn remove_exempt_address(&mut self, address: String) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can remove exempt address"
+        );
+
+        let address = parse_address(&address);
+
+        assert!(
+            self.exempt_addresses.remove(&address),
+            "Nothing to remove, address: {} isn't exempt",
+            format_address(&address)
+        );
+
+        FeesEvent::RemoveExemptAddress {
+            address: format_address(&address),
+        }
+        .emit();
+    }
+
+    /// Return a list of addresses which are exempt from fees.
+    #[must_use]
+    pub fn exempt_addresses(&self, from_index: Option<u64>, limit: Option<u64>) -> Vec<String> {
+        paginate(self.exempt_addresses.iter(), from_index, limit)
+            .map(format_address)
+            .collect()
+    }
+
+    /// Returns the snapshot of the contract configuration.
+    #[must_use]
+    pub fn get_config(&self) -> Config {
+        Config {
+            owner: self.owner.clone(),
+            proposed_owner: self.proposed_owner.clone(),
+            percent: self.get_fee_percent(),
+            pending_fee_change: self.get_pending_fee_change(),
+            fee_increase_delay: self.fee_increase_delay,
+            max_fee_delta: self.get_max_fee_delta(),
+            paused: self.paused,
+            supported_tokens_count: self.supported_tokens.len(),
+            exempt_addresses_count: u64::try_from(self.exempt_addresses.len()).unwrap_or(u64::MAX),
+            free_forwards: self.free_forwards,
+            referral_share: U64(self.referral_share),
+            referrers_count: u64::try_from(self.referrers.len()).unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Returns current fee percent.
+    #[must_use]
+    pub fn get_fee_percent(&self) -> Option<String> {
+        self.current_percent().map(format_percent)
+    }
+
+    /// Return a list of supported tokens.
+    #[must_use]
+    pub fn supported_tokens(&self) -> Vec<&AccountId> {
+        self.supported_tokens.iter().collect()
+    }
+
+    /// Return a page of the list of supported tokens.
+    #[must_use]
+    pub fn supported_tokens_paged(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<&AccountId> {
+        paginate(self.supported_tokens.iter(), from_index, limit).collect()
+    }
+
+    /// Return the number of supported tokens.
+    #[must_use]
+    pub fn supported_tokens_count(&self) -> u64 {
+        self.supported_tokens.len()
+    }
+
+    /// Add an account id of a new supported NEP-141 token. The token is added after its
+    /// `ft_metadata` has been received successfully, so the accounts which are not NEP-141 tokens
+    /// are rejected. The promise resolves to `true` if the token has been added.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the invoker of the transaction is not owner or token manager or
+    /// the added token is already exist.
+    #[handle_result]
+    pub fn add_supported_token(&mut self, token_id: AccountId) -> Result<Promise, FeesError> {
+        self.require_role(Role::TokenManager, "add token")?;
+
+        if self.supported_tokens.contains(&token_id) {
+            return Err(FeesError::TokenAlreadyPresent);
+        }
+
+        Ok(validate_token(token_id))
+    }
+
+    /// The callback of the `ft_metadata` requested by `add_supported_token`. Stores the metadata
+    /// and adds the token. Returns `false` if the token has been added in the meantime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the account is not a NEP-141 token.
+    #[private]
+    pub fn on_ft_metadata(
+        &mut self,
+        token_id: AccountId,
+        #[callback_result] metadata: Result<FtMetadata, PromiseError>,
+    ) -> bool {
+        let metadata = metadata
+            .ok()
+            .filter(|metadata| metadata.spec.starts_with("ft-"))
+            .unwrap_or_else(|| {
+                env::panic_str(&format!("Account: {token_id} is not a NEP-141 token"))
+            });
+
+        if !self.supported_tokens.insert(&token_id) {
+            return false;
+        }
+
+        FeesEvent::AddSupportedToken {
+            token_id: &token_id,
+        }
+        .emit();
+        self.token_metadata.insert(
+            token_id,
+            TokenMetadata {
+                symbol: metadata.symbol,
+                decimals: metadata.decimals,
+            },
+        );
+
+        true
+    }
+
+    /// Returns the metadata of the token, which has been received while adding the token.
+    #[must_use]
+    pub fn get_token_metadata(&self, token_id: &AccountId) -> Option<&TokenMetadata> {
+        self.token_metadata.get(token_id)
+    }
+
+    /// Remove the token from the list of supported.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the invoker of the transaction is not owner or token manager or
+    /// the removed token is not exists.
+    #[handle_result]
+    pub fn remove_supported_token(&mut self, token_id: &AccountId) -> Result<(), FeesError> {
+        self.require_role(Role::TokenManager, "remove token")?;
+
+        if !self.supported_tokens.remove(token_id) {
+            return Err(FeesError::TokenNotAdded(token_id.clone()));
+        }
+
+        FeesEvent::RemoveSupportedToken { token_id }.emit();
+
+        Ok(())
+    }
+
+    /// Add a list of new supported NEP-141 tokens. Every token is added after its `ft_metadata`
+    /// has been received successfully the same way as in `add_supported_token`. Returns a list
+    /// of flags, where every flag shows whether the corresponding token is being added.
+    /// The flag is `false` if the token is already present.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the invoker of the transaction is not owner or token manager.
+    #[handle_result]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn add_supported_tokens(
+        &mut self,
+        token_ids: Vec<AccountId>,
+    ) -> Result<Vec<bool>, FeesError> {
+        self.require_role(Role::TokenManager, "add tokens")?;
+
+        let mut pending = BTreeSet::new();
+
+        Ok(token_ids
+            .into_iter()
+            .map(|token_id| {
+                let is_new =
+                    !self.supported_tokens.contains(&token_id) && pending.insert(token_id.clone());
+
+                if is_new {
+                    let _ = validate_token(token_id);
+                }
+
+                is_new
+            })
+            .collect())
+    }
+
+    /// Remove a list of tokens from the list of supported. Returns a list of flags, where every
+    /// flag shows whether the corresponding token has been removed. The flag is `false` if the
+    /// token hasn't been added.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the invoker of the transaction is not owner or token manager.
+    #[handle_result]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn remove_supported_tokens(
+        &mut self,
+        token_ids: Vec<AccountId>,
+    ) -> Result<Vec<bool>, FeesError> {
+        self.require_role(Role::TokenManager, "remove tokens")?;
+
+        Ok(token_ids
+            .iter()
+            .map(|token_id| {
+                let is_removed = self.supported_tokens.remove(token_id);
+
+                if is_removed {
+                    FeesEvent::RemoveSupportedToken { token_id }.emit();
+                }
+
+                is_removed
+            })
+            .collect())
+    }
+
+    /// Set the bond in yoctoNEAR, which should be attached to `register_token` to request
+    /// the listing of a token. `None` disables the registration of tokens by third parties.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or token manager.
+    pub fn set_listing_bond(&mut self, bond: Option<U128>) {
+        assert!(
+            self.has_role(Role::TokenManager),
+            "Only owner or token manager can set listing bond"
+        );
+
+        self.listing_bond = bond.filter(|bond| bond.0 > 0);
+        FeesEvent::SetListingBond {
+            bond: self.listing_bond,
+        }
+        .emit();
+    }
+
+    /// Returns the bond in yoctoNEAR required to register a token.
+    #[must_use]
+    pub const fn get_listing_bond(&self) -> Option<U128> {
+        self.listing_bond
+    }
+
+    /// Request the listing of the NEP-141 token by attaching the listing bond. The token is
+    /// listed after the owner or token manager approves the registration and the bond is
+    /// refunded. The bond of the rejected registration could be slashed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the registration is disabled, the attached deposit is less than the bond or
+    /// the token is already supported or registered.
+    #[payable]
+    pub fn register_token(&mut self, token_id: AccountId) {
+        let U128(bond) = self
+            .listing_bond
+            .unwrap_or_else(|| env::panic_str("Token registration is disabled"));
+        let deposit = env::attached_deposit().as_yoctonear();
+        assert!(
+            deposit >= bond,
+            "Attached deposit must be at least the listing bond: {bond}"
+        );
+        assert!(
+            !self.supported_tokens.contains(&token_id),
+            "Token is already present"
+        );
+        assert!(
+            !self.token_registrations.contains_key(&token_id),
+            "Token is already registered"
+        );
+
+        let registration = TokenRegistration {
+            account_id: env::predecessor_account_id(),
+            bond: U128(deposit),
+        };
+
+        FeesEvent::RegisterToken {
+            token_id: &token_id,
+            account_id: &registration.account_id,
+            bond: registration.bond,
+        }
+        .emit();
+        self.token_registrations.insert(token_id, registration);
+    }
+
+    /// Approve the registration of the token. The bond is refunded to the registrant and
+    /// the token is added the same way as in `add_supported_token`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or token manager or the token
+    /// isn't registered.
+    pub fn approve_token(&mut self, token_id: AccountId) -> Promise {
+        assert!(
+            self.has_role(Role::TokenManager),
+            "Only owner or token manager can approve token"
+        );
+        let registration = self.take_registration(&token_id);
+
+        FeesEvent::ApproveToken {
+            token_id: &token_id,
+        }
+        .emit();
+
+        Promise::new(registration.account_id)
+            .transfer(NearToken::from_yoctonear(registration.bond.0))
+            .and(validate_token(token_id))
+    }
+
+    /// Reject the registration of the token. The bond is refunded to the registrant or,
+    /// if `slash` is `true`, transferred to the owner.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or token manager or the token
+    /// isn't registered.
+    pub fn reject_token(&mut self, token_id: &AccountId, slash: bool) -> Promise {
+        assert!(
+            self.has_role(Role::TokenManager),
+            "Only owner or token manager can reject token"
+        );
+        let registration = self.take_registration(token_id);
+
+        FeesEvent::RejectToken { token_id, slash }.emit();
+
+        let receiver_id = if slash {
+            self.owner.clone()
+        } else {
+            registration.account_id
+        };
+
+        Promise::new(receiver_id).transfer(NearToken::from_yoctonear(registration.bond.0))
+    }
+
+    /// Returns the pending registration of the token.
+    #[must_use]
+    pub fn get_token_registration(&self, token_id: &AccountId) -> Option<&TokenRegistration> {
+        self.token_registrations.get(token_id)
+    }
+
+    /// Return a page of the pending registrations of the tokens.
+    #[must_use]
+    pub fn token_registrations(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(&AccountId, &TokenRegistration)> {
+        paginate(self.token_registrations.iter(), from_index, limit).collect()
+    }
+
+    /// Add the token to the list of tokens supported on the specific network. The list of
+    /// the network overrides the global list of supported tokens for the network.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or token manager or if the token
+    /// is already present.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn add_network_supported_token(&mut self, network_id: AccountId, token_id: AccountId) {
+        assert!(
+            self.has_role(Role::TokenManager),
+            "Only owner or token manager can add token"
+        );
+        assert!(
+            self.network_supported_tokens
+                .entry(network_id.clone())
+                .or_insert_with(|| SupportedTokens::for_network(&network_id))
+                .insert(&token_id),
+            "Token is already present"
+        );
+
+        FeesEvent::AddNetworkSupportedToken {
+            network_id: &network_id,
+            token_id: &token_id,
+        }
+        .emit();
+    }
+
+    /// Remove the token from the list of tokens supported on the specific network. The list
+    /// stays in effect for the network even if it becomes empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or token manager or if the token
+    /// hasn't been added.
+    pub fn remove_network_supported_token(&mut self, network_id: &AccountId, token_id: &AccountId) {
+        assert!(
+            self.has_role(Role::TokenManager),
+            "Only owner or token manager can remove token"
+        );
+        assert!(
+            self.network_supported_tokens
+                .get_mut(network_id)
+                .is_some_and(|tokens| tokens.remove(token_id)),
+            "Nothing to remove, token: {token_id} hasn't been added"
+        );
+
+        FeesEvent::RemoveNetworkSupportedToken {
+            network_id,
+            token_id,
+        }
+        .emit();
+    }
+
+    /// Remove the list of tokens supported on the specific network, so the global list of
+    /// supported tokens is used for the network.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or token manager or if the network
+    /// has no list of supported tokens.
+    pub fn remove_network_supported_tokens(&mut self, network_id: &AccountId) {
+        assert!(
+            self.has_role(Role::TokenManager),
+            "Only owner or token manager can remove tokens"
+        );
+
+        let mut tokens = self
+            .network_supported_tokens
+            .remove(network_id)
+            .unwrap_or_else(|| {
+                env::panic_str(&format!(
+                    "Nothing to remove, network: {network_id} has no supported tokens"
+                ))
+            });
+        tokens.clear();
+
+        FeesEvent::RemoveNetworkSupportedTokens { network_id }.emit();
+    }
+
+    /// Return a page of the list of tokens supported on the specific network or `None` if
+    /// the global list is used for the network.
+    #[must_use]
+    pub fn network_supported_tokens(
+        &self,
+        network_id: &AccountId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Option<Vec<&AccountId>> {
+        self.network_supported_tokens
+            .get(network_id)
+            .map(|tokens| paginate(tokens.iter(), from_index, limit).collect())
+    }
+
+    /// Add the network to the registry of supported target networks. Once the registry isn't
+    /// empty, no fees are charged for forwards to the networks missing in it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the network is already present.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn add_supported_network(&mut self, network_id: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can add network"
+        );
+
+        assert!(
+            self.supported_networks.insert(network_id.clone()),
+            "Network is already present"
+        );
+
+        FeesEvent::AddSupportedNetwork {
+            network_id: &network_id,
+        }
+        .emit();
+    }
+
+    /// Remove the network from the registry of supported target networks. Removing the last
+    /// network makes all networks supported again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the network hasn't been added.
+    pub fn remove_supported_network(&mut self, network_id: &AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can remove network"
+        );
+        assert!(
+            self.supported_networks.remove(network_id),
+            "Nothing to remove, network: {network_id} hasn't been added"
+        );
+
+        FeesEvent::RemoveSupportedNetwork { network_id }.emit();
+    }
+
+    /// Return the registry of supported target networks. The empty registry means that all
+    /// networks are supported.
+    #[must_use]
+    pub fn supported_networks(&self) -> Vec<&AccountId> {
+        self.supported_networks.iter().collect()
+    }
+
+    /// Returns `true` if the token is supported on the network.
+    #[must_use]
+    pub fn is_token_supported(&self, token_id: &AccountId, network_id: &AccountId) -> bool {
+        self.network_supported_tokens
+            .get(network_id)
+            .unwrap_or(&self.supported_tokens)
+            .contains(token_id)
+    }
+
+    /// Returns `true` if the network is in the registry of supported networks and at least one
+    /// token is supported on it, so the deposits to the network could be charged.
+    #[must_use]
+    pub fn is_network_supported(&self, network_id: &AccountId) -> bool {
+        self.is_network_registered(network_id)
+            && !self
+                .network_supported_tokens
+                .get(network_id)
+                .unwrap_or(&self.supported_tokens)
+                .is_empty()
+    }
+
+    /// Suspend the token, so no fees are charged for it until it's resumed. Unlike removing,
+    /// the suspension keeps the token in the lists of supported tokens and retains its
+    /// configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or token manager or if the token
+    /// is already suspended.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn suspend_token(&mut self, token_id: AccountId) {
+        assert!(
+            self.has_role(Role::TokenManager),
+            "Only owner or token manager can suspend token"
+        );
+
+        assert!(
+            self.suspended_tokens.insert(token_id.clone()),
+            "Token is already suspended"
+        );
+
+        FeesEvent::SuspendToken {
+            token_id: &token_id,
+        }
+        .emit();
+    }
+
+    /// Resume the suspended token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or token manager or if the token
+    /// isn't suspended.
+    pub fn resume_token(&mut self, token_id: &AccountId) {
+        assert!(
+            self.has_role(Role::TokenManager),
+            "Only owner or token manager can resume token"
+        );
+        assert!(
+            self.suspended_tokens.remove(token_id),
+            "Nothing to resume, token: {token_id} isn't suspended"
+        );
+
+        FeesEvent::ResumeToken { token_id }.emit();
+    }
+
+    /// Returns `true` if the token is suspended.
+    #[must_use]
+    pub fn is_token_suspended(&self, token_id: &AccountId) -> bool {
+        self.suspended_tokens.contains(token_id)
+    }
+
+    /// Returns a list of suspended tokens.
+    #[must_use]
+    pub fn suspended_tokens(&self) -> Vec<&AccountId> {
+        self.suspended_tokens.iter().collect()
+    }
+
+    /// Set the fee percent for a specific token. The percent overrides the global one
+    /// for the token. `None` or `"0"` disables fees for the token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter, or the percent is
+    /// invalid.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_token_fee(&mut self, token_id: AccountId, percent: Option<String>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set token fee"
+        );
+
+        match parse_percent(percent.as_deref()) {
+            Ok(value) => {
+                self.log_fee_change(
+                    FeeParameter::TokenFee,
+                    Some(&token_id),
+                    self.token_fees.get(&token_id).copied().map(percent_value),
+                    Some(percent_value(value)),
+                );
+                FeesEvent::SetTokenFee {
+                    token_id: &token_id,
+                    percent: value.map(format_percent),
+                }
+                .emit();
+                self.token_fees.insert(token_id, value);
+            }
+            Err(e) => env::panic_str(&format!("Couldn't parse percent: {e}")),
+        }
+    }
+
+    /// Remove the fee percent of the token, so the global percent is used for it again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter, or the token has no fee
+    /// override.
+    pub fn remove_token_fee(&mut self, token_id: &AccountId) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can remove token fee"
+        );
+        let old_percent = self.token_fees.remove(token_id).unwrap_or_else(|| {
+            env::panic_str(&format!(
+                "Nothing to remove, token: {token_id} has no fee override"
+            ))
+        });
+
+        self.log_fee_change(
+            FeeParameter::TokenFee,
+            Some(token_id),
+            Some(percent_value(old_percent)),
+            None,
+        );
+
+        FeesEvent::RemoveTokenFee { token_id }.emit();
+    }
+
+    /// Returns the fee percent which is applied to the token.
+    #[must_use]
+    pub fn get_token_fee_percent(&self, token_id: &AccountId) -> Option<String> {
+        self.token_fees
+            .get(token_id)
+            .copied()
+            .unwrap_or(self.percent)
+            .map(format_percent)
+    }
+
+    /// Set the volume tiers of the fee for a specific token. Every tier is applied to
+    /// amounts less than its `up_to` value, tiers must be sorted by `up_to` in ascending
+    /// order and the last tier must be unbounded, i.e. have `up_to` equal to `None`.
+    /// The tiers have a priority over the fee percent of the token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter, or the tiers are
+    /// invalid.
+    pub fn set_token_fee_tiers(&mut self, token_id: AccountId, tiers: Vec<FeeTier>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set token fee tiers"
+        );
+        assert!(
+            matches!(tiers.last(), Some(tier) if tier.up_to.is_none()),
+            "The last tier must be unbounded"
+        );
+        assert!(
+            tiers
+                .windows(2)
+                .all(|pair| match (pair[0].up_to, pair[1].up_to) {
+                    (Some(U128(current)), Some(U128(next))) => current < next,
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                }),
+            "Tiers must be sorted by the upper bound in ascending order"
+        );
+
+        let tiers: Vec<_> = tiers
+            .into_iter()
+            .map(|tier| match parse_percent(tier.percent.as_deref()) {
+                Ok(percent) => Tier {
+                    up_to: tier.up_to,
+                    percent,
+                },
+                Err(e) => env::panic_str(&format!("Couldn't parse percent: {e}")),
+            })
+            .collect();
+
+        self.log_fee_change(
+            FeeParameter::TokenFeeTiers,
+            Some(&token_id),
+            self.token_fee_tiers
+                .get(&token_id)
+                .map(Vec::as_slice)
+                .map(tiers_value),
+            Some(tiers_value(&tiers)),
+        );
+        FeesEvent::SetTokenFeeTiers {
+            token_id: &token_id,
+            tiers: &tiers.iter().map(Tier::to_fee_tier).collect::<Vec<_>>(),
+        }
+        .emit();
+        self.token_fee_tiers.insert(token_id, tiers);
+    }
+
+    /// Remove the volume tiers of the fee for the token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter, or the token has no
+    /// tiers.
+    pub fn remove_token_fee_tiers(&mut self, token_id: &AccountId) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can remove token fee tiers"
+        );
+        let old_tiers = self.token_fee_tiers.remove(token_id).unwrap_or_else(|| {
+            env::panic_str(&format!(
+                "Nothing to remove, token: {token_id} has no fee tiers"
+            ))
+        });
+
+        self.log_fee_change(
+            FeeParameter::TokenFeeTiers,
+            Some(token_id),
+            Some(tiers_value(&old_tiers)),
+            None,
+        );
+
+        FeesEvent::RemoveTokenFeeTiers { token_id }.emit();
+    }
+
+    /// Returns the volume tiers of the fee for the token.
+    #[must_use]
+    pub fn get_token_fee_tiers(&self, token_id: &AccountId) -> Option<Vec<FeeTier>> {
+        self.token_fee_tiers
+            .get(token_id)
+            .map(|tiers| tiers.iter().map(Tier::to_fee_tier).collect())
+    }
+
+    /// Set the flat fee for a specific token in the token units. The flat fee is added
+    /// on top of the percentage fee. `None` removes the flat fee of the token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter.
+    pub fn set_token_flat_fee(&mut self, token_id: AccountId, fee: Option<U128>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set token flat fee"
+        );
+
+        let fee = fee.filter(|fee| fee.0 > 0);
+
+        self.log_fee_change(
+            FeeParameter::TokenFlatFee,
+            Some(&token_id),
+            self.token_flat_fees
+                .get(&token_id)
+                .map(|U128(fee)| fee.to_string()),
+            fee.map(|U128(fee)| fee.to_string()),
+        );
+
+        FeesEvent::SetTokenFlatFee {
+            token_id: &token_id,
+            fee,
+        }
+        .emit();
+
+        if let Some(fee) = fee {
+            self.token_flat_fees.insert(token_id, fee);
+        } else {
+            self.token_flat_fees.remove(&token_id);
+        }
+    }
+
+    /// Returns the flat fee of the token.
+    #[must_use]
+    pub fn get_token_flat_fee(&self, token_id: &AccountId) -> Option<U128> {
+        self.token_flat_fees.get(token_id).copied()
+    }
+
+    /// Set the flat fee in USD cents, which is converted to the token units by the latest
+    /// prices from the price oracle. The flat fee of the token has a priority over it.
+    /// `None` removes the USD flat fee.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter.
+    pub fn set_usd_flat_fee(&mut self, fee: Option<U128>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set USD flat fee"
+        );
+
+        let fee = fee.filter(|fee| fee.0 > 0);
+
+        self.log_fee_change(
+            FeeParameter::UsdFlatFee,
+            None,
+            self.usd_flat_fee.map(|U128(fee)| fee.to_string()),
+            fee.map(|U128(fee)| fee.to_string()),
+        );
+        self.usd_flat_fee = fee;
+
+        FeesEvent::SetUsdFlatFee { fee }.emit();
+    }
+
+    /// Returns the flat fee in USD cents.
+    #[must_use]
+    pub const fn get_usd_flat_fee(&self) -> Option<U128> {
+        self.usd_flat_fee
+    }
+
+    /// Set the account of the price oracle, which provides the prices for the USD flat fee.
+    /// `None` disables the price updates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner.
+    pub fn set_price_oracle(&mut self, oracle_id: Option<AccountId>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set price oracle"
+        );
+
+        FeesEvent::SetPriceOracle {
+            oracle_id: &oracle_id,
+        }
+        .emit();
+        self.price_oracle = oracle_id;
+    }
+
+    /// Returns the account of the price oracle.
+    #[must_use]
+    pub const fn get_price_oracle(&self) -> Option<&AccountId> {
+        self.price_oracle.as_ref()
+    }
+
+    /// Set the maximum age of the prices in seconds. The USD flat fee isn't charged for
+    /// the tokens with the older prices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner.
+    pub fn set_max_price_age(&mut self, max_age: u64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set max price age"
+        );
+
+        self.max_price_age = max_age;
+        FeesEvent::SetMaxPriceAge { max_age }.emit();
+    }
+
+    /// Returns the maximum age of the prices in seconds.
+    #[must_use]
+    pub const fn get_max_price_age(&self) -> u64 {
+        self.max_price_age
+    }
+
+    /// Request the latest prices of the tokens from the price oracle. Anyone can update
+    /// the prices since they come from the oracle only.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the price oracle isn't set.
+    pub fn update_prices(&mut self, token_ids: Vec<AccountId>) -> Promise {
+        let oracle_id = self
+            .price_oracle
+            .clone()
+            .unwrap_or_else(|| env::panic_str("Price oracle is not set"));
+
+        ext_oracle::ext(oracle_id)
+            .with_static_gas(GET_PRICE_DATA_GAS)
+            .get_price_data(Some(token_ids))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(ON_PRICE_DATA_GAS)
+                    .on_price_data(),
+            )
+    }
+
+    /// The callback of the `get_price_data` requested by `update_prices`. Stores the prices
+    /// of the tokens, which are newer than the stored ones. Returns the number of the updated
+    /// prices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the price oracle hasn't returned the prices.
+    #[private]
+    pub fn on_price_data(
+        &mut self,
+        #[callback_result] price_data: Result<PriceData, PromiseError>,
+    ) -> u32 {
+        let price_data =
+            price_data.unwrap_or_else(|_| env::panic_str("Couldn't get prices from the oracle"));
+        let mut updated = 0;
+
+        for asset in price_data.prices {
+            let Some(price) = asset.price else {
+                continue;
+            };
+            let is_newer = self
+                .token_prices
+                .get(&asset.asset_id)
+                .map_or(true, |old| old.timestamp.0 < price_data.timestamp.0);
+
+            if is_newer {
+                self.token_prices.insert(
+                    asset.asset_id,
+                    TokenPrice {
+                        multiplier: price.multiplier,
+                        decimals: price.decimals,
+                        timestamp: price_data.timestamp,
+                    },
+                );
+                updated += 1;
+            }
+        }
+
+        updated
+    }
+
+    /// Returns the latest price of the token received from the price oracle.
+    #[must_use]
+    pub fn get_token_price(&self, token_id: &AccountId) -> Option<&TokenPrice> {
+        self.token_prices.get(token_id)
+    }
+
+    /// Set the amount of the token in the token units, below which no fee is charged, since
+    /// the transfer of the fee would cost more than the fee itself. `None` removes
+    /// the threshold of the token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter.
+    pub fn set_dust_threshold(&mut self, token_id: AccountId, threshold: Option<U128>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set dust threshold"
+        );
+
+        let threshold = threshold.filter(|threshold| threshold.0 > 0);
+
+        self.log_fee_change(
+            FeeParameter::DustThreshold,
+            Some(&token_id),
+            self.dust_thresholds
+                .get(&token_id)
+                .map(|U128(threshold)| threshold.to_string()),
+            threshold.map(|U128(threshold)| threshold.to_string()),
+        );
+
+        FeesEvent::SetDustThreshold {
+            token_id: &token_id,
+            threshold,
+        }
+        .emit();
+
+        if let Some(threshold) = threshold {
+            self.dust_thresholds.insert(token_id, threshold);
+        } else {
+            self.dust_thresholds.remove(&token_id);
+        }
+    }
+
+    /// Returns the amount of the token below which no fee is charged.
+    #[must_use]
+    pub fn get_dust_threshold(&self, token_id: &AccountId) -> Option<U128> {
+        self.dust_thresholds.get(token_id).copied()
+    }
+
+    /// Set the minimum and maximum of the percentage fee for a specific token in the token
+    /// units. Setting both caps to `None` removes the caps of the token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter, or the minimum is
+    /// greater than the maximum.
+    pub fn set_token_fee_caps(
+        &mut self,
+        token_id: AccountId,
+        min: Option<U128>,
+        max: Option<U128>,
+    ) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set token fee caps"
+        );
+
+        if let (Some(U128(min)), Some(U128(max))) = (min, max) {
+            assert!(
+                min <= max,
+                "The minimum fee can't be greater than the maximum fee"
+            );
+        }
+
+        let caps = FeeCaps { min, max };
+        self.log_fee_change(
+            FeeParameter::TokenFeeCaps,
+            Some(&token_id),
+            self.token_fee_caps.get(&token_id).map(caps_value),
+            (min.is_some() || max.is_some()).then(|| caps_value(&caps)),
+        );
+        FeesEvent::SetTokenFeeCaps {
+            token_id: &token_id,
+            min,
+            max,
+        }
+        .emit();
+
+        if min.is_none() && max.is_none() {
+            self.token_fee_caps.remove(&token_id);
+        } else {
+            self.token_fee_caps.insert(token_id, caps);
+        }
+    }
+
+    /// Returns the minimum and maximum of the percentage fee for the token.
+    #[must_use]
+    pub fn get_token_fee_caps(&self, token_id: &AccountId) -> Option<&FeeCaps> {
+        self.token_fee_caps.get(token_id)
+    }
+
+    /// Declare the promotion for the token, which makes the fee for the token zero from
+    /// the `start` until the `end` block timestamps in nanoseconds. The new promotion replaces
+    /// the existing one for the token. The promotion is removed automatically after it expires.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner, the `start` is not before
+    /// the `end` or the `end` is not in the future.
+    pub fn set_promotion(&mut self, token_id: AccountId, start: U64, end: U64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set promotion"
+        );
+        assert!(start.0 < end.0, "Promotion must start before it ends");
+        assert!(
+            end.0 > env::block_timestamp(),
+            "Promotion must end in the future"
+        );
+
+        FeesEvent::SetPromotion {
+            token_id: &token_id,
+            start,
+            end,
+        }
+        .emit();
+        self.promotions.insert(token_id, Promotion { start, end });
+    }
+
+    /// Remove the promotion for the token before it expires.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or the token has no promotion.
+    pub fn remove_promotion(&mut self, token_id: &AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can remove promotion"
+        );
+        assert!(
+            self.promotions.remove(token_id).is_some(),
+            "Nothing to remove, token: {token_id} has no promotion"
+        );
+
+        FeesEvent::RemovePromotion { token_id }.emit();
+    }
+
+    /// Returns the promotion for the token, which hasn't expired yet.
+    #[must_use]
+    pub fn get_promotion(&self, token_id: &AccountId) -> Option<&Promotion> {
+        self.promotions
+            .get(token_id)
+            .filter(|promotion| !promotion.is_expired())
+    }
+
+    /// Return a list of the promotions, which are active at the moment.
+    #[must_use]
+    pub fn active_promotions(&self) -> Vec<(&AccountId, &Promotion)> {
+        self.promotions
+            .iter()
+            .filter(|(_, promotion)| promotion.is_active())
+            .collect()
+    }
+
+    /// Set the fee percent for a specific Aurora Network (silo). The percent overrides
+    /// the global one for forwards to the network, but not the per-token percents.
+    /// `None` or `"0"` disables fees for the network.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter, or the percent is
+    /// invalid.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_network_fee(&mut self, network_id: AccountId, percent: Option<String>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set network fee"
+        );
+
+        match parse_percent(percent.as_deref()) {
+            Ok(value) => {
+                self.log_fee_change(
+                    FeeParameter::NetworkFee,
+                    Some(&network_id),
+                    self.network_fees
+                        .get(&network_id)
+                        .copied()
+                        .map(percent_value),
+                    Some(percent_value(value)),
+                );
+                FeesEvent::SetNetworkFee {
+                    network_id: &network_id,
+                    percent: value.map(format_percent),
+                }
+                .emit();
+                self.network_fees.insert(network_id, value);
+            }
+            Err(e) => env::panic_str(&format!("Couldn't parse percent: {e}")),
+        }
+    }
+
+    /// Remove the fee percent of the network, so the global percent is used for it again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter, or the network has no
+    /// fee override.
+    pub fn remove_network_fee(&mut self, network_id: &AccountId) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can remove network fee"
+        );
+        let old_percent = self.network_fees.remove(network_id).unwrap_or_else(|| {
+            env::panic_str(&format!(
+                "Nothing to remove, network: {network_id} has no fee override"
+            ))
+        });
+
+        self.log_fee_change(
+            FeeParameter::NetworkFee,
+            Some(network_id),
+            Some(percent_value(old_percent)),
+            None,
+        );
+
+        FeesEvent::RemoveNetworkFee { network_id }.emit();
+    }
+
+    /// Returns the fee percent which is applied to the network.
+    #[must_use]
+    pub fn get_network_fee_percent(&self, network_id: &AccountId) -> Option<String> {
+        self.network_fees
+            .get(network_id)
+            .copied()
+            .unwrap_or(self.percent)
+            .map(format_percent)
+    }
+
+    /// Return a list of networks with overridden fee percents.
+    #[must_use]
+    pub fn network_fees(&self) -> Vec<(&AccountId, Option<String>)> {
+        self.network_fees
+            .iter()
+            .map(|(network_id, percent)| (network_id, percent.map(format_percent)))
+            .collect()
+    }
+
+    /// Set the negotiated fee percent for the forwards requested by a specific account, e.g.
+    /// the forwarder of an exchange. The percent has a priority over the percents of the tokens
+    /// and the networks and the global one. `None` or `"0"` disables fees for the account.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter, or the percent is
+    /// invalid.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_account_fee(&mut self, account_id: AccountId, percent: Option<String>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set account fee"
+        );
+
+        match parse_percent(percent.as_deref()) {
+            Ok(value) => {
+                self.log_fee_change(
+                    FeeParameter::AccountFee,
+                    Some(&account_id),
+                    self.account_fees
+                        .get(&account_id)
+                        .copied()
+                        .map(percent_value),
+                    Some(percent_value(value)),
+                );
+                FeesEvent::SetAccountFee {
+                    account_id: &account_id,
+                    percent: value.map(format_percent),
+                }
+                .emit();
+                self.account_fees.insert(account_id, value);
+            }
+            Err(e) => env::panic_str(&format!("Couldn't parse percent: {e}")),
+        }
+    }
+
+    /// Remove the fee percent of the account, so the regular percents are used for it again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter, or the account has no
+    /// fee override.
+    pub fn remove_account_fee(&mut self, account_id: &AccountId) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can remove account fee"
+        );
+        let old_percent = self.account_fees.remove(account_id).unwrap_or_else(|| {
+            env::panic_str(&format!(
+                "Nothing to remove, account: {account_id} has no fee override"
+            ))
+        });
+
+        self.log_fee_change(
+            FeeParameter::AccountFee,
+            Some(account_id),
+            Some(percent_value(old_percent)),
+            None,
+        );
+
+        FeesEvent::RemoveAccountFee { account_id }.emit();
+    }
+
+    /// Returns the fee percent of the account, `None` if the account has no fee override.
+    #[must_use]
+    pub fn get_account_fee_percent(&self, account_id: &AccountId) -> Option<String> {
+        self.account_fees
+            .get(account_id)
+            .copied()
+            .flatten()
+            .map(format_percent)
+    }
+
+    /// Return a list of accounts with overridden fee percents.
+    #[must_use]
+    pub fn account_fees(&self) -> Vec<(&AccountId, Option<String>)> {
+        self.account_fees
+            .iter()
+            .map(|(account_id, percent)| (account_id, percent.map(format_percent)))
+            .collect()
+    }
+
+    /// Set the minimum amount of the token which could be forwarded to the network. The fee
+    /// calculation of the smaller amounts is rejected, so the forwarding is aborted. `None`
+    /// removes the minimum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter.
+    pub fn set_min_amount(
+        &mut self,
+        network_id: AccountId,
+        token_id: AccountId,
+        amount: Option<U128>,
+    ) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set min amount"
+        );
+
+        let amount = amount.filter(|amount| amount.0 > 0);
+
+        FeesEvent::SetMinAmount {
+            network_id: &network_id,
+            token_id: &token_id,
+            amount,
+        }
+        .emit();
+
+        if let Some(amount) = amount {
+            self.min_amounts
+                .entry(network_id)
+                .or_default()
+                .insert(token_id, amount);
+        } else if let Some(amounts) = self.min_amounts.get_mut(&network_id) {
+            amounts.remove(&token_id);
+
+            if amounts.is_empty() {
+                self.min_amounts.remove(&network_id);
+            }
+        }
+    }
+
+    /// Returns the minimum amount of the token which could be forwarded to the network.
+    #[must_use]
+    pub fn get_min_amount(&self, network_id: &AccountId, token_id: &AccountId) -> Option<U128> {
+        self.min_amounts
+            .get(network_id)
+            .and_then(|amounts| amounts.get(token_id))
+            .copied()
+    }
+
+    /// Return a list of the minimum amounts of the tokens for the network.
+    #[must_use]
+    pub fn network_min_amounts(&self, network_id: &AccountId) -> Vec<(&AccountId, U128)> {
+        self.min_amounts
+            .get(network_id)
+            .map(|amounts| amounts.iter().map(|(id, amount)| (id, *amount)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Set the maximum amount of the token which could be forwarded at once. The fee
+    /// calculation of the larger amounts is rejected, so the forwarder holds the tokens
+    /// instead of forwarding them. `None` removes the maximum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the invoker of the transaction is not owner or fee setter.
+    pub fn set_max_amount(&mut self, token_id: AccountId, amount: Option<U128>) {
+        assert!(
+            self.has_role(Role::FeeSetter),
+            "Only owner or fee setter can set max amount"
+        );
+
+        FeesEvent::SetMaxAmount {
+            token_id: &token_id,
+            amount,
+        }
+        .emit();
+
+        if let Some(amount) = amount {
+            self.max_amounts.insert(token_id, amount);
+        } else {
+            self.max_amounts.remove(&token_id);
+        }
+    }
+
+    /// Returns the maximum amount of the token which could be forwarded at once.
+    #[must_use]
+    pub fn get_max_amount(&self, token_id: &AccountId) -> Option<U128> {
+        self.max_amounts.get(token_id).copied()
+    }
+
+    /// Return a list of the maximum amounts of the tokens.
+    #[must_use]
+    pub fn max_amounts(&self) -> Vec<(&AccountId, U128)> {
+        self.max_amounts
+            .iter()
+            .map(|(token_id, amount)| (token_id, *amount))
+            .collect()
+    }
+
+    /// Return a page of the log of the fee configuration changes in the order they have been
+    /// made.
+    #[must_use]
+    pub fn fee_history(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<&FeeHistoryEntry> {
+        paginate(self.fee_history.iter(), from_index, limit).collect()
+    }
+
+    /// Return the number of entries in the log of the fee configuration changes.
+    #[must_use]
+    pub fn fee_history_count(&self) -> u64 {
+        u64::from(self.fee_history.len())
+    }
+}
+
+impl FeesCalculator {
+    /// Returns the global fee percent, taking into account the scheduled change, which has
+    /// already taken effect but hasn't been applied to the state yet.
+    fn current_percent(&self) -> Option<U64> {
+        match self.pending_fee_change {
+            Some(change) if change.is_effective() => change.percent,
+            _ => self.percent,
+        }
+    }
+
+    /// Sets the fee percent or schedules it after the fee increase delay if it's an increase.
+    /// The change is compared with the percent, which has already taken effect.
+    fn update_fee_percent(&mut self, value: Option<U64>) -> Result<(), FeesError> {
+        self.apply_pending_fee_change();
+
+        if let Some(max_delta) = self.max_fee_delta {
+            if fee_delta(self.percent, value) > max_delta.0 {
+                return Err(FeesError::FeeDeltaExceeded(format_percent(max_delta)));
+            }
+        }
+
+        if self.fee_increase_delay > 0 && is_fee_increase(self.percent, value) {
+            let effective_at = self.fee_increase_effective_at();
+
+            FeesEvent::ScheduleFeeChange {
+                percent: value.map(format_percent),
+                effective_at,
+            }
+            .emit();
+            self.pending_fee_change = Some(PendingFeeChange {
+                percent: value,
+                effective_at,
+            });
+            return Ok(());
+        }
+
+        self.log_fee_change(
+            FeeParameter::FeePercent,
+            None,
+            Some(percent_value(self.percent)),
+            Some(percent_value(value)),
+        );
+        self.percent = value;
+        FeesEvent::SetFeePercent {
+            percent: value.map(format_percent),
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Returns the earliest block timestamp when the fee increase could take effect.
+    fn fee_increase_effective_at(&self) -> U64 {
+        U64(env::block_timestamp()
+            .saturating_add(self.fee_increase_delay.saturating_mul(NANOS_PER_SEC)))
+    }
+
+    /// Applies the scheduled fee change to the state if it has already taken effect.
+    fn apply_pending_fee_change(&mut self) {
+        if let Some(change) = self.pending_fee_change {
+            if change.is_effective() {
+                // Only the owner can schedule the fee change, so the change is attributed
+                // to the owner and to the moment it has taken effect.
+                self.fee_history.push(FeeHistoryEntry {
+                    account_id: self.owner.clone(),
+                    timestamp: change.effective_at,
+                    parameter: FeeParameter::FeePercent.as_str().to_string(),
+                    target_id: None,
+                    old_value: Some(percent_value(self.percent)),
+                    new_value: Some(percent_value(change.percent)),
+                });
+                self.percent = change.percent;
+                self.pending_fee_change = None;
+                FeesEvent::SetFeePercent {
+                    percent: change.percent.map(format_percent),
+                }
+                .emit();
+            }
+        }
+    }
+
+    /// Returns the result with the waived fee if the account has free forwards left. The free
+    /// forward isn't consumed.
+    fn free_forward_result(
+        &self,
+        result: FeeResult,
+        amount: U128,
+        account_id: &AccountId,
+    ) -> FeeResult {
+        if !result.rejected && result.fee.0 > 0 && self.remaining_free_forwards(account_id) > 0 {
+            FeeResult::new(0, amount, FeeRule::FreeForward, result.fee_recipient)
+        } else {
+            result
+        }
+    }
+
+    /// Consumes one of the free fee calculations of the invoker of the transaction and
+    /// returns `true` if the invoker had one.
+    fn use_free_forward(&mut self) -> bool {
+        if self.free_forwards == 0 {
+            return false;
+        }
+
+        let used = self
+            .forwards_count
+            .entry(env::predecessor_account_id())
+            .or_default();
+
+        if *used < self.free_forwards {
+            *used += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Checks if the invoker of the transaction is owner or has the role. While the multisig
+    /// is enabled, only the executed proposals, i.e. the calls made by the contract itself,
+    /// have all roles.
+    fn has_role(&self, role: Role) -> bool {
+        let predecessor_id = env::predecessor_account_id();
+
+        if self.multisig.is_some() {
+            return predecessor_id == env::current_account_id();
+        }
+
+        predecessor_id == self.owner
+            || self
+                .roles
+                .get(&role)
+                .is_some_and(|members| members.contains(&predecessor_id))
+    }
+
+    /// Checks that the invoker is a direct sub-account of a factory with the `Factory` role,
+    /// so the statistics and the free forwards can't be spent by arbitrary accounts.
+    fn assert_known_forwarder(&self) {
+        let predecessor_id = env::predecessor_account_id();
+        let is_known = self.roles.get(&Role::Factory).is_some_and(|factories| {
+            factories
+                .iter()
+                .any(|factory_id| predecessor_id.is_sub_account_of(factory_id))
+        });
+
+        assert!(
+            is_known,
+            "Only forwarders of known factories can record forwards"
+        );
+    }
+
+    /// Returns `true` if the registry of supported networks is empty or contains the network.
+    fn is_network_registered(&self, network_id: &AccountId) -> bool {
+        self.supported_networks.is_empty() || self.supported_networks.contains(network_id)
+    }
+
+    /// Removes and returns the pending registration of the token.
+    fn take_registration(&mut self, token_id: &AccountId) -> TokenRegistration {
+        self.token_registrations
+            .remove(token_id)
+            .unwrap_or_else(|| env::panic_str(&format!("Token: {token_id} isn't registered")))
+    }
+
+    /// Returns an error if the predecessor is neither the owner nor a member of the role.
+    fn require_role(&self, role: Role, action: &'static str) -> Result<(), FeesError> {
+        if self.has_role(role) {
+            Ok(())
+        } else {
+            Err(FeesError::Unauthorized { role, action })
+        }
+    }
+
+    /// Returns `true` if the predecessor has the fee setter role or the active delegation.
+    /// The delegations are ignored while the multisig is enabled.
+    fn can_set_fee_percent(&self) -> bool {
+        self.has_role(Role::FeeSetter)
+            || (self.multisig.is_none()
+                && self
+                    .get_fee_delegation(&env::predecessor_account_id())
+                    .is_some())
+    }
+
+    /// Checks if the invoker of the transaction is owner while the multisig is disabled or
+    /// the executed proposal while it's enabled.
+    fn is_owner_or_multisig(&self) -> bool {
+        let predecessor_id = env::predecessor_account_id();
+
+        if self.multisig.is_some() {
+            predecessor_id == env::current_account_id()
+        } else {
+            predecessor_id == self.owner
+        }
+    }
+
+    /// Asserts the multisig is enabled and the invoker of the transaction is its signer.
+    /// Returns the signer.
+    fn assert_multisig_signer(&self) -> AccountId {
+        let predecessor_id = env::predecessor_account_id();
+        assert!(
+            self.multisig
+                .as_ref()
+                .is_some_and(|multisig| multisig.is_signer(&predecessor_id)),
+            "Only multisig signer can manage proposals"
+        );
+
+        predecessor_id
+    }
+
+    /// Appends the change of the fee configuration made by the predecessor to the log.
+    fn log_fee_change(
+        &mut self,
+        parameter: FeeParameter,
+        target_id: Option<&AccountId>,
+        old_value: Option<String>,
+        new_value: Option<String>,
+    ) {
+        self.fee_history.push(FeeHistoryEntry {
+            account_id: env::predecessor_account_id(),
+            timestamp: env::block_timestamp().into(),
+            parameter: parameter.as_str().to_string(),
+            target_id: target_id.cloned(),
+            old_value,
+            new_value,
+        });
+    }
+
+    /// Returns the fee result without changing the state.
+    fn quote(
+        &self,
+        amount: U128,
+        token_id: &AccountId,
+        target_network: &AccountId,
+        target_address: Address,
+        account_id: Option<&AccountId>,
+    ) -> FeeResult {
+        if !is_valid_address(target_address) {
+            return FeeResult::invalid_address(amount);
+        }
+
+        if let Some(rule) = self.rejection_rule(amount, token_id, target_network) {
+            return FeeResult::rejected(amount, rule);
+        }
+
+        let (fee, rule) =
+            self.quote_fee(amount, token_id, target_network, target_address, account_id);
+
+        FeeResult::new(fee, amount, rule, self.get_fee_recipient(token_id))
+    }
+
+    fn fee_result(
+        &mut self,
+        amount: U128,
+        token_id: &AccountId,
+        network_id: &AccountId,
+        target_address: Address,
+    ) -> FeeResult {
+        assert!(!self.paused, "Fees contract is paused");
+
+        if !is_valid_address(target_address) {
+            return FeeResult::invalid_address(amount);
+        }
+
+        if let Some(rule) = self.rejection_rule(amount, token_id, network_id) {
+            return FeeResult::rejected(amount, rule);
+        }
+
+        self.apply_pending_fee_change();
+
+        let (mut fee, mut rule) = self.fee(amount, token_id, network_id, target_address);
+
+        if !matches!(
+            rule,
+            FeeRule::UnsupportedNetwork
+                | FeeRule::UnsupportedToken
+                | FeeRule::UnlistedTokenFee
+                | FeeRule::SuspendedToken
+                | FeeRule::ExemptAddress
+        ) {
+            if let Some(surcharge) = self.take_unbridged_surcharge(token_id, network_id) {
+                fee = fee.saturating_add(surcharge).min(amount.0);
+                rule = FeeRule::UnbridgedSurcharge;
+            }
+        }
+
+        FeeResult::new(fee, amount, rule, self.get_fee_recipient(token_id))
+    }
+
+    /// Returns the fee percent if the fee is the pure percentage of the amount, so
+    /// the forwarder could cache it. The fees with the tiers, the caps, the flat fees, the dust
+    /// threshold, the promotion, the unbridged surcharge, the amount limits, the free forwards
+    /// left, the pending fee change or the rounding other than the floor one aren't reported.
+    fn cacheable_percent(
+        &self,
+        result: &FeeResult,
+        amount: U128,
+        token_id: &AccountId,
+        network_id: &AccountId,
+    ) -> Option<U64> {
+        let account_id = env::predecessor_account_id();
+        let (percent, rule) = self.fee_percent(amount, token_id, network_id, Some(&account_id));
+        let has_free_forwards = self.free_forwards > 0
+            && self
+                .forwards_count
+                .get(&account_id)
+                .map_or(true, |used| *used < self.free_forwards);
+        let is_linear = matches!(
+            rule,
+            FeeRule::AccountFee | FeeRule::TokenFee | FeeRule::NetworkFee | FeeRule::GlobalFee
+        ) && result.rule == rule.as_str()
+            && self.rounding_mode == RoundingMode::Floor
+            && self.pending_fee_change.is_none()
+            && self.token_fee_caps.get(token_id).is_none()
+            && self.token_flat_fees.get(token_id).is_none()
+            && self.usd_flat_fee.is_none()
+            && self.dust_thresholds.get(token_id).is_none()
+            && self.promotions.get(token_id).is_none()
+            && self.unbridged_surcharges.get(token_id).is_none()
+            && self.get_min_amount(network_id, token_id).is_none()
+            && self.get_max_amount(token_id).is_none()
+            && !has_free_forwards;
+
+        percent.filter(|_| is_linear)
+    }
+
+    /// Returns the rule if the amount shouldn't be forwarded, because it's out of the allowed
+    /// range of the token.
+    fn rejection_rule(
+        &self,
+        amount: U128,
+        token_id: &AccountId,
+        network_id: &AccountId,
+    ) -> Option<FeeRule> {
+        if self.is_below_min_amount(amount, token_id, network_id) {
+            return Some(FeeRule::BelowMinAmount);
+        }
+
+        self.get_max_amount(token_id)
+            .is_some_and(|max_amount| amount.0 > max_amount.0)
+            .then_some(FeeRule::AboveMaxAmount)
+    }
+
+    fn is_below_min_amount(
+        &self,
+        U128(amount): U128,
+        token_id: &AccountId,
+        network_id: &AccountId,
+    ) -> bool {
+        self.get_min_amount(network_id, token_id)
+            .is_some_and(|U128(min_amount)| amount < min_amount)
+    }
+
+    /// Returns the unbridged surcharge of the token if it hasn't been charged for the network
+    /// yet and the token isn't bridged to it, and marks it as charged.
+    fn take_unbridged_surcharge(
+        &mut self,
+        token_id: &AccountId,
+        network_id: &AccountId,
+    ) -> Option<u128> {
+        let U128(surcharge) = *self.unbridged_surcharges.get(token_id)?;
+
+        self.charged_surcharges
+            .insert((network_id.clone(), token_id.clone()))
+            .then_some(surcharge)
+    }
+
+    /// Returns the fee and the rule which has been applied to calculate it. Consumes the free
+    /// forward of the forwarder if the fee isn't zero.
+    fn fee(
+        &mut self,
+        amount: U128,
+        token_id: &AccountId,
+        network_id: &AccountId,
+        target_address: Address,
+    ) -> (u128, FeeRule) {
+        if self
+            .promotions
+            .get(token_id)
+            .is_some_and(Promotion::is_expired)
+        {
+            self.promotions.remove(token_id);
+        }
+
+        let account_id = env::predecessor_account_id();
+        let (fee, rule) = self.quote_fee(
+            amount,
+            token_id,
+            network_id,
+            target_address,
+            Some(&account_id),
+        );
+
+        if fee > 0 && self.use_free_forward() {
+            (0, FeeRule::FreeForward)
+        } else {
+            (fee, rule)
+        }
+    }
+
+    /// Returns the fee and the rule without changing the state, so the free forwards aren't
+    /// taken into account. The `account_id` is the account which requests the fee.
+    fn quote_fee(
+        &self,
+        amount: U128,
+        token_id: &AccountId,
+        network_id: &AccountId,
+        target_address: Address,
+        account_id: Option<&AccountId>,
+    ) -> (u128, FeeRule) {
+        if !self.is_network_registered(network_id) {
+            return (0, FeeRule::UnsupportedNetwork);
+        }
+
+        let is_listed = self.is_token_supported(token_id, network_id);
+
+        if !is_listed && self.unlisted_token_percent.is_none() {
+            return (0, FeeRule::UnsupportedToken);
+        }
+
+        if self.suspended_tokens.contains(token_id) {
+            return (0, FeeRule::SuspendedToken);
+        }
+
+        if self.exempt_addresses.contains(&target_address) {
+            return (0, FeeRule::ExemptAddress);
+        }
+
+        if let Some(percent) = self.unlisted_token_percent.filter(|_| !is_listed) {
+            let fee = calculate_percent_fee(amount, percent, self.rounding_mode);
+            return (fee.min(amount.0), FeeRule::UnlistedTokenFee);
+        }
+
+        if self
+            .promotions
+            .get(token_id)
+            .is_some_and(Promotion::is_active)
+        {
+            return (0, FeeRule::Promotion);
+        }
+
+        if self
+            .dust_thresholds
+            .get(token_id)
+            .is_some_and(|U128(threshold)| amount.0 < *threshold)
+        {
+            return (0, FeeRule::DustWaiver);
+        }
+
+        let (percent, rule) = self.fee_percent(amount, token_id, network_id, account_id);
+        let percent_fee = percent.map_or(0, |percent| {
+            let fee = calculate_percent_fee(amount, percent, self.rounding_mode);
+            self.token_fee_caps
+                .get(token_id)
+                .map_or(fee, |caps| caps.clamp(fee))
+        });
+        let flat_fee = self
+            .token_flat_fees
+            .get(token_id)
+            .map(|U128(flat_fee)| *flat_fee)
+            .or_else(|| self.usd_flat_fee_in_tokens(token_id))
+            .unwrap_or_default();
+
+        (percent_fee.saturating_add(flat_fee).min(amount.0), rule)
+    }
+
+    /// Returns the USD flat fee converted to the token units by the latest price of the token.
+    /// The fee is skipped if there is no price of the token or the price is stale.
+    fn usd_flat_fee_in_tokens(&self, token_id: &AccountId) -> Option<u128> {
+        let U128(cents) = self.usd_flat_fee?;
+        let price = self.token_prices.get(token_id)?;
+
+        if price.is_stale(env::block_timestamp(), self.max_price_age) {
+            return None;
+        }
+
+        price.usd_cents_to_tokens(cents, self.rounding_mode)
+    }
+
+    /// Returns the fee for the native NEAR and the rule which has been applied to calculate it.
+    fn native_fee(&self, amount: U128, target_address: Address) -> (u128, FeeRule) {
+        if self.exempt_addresses.contains(&target_address) {
+            return (0, FeeRule::ExemptAddress);
+        }
+
+        let percent_fee = self.native_percent.map_or(0, |percent| {
+            calculate_percent_fee(amount, percent, self.rounding_mode)
+        });
+        let flat_fee = self.native_flat_fee.map_or(0, |U128(flat_fee)| flat_fee);
+
+        let fee = percent_fee.saturating_add(flat_fee).min(amount.0);
+
+        (fee, FeeRule::NativeFee)
+    }
+
+    /// The tiers of the token have a priority over the percent of the token, which has
+    /// a priority over the percent of the network, which in turn has a priority over
+    /// the global percent.
+    fn fee_percent(
+        &self,
+        U128(amount): U128,
+        token_id: &AccountId,
+        network_id: &AccountId,
+        account_id: Option<&AccountId>,
+    ) -> (Option<U64>, FeeRule) {
+        if let Some(percent) = account_id.and_then(|account_id| self.account_fees.get(account_id)) {
+            return (*percent, FeeRule::AccountFee);
+        }
+
+        if let Some(tiers) = self.token_fee_tiers.get(token_id) {
+            let percent = tiers
+                .iter()
+                .find(|tier| tier.up_to.map_or(true, |U128(up_to)| amount < up_to))
+                .and_then(|tier| tier.percent);
+            return (percent, FeeRule::TokenTiers);
+        }
+
+        if let Some(percent) = self.token_fees.get(token_id) {
+            return (*percent, FeeRule::TokenFee);
+        }
+
+        if let Some(percent) = self.network_fees.get(network_id) {
+            return (*percent, FeeRule::NetworkFee);
+        }
+
+        (self.current_percent(), FeeRule::GlobalFee)
+    }
+}
+
+/// The rounding of the fee divisions.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum RoundingMode {
+    /// Rounds down, in favor of the user.
+    Floor,
+    /// Rounds up, in favor of the protocol.
+    Ceil,
+    /// Rounds to the nearest, the halves are rounded up.
+    HalfUp,
+}
+
+impl RoundingMode {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Floor => "floor",
+            Self::Ceil => "ceil",
+            Self::HalfUp => "half_up",
+        }
+    }
+
+    /// Divides the numerator by the non-zero denominator with the rounding.
+    #[must_use]
+    pub const fn div(self, numerator: u128, denominator: u128) -> u128 {
+        let quotient = numerator / denominator;
+        let remainder = numerator % denominator;
+        let round_up = match self {
+            Self::Floor => false,
+            Self::Ceil => remainder > 0,
+            Self::HalfUp => remainder > 0 && remainder >= denominator - remainder,
+        };
+
+        if round_up {
+            quotient + 1
+        } else {
+            quotient
+        }
+    }
+}
+
+/// Roles which allow to manage the contract along with the owner.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    /// Allows to change the fee configuration.
+    FeeSetter,
+    /// Allows to add and remove supported tokens.
+    TokenManager,
+    /// Marks the factory, whose direct sub-accounts are the forwarders, which are allowed
+    /// to record the forwards.
+    Factory,
+}
+
+/// Volume tier of the fee. The tier is applied to amounts less than `up_to`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeTier {
+    pub up_to: Option<U128>,
+    pub percent: Option<String>,
+}
+
+/// The minimum and maximum of the percentage fee in the token units.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeCaps {
+    pub min: Option<U128>,
+    pub max: Option<U128>,
+}
+
+impl FeeCaps {
+    fn clamp(&self, fee: u128) -> u128 {
+        let fee = self.min.map_or(fee, |U128(min)| fee.max(min));
+        self.max.map_or(fee, |U128(max)| fee.min(max))
+    }
+}
+
+/// The time window in block timestamps in nanoseconds, while the fee for the token is zero.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Promotion {
+    pub start: U64,
+    pub end: U64,
+}
+
+impl Promotion {
+    fn is_active(&self) -> bool {
+        (self.start.0..self.end.0).contains(&env::block_timestamp())
+    }
+
+    fn is_expired(&self) -> bool {
+        env::block_timestamp() >= self.end.0
+    }
+}
+
+/// The metadata of the NEP-141 token, which is returned by the `ft_metadata`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// The metadata of the supported token.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMetadata {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// The request to list the token made by a third party.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenRegistration {
+    /// The account which has registered the token and receives the refund of the bond.
+    pub account_id: AccountId,
+    /// The attached bond in yoctoNEAR.
+    pub bond: U128,
+}
+
+#[ext_contract(ext_token)]
+pub trait ExtToken {
+    fn ft_metadata(&self) -> FtMetadata;
+}
+
+/// The result of the fee calculation.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeResult {
+    /// The fee which should be transferred to the `fee_recipient`.
+    pub fee: U128,
+    /// The amount left after the fee.
+    pub net_amount: U128,
+    pub fee_recipient: AccountId,
+    /// The rule which has been applied to calculate the fee.
+    pub rule: String,
+    /// `true` if the amount is below the minimum of the token for the network, above the
+    /// maximum of the token or the target address is invalid, so it shouldn't be forwarded.
+    pub rejected: bool,
+    /// `true` if the target address is invalid, e.g. zero, so the tokens would be lost.
+    pub invalid_address: bool,
+    /// The fee percent if the fee is the pure percentage of the amount, so the forwarder
+    /// could cache it. It's reported only by the `record_and_calculate_fees`.
+    pub percent: Option<U64>,
+}
+
+impl FeeResult {
+    fn new(fee: u128, amount: U128, rule: FeeRule, fee_recipient: AccountId) -> Self {
+        Self {
+            fee: fee.into(),
+            net_amount: amount.0.saturating_sub(fee).into(),
+            fee_recipient,
+            rule: rule.as_str().to_string(),
+            rejected: false,
+            invalid_address: false,
+            percent: None,
+        }
+    }
+
+    fn rejected(amount: U128, rule: FeeRule) -> Self {
+        Self {
+            rejected: true,
+            ..Self::new(0, amount, rule, env::current_account_id())
+        }
+    }
+
+    fn invalid_address(amount: U128) -> Self {
+        Self {
+            rejected: true,
+            invalid_address: true,
+            ..Self::new(
+                0,
+                amount,
+                FeeRule::InvalidAddress,
+                env::current_account_id(),
+            )
+        }
+    }
+}
+
+/// The fee quote with the amounts formatted with the decimals of the token.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeQuote {
+    /// The fee result in the token units.
+    pub result: FeeResult,
+    /// The quoted amount, e.g. `1.5`.
+    pub amount: String,
+    pub fee: String,
+    pub net_amount: String,
+    /// The symbol of the token, `None` if the metadata of the token is unknown.
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+/// The rule which has been applied to calculate the fee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeeRule {
+    InvalidAddress,
+    BelowMinAmount,
+    AboveMaxAmount,
+    UnsupportedNetwork,
+    UnsupportedToken,
+    SuspendedToken,
+    ExemptAddress,
+    UnlistedTokenFee,
+    Promotion,
+    DustWaiver,
+    FreeForward,
+    UnbridgedSurcharge,
+    AccountFee,
+    TokenTiers,
+    TokenFee,
+    NetworkFee,
+    GlobalFee,
+    NativeFee,
+    NearFee,
+}
+
+impl FeeRule {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::InvalidAddress => "invalid_address",
+            Self::BelowMinAmount => "below_min_amount",
+            Self::AboveMaxAmount => "above_max_amount",
+            Self::UnsupportedNetwork => "unsupported_network",
+            Self::UnsupportedToken => "unsupported_token",
+            Self::SuspendedToken => "suspended_token",
+            Self::ExemptAddress => "exempt_address",
+            Self::UnlistedTokenFee => "unlisted_token_fee",
+            Self::Promotion => "promotion",
+            Self::DustWaiver => "dust_waiver",
+            Self::FreeForward => "free_forward",
+            Self::UnbridgedSurcharge => "unbridged_surcharge",
+            Self::AccountFee => "account_fee",
+            Self::TokenTiers => "token_tiers",
+            Self::TokenFee => "token_fee",
+            Self::NetworkFee => "network_fee",
+            Self::GlobalFee => "global_fee",
+            Self::NativeFee => "native_fee",
+            Self::NearFee => "near_fee",
+        }
+    }
+}
+
+/// The entry of the log of the fee configuration changes.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeHistoryEntry {
+    /// The account which has made the change.
+    pub account_id: AccountId,
+    /// The block timestamp in nanoseconds when the change has taken effect.
+    pub timestamp: U64,
+    /// The changed parameter, e.g. `token_fee`.
+    pub parameter: String,
+    /// The token or the network the parameter belongs to.
+    pub target_id: Option<AccountId>,
+    /// The value before the change, `None` if the value hasn't been set.
+    pub old_value: Option<String>,
+    /// The value after the change, `None` if the value has been removed.
+    pub new_value: Option<String>,
+}
+
+/// The parameter of the fee configuration, which changes are logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeeParameter {
+    FeePercent,
+    TokenFee,
+    NetworkFee,
+    TokenFeeTiers,
+    TokenFlatFee,
+    TokenFeeCaps,
+    UnbridgedSurcharge,
+    NativeFee,
+    NativeFlatFee,
+    UsdFlatFee,
+    RoundingMode,
+    DustThreshold,
+    NearFee,
+    AccountFee,
+    UnlistedTokenFee,
+}
+
+impl FeeParameter {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::FeePercent => "fee_percent",
+            Self::TokenFee => "token_fee",
+            Self::NetworkFee => "network_fee",
+            Self::TokenFeeTiers => "token_fee_tiers",
+            Self::TokenFlatFee => "token_flat_fee",
+            Self::TokenFeeCaps => "token_fee_caps",
+            Self::UnbridgedSurcharge => "unbridged_surcharge",
+            Self::NativeFee => "native_fee",
+            Self::NativeFlatFee => "native_flat_fee",
+            Self::UsdFlatFee => "usd_flat_fee",
+            Self::RoundingMode => "rounding_mode",
+            Self::DustThreshold => "dust_threshold",
+            Self::NearFee => "near_fee",
+            Self::AccountFee => "account_fee",
+            Self::UnlistedTokenFee => "unlisted_token_fee",
+        }
+    }
+}
+
+/// The forwarding statistics of a token or a network.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Stats {
+    /// The number of the recorded fee calculations.
+    pub forwards: u64,
+    /// The total forwarded amount before the fees.
+    pub volume: U128,
+    /// The total quoted fees.
+    pub fees: U128,
+}
+
+impl Stats {
+    fn record(&mut self, amount: u128, fee: u128) {
+        self.forwards = self.forwards.saturating_add(1);
+        self.volume = self.volume.0.saturating_add(amount).into();
+        self.fees = self.fees.0.saturating_add(fee).into();
+    }
+}
+
+/// The fee split between the treasury and the referrer.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeSplit {
+    pub treasury: U128,
+    pub referrer: U128,
+    pub referrer_id: Option<AccountId>,
+}
+
+/// The snapshot of the contract configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Config {
+    pub owner: AccountId,
+    pub proposed_owner: Option<AccountId>,
+    /// The current global fee percent.
+    pub percent: Option<String>,
+    pub pending_fee_change: Option<FeeChange>,
+    /// The delay in seconds of the fee percent increases.
+    pub fee_increase_delay: u64,
+    /// The max change of the fee percent in percentage points made by a single call.
+    pub max_fee_delta: Option<String>,
+    pub paused: bool,
+    pub supported_tokens_count: u64,
+    pub exempt_addresses_count: u64,
+    pub free_forwards: u64,
+    /// The share of the fee in basis points which goes to the referrer.
+    pub referral_share: U64,
+    pub referrers_count: u64,
+}
+
+/// The source metadata of the contract, see the NEP-330.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractSourceMetadata {
+    pub version: String,
+    /// The link to the source code of the contract.
+    pub link: String,
+    pub standards: Vec<Standard>,
+}
+
+/// The standard implemented by the contract.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Standard {
+    pub standard: String,
+    pub version: String,
+}
+
+/// The scheduled change of the global fee percent.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeChange {
+    pub percent: Option<String>,
+    pub effective_at: U64,
+}
+
+#[derive(Clone, Copy, BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct PendingFeeChange {
+    percent: Option<U64>,
+    effective_at: U64,
+}
+
+impl PendingFeeChange {
+    fn is_effective(&self) -> bool {
+        env::block_timestamp() >= self.effective_at.0
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct Tier {
+    up_to: Option<U128>,
+    percent: Option<U64>,
+}
+
+impl Tier {
+    fn to_fee_tier(&self) -> FeeTier {
+        FeeTier {
+            up_to: self.up_to,
+            percent: self.percent.map(format_percent),
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+enum KeyPrefix {
+    SupportedTokens,
+    SupportedTokensList,
+    ForwardsCount,
+    NetworkSupportedTokens(AccountId),
+    NetworkSupportedTokensList(AccountId),
+    FeeHistory,
+    ChargedSurcharges,
+    TokenStats,
+    TokenStatsList,
+    NetworkStats,
+    NetworkStatsList,
+    TokenMetadata,
+    TokenFees,
+    TokenPrices,
+    TokenFeeTiers,
+    TokenFlatFees,
+    TokenFeeCaps,
+    DustThresholds,
+    UnbridgedSurcharges,
+}
+
+impl IntoStorageKey for KeyPrefix {
+    fn into_storage_key(self) -> Vec<u8> {
+        match self {
+            Self::SupportedTokens => b"supported_tokens".to_vec(),
+            Self::SupportedTokensList => b"supported_tokens_list".to_vec(),
+            Self::ForwardsCount => b"forwards_count".to_vec(),
+            Self::FeeHistory => b"fee_history".to_vec(),
+            Self::ChargedSurcharges => b"charged_surcharges".to_vec(),
+            Self::TokenStats => b"token_stats".to_vec(),
+            Self::TokenStatsList => b"token_stats_list".to_vec(),
+            Self::NetworkStats => b"network_stats".to_vec(),
+            Self::NetworkStatsList => b"network_stats_list".to_vec(),
+            Self::TokenMetadata => b"token_metadata".to_vec(),
+            Self::TokenFees => b"token_fees".to_vec(),
+            Self::TokenPrices => b"token_prices".to_vec(),
+            Self::TokenFeeTiers => b"token_fee_tiers".to_vec(),
+            Self::TokenFlatFees => b"token_flat_fees".to_vec(),
+            Self::TokenFeeCaps => b"token_fee_caps".to_vec(),
+            Self::DustThresholds => b"dust_thresholds".to_vec(),
+            Self::UnbridgedSurcharges => b"unbridged_surcharges".to_vec(),
+            // The network id is borsh serialized, so the prefixes of different networks
+            // never overlap.
+            Self::NetworkSupportedTokens(network_id) => {
+                network_key(b"network_supported_tokens", &network_id)
+            }
+            Self::NetworkSupportedTokensList(network_id) => {
+                network_key(b"network_supported_tokens_list", &network_id)
+            }
+        }
+    }
+}
+
+fn network_key(prefix: &[u8], network_id: &AccountId) -> Vec<u8> {
+    let mut key = prefix.to_vec();
+    BorshSerialize::serialize(network_id, &mut key)
+        .unwrap_or_else(|_| env::panic_str("Couldn't serialize network id"));
+    key
+}
+
+/// Requests the `ft_metadata` of the token and adds the token in the callback.
+fn validate_token(token_id: AccountId) -> Promise {
+    ext_token::ext(token_id.clone())
+        .with_static_gas(FT_METADATA_GAS)
+        .ft_metadata()
+        .then(
+            FeesCalculator::ext(env::current_account_id())
+                .with_static_gas(ON_FT_METADATA_GAS)
+                .on_ft_metadata(token_id),
+        )
+}
+
+/// Skips `from_index` items of the iterator and takes not more than `limit` of them.
+fn paginate<I: Iterator>(
+    iter: I,
+    from_index: Option<u64>,
+    limit: Option<u64>,
+) -> impl Iterator<Item = I::Item> {
+    let from_index = from_index.map_or(0, |v| usize::try_from(v).unwrap_or(usize::MAX));
+    let limit = limit.map_or(usize::MAX, |v| usize::try_from(v).unwrap_or(usize::MAX));
+
+    iter.skip(from_index).take(limit)
+}
+
+/// Returns `false` for the zero address, which is the default value of the uninitialized
+/// addresses, so the tokens sent to it would be lost.
+fn is_valid_address(address: Address) -> bool {
+    address != Address::zero()
+}
+
+fn parse_address(address: &str) -> Address {
+    Address::decode(address.trim_start_matches("0x"))
+        .unwrap_or_else(|e| env::panic_str(&format!("Couldn't parse address: {e}")))
+}
+
+fn format_address(address: &Address) -> String {
+    format!("0x{}", address.encode())
+}
+
+/// Returns the share of the fee in basis points, rounded down.
+fn calculate_share(fee: u128, share: u64) -> u128 {
+    let share = u128::from(share);
+    let max_bps = u128::from(MAX_BPS);
+
+    // Split the fee to avoid the overflow of the multiplication.
+    (fee / max_bps) * share + (fee % max_bps) * share / max_bps
+}
+
+fn calculate_percent_fee(U128(amount): U128, U64(percent): U64, rounding: RoundingMode) -> u128 {
+    let fee = rounding.div(
+        u128::from(percent).checked_mul(amount).unwrap_or_default(),
+        MAX_PERCENT,
+    );
+
+    // if the fee was computed to `0`
+    // i.e. because the amount was too small
+    // we substitute it with our minimal possible fee, which is `1`
+    if fee == 0 {
+        1
+    } else {
+        fee
+    }
+}
+
+/// Formats the percent with at least 2 and at most 4 decimals, e.g. `5.00` or `0.125`.
+#[allow(clippy::cast_precision_loss)]
+fn format_percent(U64(percent): U64) -> String {
+    let mut result = format!("{:.4}", percent as f64 / 10000.0);
+    let len = result.trim_end_matches('0').len().max(result.len() - 2);
+    result.truncate(len);
+    result
+}
+
+/// Formats the amount in the token units with the decimals of the token, e.g. `1.5` for
+/// `1500000` with 6 decimals.
+fn format_amount(amount: u128, decimals: u8) -> String {
+    let decimals = usize::from(decimals);
+    let digits = format!("{amount:0>width$}", width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{integer}.{fraction}")
+    }
+}
+
+/// Returns `true` if the new percent is higher than the old one, where the disabled fee is zero.
+fn is_fee_increase(old: Option<U64>, new: Option<U64>) -> bool {
+    new.map_or(0, |U64(percent)| percent) > old.map_or(0, |U64(percent)| percent)
+}
+
+/// Returns the difference between the old and the new percent, where the disabled fee is zero.
+fn fee_delta(old: Option<U64>, new: Option<U64>) -> u64 {
+    new.map_or(0, |U64(percent)| percent)
+        .abs_diff(old.map_or(0, |U64(percent)| percent))
+}
+
+/// Formats the percent for the log of the fee changes, where the disabled fee is zero.
+fn percent_value(percent: Option<U64>) -> String {
+    format_percent(percent.unwrap_or(U64(0)))
+}
+
+fn tiers_value(tiers: &[Tier]) -> String {
+    let tiers: Vec<_> = tiers.iter().map(Tier::to_fee_tier).collect();
+    serde_json::to_string(&tiers).unwrap_or_default()
+}
+
+fn caps_value(caps: &FeeCaps) -> String {
+    serde_json::to_string(caps).unwrap_or_default()
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn parse_percent(percent: Option<&str>) -> Result<Option<U64>, ParseError> {
+    let Some(percent) = percent else {
+        return Ok(None);
+    };
+
+    validate_decimal_part(percent)?;
+
+    let result = f64::from_str(percent)
+        .map(|p| (p * 10000.0).round() as u64) // as conversion is safe here because we validate the number of decimals
+        .map_err(ParseError::ParseFloat)?;
+
+    validate_percent(result)
+}
+
+/// Checks that the fee in 1/100 of a basis point is in the allowed range. Zero means no fee.
+const fn validate_percent(percent: u64) -> Result<Option<U64>, ParseError> {
+    if percent == 0 {
+        Ok(None)
+    } else if percent < MIN_FEE_PERCENT {
+        Err(ParseError::TooLowPercent)
+    } else if percent > MAX_FEE_PERCENT {
+        Err(ParseError::TooHighPercent)
+    } else {
+        Ok(Some(U64(percent)))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    ParseFloat(ParseFloatError),
+    TooLowPercent,
+    TooHighPercent,
+    TooManyDecimals,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        #[allow(deprecated)]
+        let msg = match self {
+            Self::ParseFloat(error) => error.description(),
+            Self::TooLowPercent => "provided percent is less than 0.0001%",
+            Self::TooHighPercent => "provided percent is more than 10%",
+            Self::TooManyDecimals => "provided percent could contain only 4 decimals",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+fn validate_decimal_part(percent: &str) -> Result<(), ParseError> {
+    match percent.split_once('.') {
+        Some((_, decimal)) if decimal.len() > 4 => Err(ParseError::TooManyDecimals),
+        _ => Ok(()), // no decimals or the number of decimals is less or equal 4.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::error::FeesError;
+    use super::multisig::{Multisig, Proposal};
+    use super::oracle::{AssetOptionalPrice, Price, PriceData};
+    use super::{
+        calculate_percent_fee, calculate_share, format_amount, parse_percent, Config,
+        ContractSourceMetadata, FeeCaps, FeeChange, FeeHistoryEntry, FeeQuote, FeeResult, FeeSplit,
+        FeeTier, FeesCalculator, FtMetadata, ParseError, Promotion, Role, RoundingMode, Stats,
+        TokenMetadata, TokenRegistration,
+    };
+    use aurora_engine_types::types::Address;
+    use near_sdk::serde_json;
+    use near_sdk::test_utils::{get_logs, VMContextBuilder};
+    use near_sdk::{testing_env, AccountId, NearToken, PromiseError};
+
+    fn ft_metadata(spec: &str) -> FtMetadata {
+        FtMetadata {
+            spec: spec.to_string(),
+            name: "Tether USD".to_string(),
+            symbol: "USDT".to_string(),
+            decimals: 6,
+        }
+    }
+
+    /// Returns the valid target address.
+    const fn receiver() -> Address {
+        Address::from_array([1; 20])
+    }
+
+    /// Grants the `Factory` role to the `near`, so the default predecessor `bob.near` is
+    /// allowed to record the forwards.
+    fn allow_recording(contract: &mut FeesCalculator) {
+        contract.grant_role(Role::Factory, "near".parse().unwrap());
+    }
+
+    /// Adds the token and simulates the successful `ft_metadata` callback.
+    fn add_token(contract: &mut FeesCalculator, token_id: &AccountId) {
+        let _ = contract.add_supported_token(token_id.clone()).unwrap();
+        assert!(contract.on_ft_metadata(token_id.clone(), Ok(ft_metadata("ft-1.0.0"))));
+    }
+
+    #[test]
+    fn test_parse_percent() {
+        assert_eq!(parse_percent(None).unwrap(), None);
+        assert_eq!(parse_percent(Some("0")).unwrap(), None);
+        assert_eq!(parse_percent(Some("10")).unwrap(), Some(100_000.into()));
+        assert_eq!(parse_percent(Some("2")).unwrap(), Some(20_000.into()));
+        assert_eq!(parse_percent(Some("0.25")).unwrap(), Some(2500.into()));
+        assert_eq!(parse_percent(Some("0.01")).unwrap(), Some(100.into()));
+        assert_eq!(parse_percent(Some("0.125")).unwrap(), Some(1250.into()));
+        assert_eq!(parse_percent(Some("0.29")).unwrap(), Some(2900.into()));
+        assert_eq!(parse_percent(Some("0.0001")).unwrap(), Some(1.into()));
+        assert!(matches!(
+            parse_percent(Some("0.00015")).err(),
+            Some(ParseError::TooManyDecimals)
+        ));
+        assert!(matches!(
+            parse_percent(Some("0.00009")).err(),
+            Some(ParseError::TooManyDecimals)
+        ));
+        assert!(matches!(
+            parse_percent(Some("10.1")).err(),
+            Some(ParseError::TooHighPercent)
+        ));
+        assert!(matches!(
+            parse_percent(Some("hello")).err(),
+            Some(ParseError::ParseFloat(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_supported_tokens() {
+        let aurora = "aurora".parse().unwrap();
+        let target_address = receiver();
+        let usdt = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            0.into() // we don't support the `usdt.near` yet, so we get 0 here
+        );
+
+        add_token(&mut contract, &usdt);
+
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            50.into()
+        );
+
+        contract.remove_supported_token(&usdt).unwrap();
+
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            0.into()
+        );
+    }
+
+    #[test]
+    fn test_unlisted_token_fee() {
+        let aurora = "aurora".parse().unwrap();
+        let usdt = "usdt.near".parse().unwrap();
+        let exempt_address = Address::decode("1111111111111111111111111111111111111111").unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        assert_eq!(contract.get_unlisted_token_fee(), None);
+
+        contract.set_unlisted_token_fee(Some("1".to_string()));
+        assert_eq!(contract.get_unlisted_token_fee(), Some("1.00".to_string()));
+
+        let result = contract.calculate_fees(1000.into(), &usdt, &aurora, receiver());
+        assert_eq!(result.fee, 10.into());
+        assert_eq!(result.rule, "unlisted_token_fee");
+
+        contract.add_exempt_address(exempt_address.encode());
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, exempt_address)
+                .rule,
+            "exempt_address"
+        );
+
+        // The supported token is charged with the regular fee.
+        add_token(&mut contract, &usdt);
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            50.into()
+        );
+
+        contract.remove_supported_token(&usdt).unwrap();
+        contract.set_unlisted_token_fee(None);
+        let result = contract.calculate_fees(1000.into(), &usdt, &aurora, receiver());
+        assert_eq!(result.fee, 0.into());
+        assert_eq!(result.rule, "unsupported_token");
+    }
+
+    #[test]
+    fn test_fee_is_never_rounded_to_zero_for_small_numbers() {
+        let aurora = "aurora".parse().unwrap();
+        let target_address = receiver();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_fee_percent(Some("5".to_string())).unwrap();
+
+        add_token(&mut contract, &usdt);
+
+        assert_eq!(
+            contract
+                .calculate_fees(1.into(), &usdt, &aurora, target_address)
+                .fee,
+            1.into()
+        );
+    }
+
+    #[test]
+    fn test_check_set_fee() {
+        let aurora = "aurora".parse().unwrap();
+        let target_address = receiver();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            50.into()
+        );
+
+        contract.set_fee_percent(Some("0".to_string())).unwrap();
+
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            0.into()
+        );
+
+        contract.set_fee_percent(Some("2.5".to_string())).unwrap();
+
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            25.into()
+        );
+    }
+
+    #[test]
+    fn test_set_percent() {
+        let mut contract = FeesCalculator::new(vec![]);
+
+        assert_eq!(contract.get_fee_percent(), Some("5.00".to_string()));
+        contract.set_fee_percent(Some("6".to_string())).unwrap();
+        assert_eq!(contract.get_fee_percent(), Some("6.00".to_string()));
+        contract.set_fee_percent(Some("7.5".to_string())).unwrap();
+        assert_eq!(contract.get_fee_percent(), Some("7.50".to_string()));
+        contract.set_fee_percent(Some("0".to_string())).unwrap();
+        assert_eq!(contract.get_fee_percent(), None);
+    }
+
+    #[test]
+    fn test_token_fee_overrides_global_percent() {
+        let aurora = "aurora".parse().unwrap();
+        let target_address = receiver();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone(), wnear.clone()]);
+
+        contract.set_token_fee(usdt.clone(), Some("0.5".to_string()));
+
+        assert_eq!(
+            contract.get_token_fee_percent(&usdt),
+            Some("0.50".to_string())
+        );
+        assert_eq!(
+            contract.get_token_fee_percent(&wnear),
+            Some("5.00".to_string())
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            5.into()
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &wnear, &aurora, target_address)
+                .fee,
+            50.into()
+        );
+
+        contract.set_token_fee(usdt.clone(), None);
+
+        assert_eq!(contract.get_token_fee_percent(&usdt), None);
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            0.into()
+        );
+
+        contract.remove_token_fee(&usdt);
+
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            50.into()
+        );
+    }
+
+    #[test]
+    fn test_account_fee() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let exchange: AccountId = "exchange.near".parse().unwrap();
+        let target_address = receiver();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        contract.set_token_fee(usdt.clone(), Some("2".to_string()));
+
+        contract.set_account_fee(exchange.clone(), Some("0.5".to_string()));
+        assert_eq!(
+            contract.get_account_fee_percent(&exchange),
+            Some("0.50".to_string())
+        );
+        assert_eq!(contract.get_account_fee_percent(&aurora), None);
+        assert_eq!(
+            contract.account_fees(),
+            vec![(&exchange, Some("0.50".to_string()))]
+        );
+
+        // The percent of the account has a priority over the percent of the token.
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(exchange.clone())
+            .build());
+        let result = contract.calculate_fees(1000.into(), &usdt, &aurora, target_address);
+        assert_eq!(result.fee, 5.into());
+        assert_eq!(result.rule, "account_fee");
+        assert_eq!(
+            contract
+                .quote_fees(
+                    1000.into(),
+                    &usdt,
+                    &aurora,
+                    target_address.encode(),
+                    Some(exchange.clone())
+                )
+                .result
+                .fee,
+            5.into()
+        );
+
+        testing_env!(VMContextBuilder::new().build());
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            20.into()
+        );
+
+        contract.remove_account_fee(&exchange);
+        assert!(contract.account_fees().is_empty());
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(exchange)
+            .build());
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            20.into()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing to remove, account: exchange.near has no fee override")]
+    fn test_remove_missing_account_fee() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.remove_account_fee(&"exchange.near".parse().unwrap());
+    }
+
+    #[test]
+    fn test_network_fee() {
+        let silo = "silo.near".parse().unwrap();
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let target_address = receiver();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone(), wnear.clone()]);
+
+        contract.set_network_fee(aurora.clone(), Some("1".to_string()));
+
+        assert_eq!(
+            contract.get_network_fee_percent(&aurora),
+            Some("1.00".to_string())
+        );
+        assert_eq!(
+            contract.get_network_fee_percent(&silo),
+            Some("5.00".to_string())
+        );
+        assert_eq!(
+            contract.network_fees(),
+            vec![(&aurora, Some("1.00".to_string()))]
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            10.into()
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &silo, target_address)
+                .fee,
+            50.into()
+        );
+
+        // The percent of the token has a priority over the percent of the network.
+        contract.set_token_fee(wnear.clone(), Some("2".to_string()));
+
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &wnear, &aurora, target_address)
+                .fee,
+            20.into()
+        );
+
+        contract.remove_network_fee(&aurora);
+
+        assert!(contract.network_fees().is_empty());
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            50.into()
+        );
+    }
+
+    #[test]
+    fn test_token_fee_tiers() {
+        let aurora = "aurora".parse().unwrap();
+        let target_address = receiver();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        let tiers = vec![
+            FeeTier {
+                up_to: Some(1000.into()),
+                percent: Some("5".to_string()),
+            },
+            FeeTier {
+                up_to: Some(100_000.into()),
+                percent: Some("2".to_string()),
+            },
+            FeeTier {
+                up_to: None,
+                percent: Some("0.5".to_string()),
+            },
+        ];
+
+        contract.set_token_fee(usdt.clone(), Some("1".to_string()));
+        contract.set_token_fee_tiers(usdt.clone(), tiers);
+
+        assert_eq!(
+            contract
+                .calculate_fees(999.into(), &usdt, &aurora, target_address)
+                .fee,
+            49.into()
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            20.into()
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(99_999.into(), &usdt, &aurora, target_address)
+                .fee,
+            1999.into()
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(100_000.into(), &usdt, &aurora, target_address)
+                .fee,
+            500.into()
+        );
+        assert_eq!(
+            contract.get_token_fee_tiers(&usdt).unwrap()[2],
+            FeeTier {
+                up_to: None,
+                percent: Some("0.50".to_string()),
+            }
+        );
+
+        contract.remove_token_fee_tiers(&usdt);
+
+        assert_eq!(contract.get_token_fee_tiers(&usdt), None);
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            10.into()
+        );
+    }
+
+    #[test]
+    fn test_cacheable_percent() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let target_address = receiver();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        allow_recording(&mut contract);
+
+        let result =
+            contract.record_and_calculate_fees(1000.into(), &usdt, &aurora, target_address);
+        assert_eq!(result.percent, Some(crate::DEFAULT_PERCENT));
+        // The quotes don't report the percent.
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .percent,
+            None
+        );
+
+        // The flat fee makes the fee non-linear.
+        contract.set_token_flat_fee(usdt.clone(), Some(100.into()));
+        let result =
+            contract.record_and_calculate_fees(1000.into(), &usdt, &aurora, target_address);
+        assert_eq!(result.fee, 150.into());
+        assert_eq!(result.percent, None);
+
+        contract.set_token_flat_fee(usdt.clone(), None);
+        contract.set_max_amount(usdt.clone(), Some(10_000.into()));
+        let result =
+            contract.record_and_calculate_fees(1000.into(), &usdt, &aurora, target_address);
+        assert_eq!(result.percent, None);
+    }
+
+    #[test]
+    fn test_token_flat_fee() {
+        let aurora = "aurora".parse().unwrap();
+        let target_address = receiver();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.set_token_flat_fee(usdt.clone(), Some(100.into()));
+
+        assert_eq!(contract.get_token_flat_fee(&usdt), Some(100.into()));
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            150.into()
+        );
+        // The fee never exceeds the amount.
+        assert_eq!(
+            contract
+                .calculate_fees(50.into(), &usdt, &aurora, target_address)
+                .fee,
+            50.into()
+        );
+
+        contract.set_fee_percent(None).unwrap();
+
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            100.into()
+        );
+
+        contract.set_token_flat_fee(usdt.clone(), None);
+
+        assert_eq!(contract.get_token_flat_fee(&usdt), None);
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            0.into()
+        );
+    }
+
+    #[test]
+    fn test_token_fee_caps() {
+        let aurora = "aurora".parse().unwrap();
+        let target_address = receiver();
+        let usdc: AccountId = "usdc.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdc.clone()]);
+
+        contract.set_token_fee_caps(usdc.clone(), Some(100_000.into()), Some(500_000_000.into()));
+
+        assert_eq!(
+            contract.get_token_fee_caps(&usdc),
+            Some(&FeeCaps {
+                min: Some(100_000.into()),
+                max: Some(500_000_000.into()),
+            })
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1_000_000.into(), &usdc, &aurora, target_address)
+                .fee,
+            100_000.into()
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1_000_000_000.into(), &usdc, &aurora, target_address)
+                .fee,
+            50_000_000.into()
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(100_000_000_000.into(), &usdc, &aurora, target_address)
+                .fee,
+            500_000_000.into()
+        );
+
+        // The caps are applied to the percentage fee only.
+        contract.set_fee_percent(None).unwrap();
+
+        assert_eq!(
+            contract
+                .calculate_fees(1_000_000.into(), &usdc, &aurora, target_address)
+                .fee,
+            0.into()
+        );
+
+        contract.set_token_fee_caps(usdc.clone(), None, None);
+
+        assert_eq!(contract.get_token_fee_caps(&usdc), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The minimum fee can't be greater than the maximum fee")]
+    fn test_inverted_token_fee_caps() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_token_fee_caps(
+            "usdc.near".parse().unwrap(),
+            Some(1000.into()),
+            Some(100.into()),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Tiers must be sorted by the upper bound in ascending order")]
+    fn test_unsorted_token_fee_tiers() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_token_fee_tiers(
+            "usdt.near".parse().unwrap(),
+            vec![
+                FeeTier {
+                    up_to: Some(1000.into()),
+                    percent: Some("5".to_string()),
+                },
+                FeeTier {
+                    up_to: Some(100.into()),
+                    percent: Some("2".to_string()),
+                },
+                FeeTier {
+                    up_to: None,
+                    percent: Some("1".to_string()),
+                },
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The last tier must be unbounded")]
+    fn test_bounded_last_token_fee_tier() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_token_fee_tiers(
+            "usdt.near".parse().unwrap(),
+            vec![FeeTier {
+                up_to: Some(1000.into()),
+                percent: Some("5".to_string()),
+            }],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing to remove, token: usdt.near has no fee override")]
+    fn test_remove_missing_token_fee() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.remove_token_fee(&"usdt.near".parse().unwrap());
+    }
+
+    #[test]
+    fn test_ownership_transfer() {
+        let owner: AccountId = "owner.near".parse().unwrap();
+        let new_owner: AccountId = "new-owner.near".parse().unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(owner.clone())
+            .build());
+        let mut contract = FeesCalculator::new(vec![]);
+
+        contract.propose_owner(Some(new_owner.clone()));
+
+        assert_eq!(contract.get_owner(), &owner);
+        assert_eq!(contract.get_proposed_owner(), Some(&new_owner));
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(new_owner.clone())
+            .build());
+        contract.accept_ownership();
+
+        assert_eq!(contract.get_owner(), &new_owner);
+        assert_eq!(contract.get_proposed_owner(), None);
+
+        contract.set_fee_percent(Some("1".to_string())).unwrap();
+
+        assert_eq!(contract.get_fee_percent(), Some("1.00".to_string()));
+    }
+
+    #[test]
+    fn test_fee_delegation() {
+        let bot: AccountId = "ops-bot.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+
+        contract.delegate_fee_setter(bot.clone(), 200.into());
+        assert_eq!(contract.get_fee_delegation(&bot), Some(200.into()));
+        assert_eq!(contract.active_fee_delegations(), vec![(&bot, 200.into())]);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bot.clone())
+            .block_timestamp(199)
+            .build());
+        contract.set_fee_percent(Some("1".to_string())).unwrap();
+        assert_eq!(contract.get_fee_percent(), Some("1.00".to_string()));
+
+        // The delegation has lapsed.
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bot.clone())
+            .block_timestamp(200)
+            .build());
+        assert_eq!(contract.get_fee_delegation(&bot), None);
+        assert!(contract.active_fee_delegations().is_empty());
+
+        testing_env!(VMContextBuilder::new().block_timestamp(200).build());
+        contract.delegate_fee_setter(bot.clone(), 300.into());
+        contract.revoke_fee_delegation(&bot);
+        assert_eq!(contract.get_fee_delegation(&bot), None);
+    }
+
+    #[test]
+    fn test_expired_fee_delegation() {
+        let bot: AccountId = "ops-bot.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.delegate_fee_setter(bot.clone(), 200.into());
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bot)
+            .block_timestamp(200)
+            .build());
+        assert_eq!(
+            contract.set_fee_percent(Some("1".to_string())),
+            Err(FeesError::Unauthorized {
+                role: Role::FeeSetter,
+                action: "set fee percent",
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Delegation must expire in the future")]
+    fn test_delegate_fee_setter_in_past() {
+        let mut contract = FeesCalculator::new(vec![]);
+
+        testing_env!(VMContextBuilder::new().block_timestamp(200).build());
+        contract.delegate_fee_setter("ops-bot.near".parse().unwrap(), 200.into());
+    }
+
+    fn multisig(signers: &[&AccountId], threshold: u32) -> Multisig {
+        Multisig {
+            signers: signers.iter().map(|&signer| signer.clone()).collect(),
+            threshold,
+        }
+    }
+
+    #[test]
+    fn test_multisig() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let carol: AccountId = "carol.near".parse().unwrap();
+        let dave: AccountId = "dave.near".parse().unwrap();
+        let signers = multisig(&[&carol, &dave], 2);
+        let mut contract = FeesCalculator::new(vec![]);
+
+        contract.set_multisig(Some(signers.clone()));
+        assert_eq!(contract.get_multisig(), Some(&signers));
+
+        // The owner couldn't change the fees alone anymore.
+        assert!(contract.set_fee_percent(Some("10".to_string())).is_err());
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol.clone())
+            .build());
+        let proposal_id = contract.propose(
+            "set_fee_percent".to_string(),
+            r#"{"percent":"1"}"#.to_string(),
+        );
+        assert_eq!(
+            contract.proposals(),
+            vec![(
+                proposal_id,
+                &Proposal {
+                    method: "set_fee_percent".to_string(),
+                    args: r#"{"percent":"1"}"#.to_string(),
+                    proposer: carol.clone(),
+                    confirmations: std::iter::once(carol).collect(),
+                }
+            )]
+        );
+
+        testing_env!(VMContextBuilder::new().predecessor_account_id(dave).build());
+        contract.confirm(proposal_id);
+        let _ = contract.execute(proposal_id);
+        assert_eq!(contract.get_proposal(proposal_id), None);
+
+        // Simulates the call made by the executed proposal.
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice)
+            .build());
+        contract.set_fee_percent(Some("1".to_string())).unwrap();
+        assert_eq!(contract.get_fee_percent(), Some("1.00".to_string()));
+
+        contract.set_multisig(None);
+        assert_eq!(contract.get_multisig(), None);
+
+        testing_env!(VMContextBuilder::new().build());
+        contract.set_fee_percent(Some("2".to_string())).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Proposal: 0 hasn't been confirmed by the required number of signers"
+    )]
+    fn test_execute_unconfirmed_proposal() {
+        let carol: AccountId = "carol.near".parse().unwrap();
+        let dave: AccountId = "dave.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_multisig(Some(multisig(&[&carol, &dave], 2)));
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol)
+            .build());
+        let proposal_id = contract.propose("set_token_fee".to_string(), "{}".to_string());
+        let _ = contract.execute(proposal_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Method: on_ft_metadata couldn't be proposed")]
+    fn test_propose_not_allowed_method() {
+        let carol: AccountId = "carol.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_multisig(Some(multisig(&[&carol], 1)));
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol)
+            .build());
+        let _ = contract.propose("on_ft_metadata".to_string(), "{}".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner or multisig can set multisig")]
+    fn test_disable_multisig_by_owner() {
+        let carol: AccountId = "carol.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_multisig(Some(multisig(&[&carol], 1)));
+        contract.set_multisig(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Multisig threshold must be between 1 and the number of signers")]
+    fn test_set_multisig_with_high_threshold() {
+        let carol: AccountId = "carol.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_multisig(Some(multisig(&[&carol], 2)));
+    }
+
+    #[test]
+    fn test_roles() {
+        let fee_setter: AccountId = "fee-setter.near".parse().unwrap();
+        let token_manager: AccountId = "token-manager.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+
+        contract.grant_role(Role::FeeSetter, fee_setter.clone());
+        contract.grant_role(Role::TokenManager, token_manager.clone());
+
+        assert_eq!(contract.role_members(Role::FeeSetter), vec![&fee_setter]);
+        assert_eq!(
+            contract.role_members(Role::TokenManager),
+            vec![&token_manager]
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(fee_setter.clone())
+            .build());
+        contract.set_fee_percent(Some("1".to_string())).unwrap();
+
+        assert_eq!(contract.get_fee_percent(), Some("1.00".to_string()));
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(token_manager)
+            .build());
+        add_token(&mut contract, &usdt);
+
+        assert_eq!(contract.supported_tokens(), vec![&usdt]);
+
+        testing_env!(VMContextBuilder::new().build());
+        contract.revoke_role(Role::FeeSetter, &fee_setter);
+
+        assert!(contract.role_members(Role::FeeSetter).is_empty());
+    }
+
+    #[test]
+    fn test_set_fee_percent_by_token_manager() {
+        let token_manager: AccountId = "token-manager.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.grant_role(Role::TokenManager, token_manager.clone());
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(token_manager)
+            .build());
+        let error = contract.set_fee_percent(Some("1".to_string())).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Only owner or fee setter can set fee percent"
+        );
+    }
+
+    #[test]
+    fn test_fee_increase_delay() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_fee_increase_delay(60);
+        assert_eq!(contract.get_fee_increase_delay(), 60);
+
+        testing_env!(VMContextBuilder::new().block_timestamp(100).build());
+        // The decrease is applied immediately.
+        contract.set_fee_percent(Some("1".to_string())).unwrap();
+        assert_eq!(contract.get_fee_percent(), Some("1.00".to_string()));
+        assert_eq!(contract.get_pending_fee_change(), None);
+
+        // The increase is scheduled after the delay.
+        contract.set_fee_bps(200.into());
+        assert_eq!(contract.get_fee_percent(), Some("1.00".to_string()));
+        assert_eq!(
+            contract.get_pending_fee_change(),
+            Some(FeeChange {
+                percent: Some("2.00".to_string()),
+                effective_at: 60_000_000_100.into(),
+            })
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(60_000_000_100)
+            .build());
+        assert_eq!(contract.get_fee_percent(), Some("2.00".to_string()));
+
+        contract.set_fee_increase_delay(0);
+        contract.set_fee_percent(Some("3".to_string())).unwrap();
+        assert_eq!(contract.get_fee_percent(), Some("3.00".to_string()));
+    }
+
+    #[test]
+    fn test_max_fee_delta() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_fee_increase_delay(60);
+        contract.set_max_fee_delta(Some("1".to_string()));
+        assert_eq!(contract.get_max_fee_delta(), Some("1.00".to_string()));
+
+        let error = contract.set_fee_percent(Some("3".to_string())).unwrap_err();
+        assert_eq!(error, FeesError::FeeDeltaExceeded("1.00".to_string()));
+        assert_eq!(
+            error.to_string(),
+            "Fee change exceeds the max delta of 1.00 percentage points"
+        );
+
+        contract.set_fee_percent(Some("4".to_string())).unwrap();
+        assert_eq!(contract.get_fee_percent(), Some("4.00".to_string()));
+
+        // The step is compared with the percent which has already taken effect.
+        contract.set_fee_bps(500.into());
+        assert!(contract.set_fee_percent(Some("6".to_string())).is_err());
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(60_000_000_000)
+            .build());
+        assert_eq!(contract.get_fee_percent(), Some("5.00".to_string()));
+        contract.set_fee_percent(Some("6".to_string())).unwrap();
+
+        contract.set_max_fee_delta(None);
+        contract.set_fee_percent(None).unwrap();
+        assert_eq!(contract.get_fee_percent(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fee change exceeds the max delta of 0.50 percentage points")]
+    fn test_set_fee_bps_exceeding_max_fee_delta() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_max_fee_delta(Some("0.5".to_string()));
+        contract.set_fee_bps(400.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "Fee increase must take effect after the fee increase delay")]
+    fn test_schedule_fee_increase_before_delay() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_fee_increase_delay(60);
+        contract.schedule_fee_change(Some("6".to_string()), 59_999_999_999.into());
+    }
+
+    #[test]
+    fn test_scheduled_fee_change() {
+        let aurora = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.schedule_fee_change(Some("1".to_string()), 100.into());
+        assert_eq!(
+            contract.get_pending_fee_change(),
+            Some(FeeChange {
+                percent: Some("1.00".to_string()),
+                effective_at: 100.into()
+            })
+        );
+        assert_eq!(contract.get_fee_percent(), Some("5.00".to_string()));
+
+        testing_env!(VMContextBuilder::new().block_timestamp(100).build());
+        assert_eq!(contract.get_pending_fee_change(), None);
+        assert_eq!(contract.get_fee_percent(), Some("1.00".to_string()));
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            10.into()
+        );
+        assert_eq!(contract.get_fee_bps(), Some(100.into()));
+    }
+
+    #[test]
+    fn test_promotion() {
+        let aurora = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone(), wnear.clone()]);
+        allow_recording(&mut contract);
+        let promotion = Promotion {
+            start: 100.into(),
+            end: 200.into(),
+        };
+
+        contract.set_promotion(usdt.clone(), promotion.start, promotion.end);
+        assert_eq!(contract.get_promotion(&usdt), Some(&promotion));
+        assert!(contract.active_promotions().is_empty());
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            50.into()
+        );
+
+        testing_env!(VMContextBuilder::new().block_timestamp(100).build());
+        assert_eq!(contract.active_promotions(), vec![(&usdt, &promotion)]);
+        assert_eq!(
+            contract.calculate_fees(1000.into(), &usdt, &aurora, receiver()),
+            FeeResult {
+                fee: 0.into(),
+                net_amount: 1000.into(),
+                fee_recipient: "alice.near".parse().unwrap(),
+                rule: "promotion".to_string(),
+                rejected: false,
+                invalid_address: false,
+                percent: None,
+            }
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &wnear, &aurora, receiver())
+                .fee,
+            50.into()
+        );
+
+        testing_env!(VMContextBuilder::new().block_timestamp(200).build());
+        assert!(contract.active_promotions().is_empty());
+        assert_eq!(contract.get_promotion(&usdt), None);
+        assert_eq!(
+            contract
+                .record_and_calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            50.into()
+        );
+        // The expired promotion has been removed during the recorded fee calculation.
+        assert!(contract.promotions.is_empty());
+    }
+
+    #[test]
+    fn test_usd_flat_fee() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone(), wnear.clone()]);
+        let price_data = |timestamp: u64| PriceData {
+            timestamp: timestamp.into(),
+            recency_duration_sec: 90,
+            prices: vec![
+                AssetOptionalPrice {
+                    asset_id: usdt.clone(),
+                    price: Some(Price {
+                        multiplier: 10000.into(),
+                        decimals: 10,
+                    }),
+                },
+                AssetOptionalPrice {
+                    asset_id: wnear.clone(),
+                    price: None,
+                },
+            ],
+        };
+
+        contract.set_fee_percent(None).unwrap();
+        contract.set_usd_flat_fee(Some(100.into()));
+        assert_eq!(contract.get_usd_flat_fee(), Some(100.into()));
+
+        // There is no price of the token yet.
+        assert_eq!(
+            contract
+                .calculate_fees(10_000_000.into(), &usdt, &aurora, receiver())
+                .fee,
+            0.into()
+        );
+
+        assert_eq!(contract.on_price_data(Ok(price_data(1_000_000_000))), 1);
+        assert_eq!(contract.get_token_price(&wnear), None);
+        assert_eq!(
+            contract.get_token_price(&usdt).map(|price| price.timestamp),
+            Some(1_000_000_000.into())
+        );
+        // The older prices are ignored.
+        assert_eq!(contract.on_price_data(Ok(price_data(0))), 0);
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(91_000_000_000)
+            .build());
+        assert_eq!(
+            contract
+                .calculate_fees(10_000_000.into(), &usdt, &aurora, receiver())
+                .fee,
+            1_000_000.into()
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(10_000_000.into(), &wnear, &aurora, receiver())
+                .fee,
+            0.into()
+        );
+
+        // The flat fee of the token has a priority over the USD one.
+        contract.set_token_flat_fee(usdt.clone(), Some(500.into()));
+        assert_eq!(
+            contract
+                .calculate_fees(10_000_000.into(), &usdt, &aurora, receiver())
+                .fee,
+            500.into()
+        );
+        contract.set_token_flat_fee(usdt.clone(), None);
+
+        // The price is stale.
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(91_000_000_001)
+            .build());
+        assert_eq!(
+            contract
+                .calculate_fees(10_000_000.into(), &usdt, &aurora, receiver())
+                .fee,
+            0.into()
+        );
+
+        contract.set_max_price_age(120);
+        assert_eq!(contract.get_max_price_age(), 120);
+        assert_eq!(
+            contract
+                .calculate_fees(10_000_000.into(), &usdt, &aurora, receiver())
+                .fee,
+            1_000_000.into()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Price oracle is not set")]
+    fn test_update_prices_without_oracle() {
+        let mut contract = FeesCalculator::new(vec![]);
+        let _ = contract.update_prices(vec!["usdt.near".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_dust_threshold() {
+        let aurora = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        contract.set_free_forwards(1);
+
+        contract.set_dust_threshold(usdt.clone(), Some(200_000.into()));
+        assert_eq!(contract.get_dust_threshold(&usdt), Some(200_000.into()));
+        assert_eq!(
+            contract.calculate_fees(199_999.into(), &usdt, &aurora, receiver()),
+            FeeResult {
+                fee: 0.into(),
+                net_amount: 199_999.into(),
+                fee_recipient: "alice.near".parse().unwrap(),
+                rule: "dust_waiver".to_string(),
+                rejected: false,
+                invalid_address: false,
+                percent: None,
+            }
+        );
+        // The waived fee doesn't consume the free forward.
+        assert_eq!(
+            contract.remaining_free_forwards(&near_sdk::env::predecessor_account_id()),
+            1
+        );
+
+        contract.set_free_forwards(0);
+        assert_eq!(
+            contract
+                .calculate_fees(200_000.into(), &usdt, &aurora, receiver())
+                .fee,
+            10_000.into()
+        );
+
+        contract.set_dust_threshold(usdt.clone(), None);
+        assert_eq!(contract.get_dust_threshold(&usdt), None);
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            50.into()
+        );
+    }
+
+    #[test]
+    fn test_calculate_fees_batch() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let dai: AccountId = "dai.near".parse().unwrap();
+        let exempt_address = "0x1111111111111111111111111111111111111111";
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        contract.add_exempt_address(exempt_address.to_string());
+        contract.set_min_amount(aurora.clone(), usdt.clone(), Some(100.into()));
+        contract.set_free_forwards(1);
+
+        let quotes = contract.calculate_fees_batch(vec![
+            (
+                1000.into(),
+                usdt.clone(),
+                aurora.clone(),
+                receiver().encode(),
+            ),
+            (1000.into(), dai, aurora.clone(), receiver().encode()),
+            (
+                1000.into(),
+                usdt.clone(),
+                aurora.clone(),
+                exempt_address.to_string(),
+            ),
+            (99.into(), usdt, aurora, receiver().encode()),
+        ]);
+
+        assert_eq!(
+            quotes
+                .iter()
+                .map(|quote| (quote.fee, quote.rule.as_str(), quote.rejected))
+                .collect::<Vec<_>>(),
+            vec![
+                (50.into(), "global_fee", false),
+                (0.into(), "unsupported_token", false),
+                (0.into(), "exempt_address", false),
+                (0.into(), "below_min_amount", true),
+            ]
+        );
+        // The quotes don't consume the free forwards.
+        assert_eq!(
+            contract.remaining_free_forwards(&near_sdk::env::predecessor_account_id()),
+            1
+        );
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(0, 6), "0");
+        assert_eq!(format_amount(1_500_000, 6), "1.5");
+        assert_eq!(format_amount(1_000_000, 6), "1");
+        assert_eq!(format_amount(50, 6), "0.00005");
+        assert_eq!(format_amount(1234, 0), "1234");
+        assert_eq!(
+            format_amount(u128::MAX, 24),
+            "340282366920938.463463374607431768211455"
+        );
+    }
+
+    #[test]
+    fn test_quote_fees() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let dai: AccountId = "dai.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![dai.clone()]);
+        add_token(&mut contract, &usdt);
+
+        let address = receiver().encode();
+        let results = contract.calculate_fees_batch(vec![(
+            1_500_000.into(),
+            usdt.clone(),
+            aurora.clone(),
+            address.clone(),
+        )]);
+        assert_eq!(
+            contract.quote_fees(1_500_000.into(), &usdt, &aurora, address, None),
+            FeeQuote {
+                result: results[0].clone(),
+                amount: "1.5".to_string(),
+                fee: "0.075".to_string(),
+                net_amount: "1.425".to_string(),
+                symbol: Some("USDT".to_string()),
+                decimals: Some(6),
+            }
+        );
+
+        // The amounts of the token without the metadata aren't scaled.
+        let quote = contract.quote_fees(1000.into(), &dai, &aurora, receiver().encode(), None);
+        assert_eq!(quote.fee, "50");
+        assert_eq!(quote.net_amount, "950");
+        assert_eq!(quote.symbol, None);
+        assert_eq!(quote.decimals, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Couldn't parse address")]
+    fn test_calculate_fees_batch_with_invalid_address() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let contract = FeesCalculator::new(vec![usdt.clone()]);
+        let _ = contract.calculate_fees_batch(vec![(
+            1000.into(),
+            usdt,
+            "aurora".parse().unwrap(),
+            "0x1234".to_string(),
+        )]);
+    }
+
+    #[test]
+    fn test_invalid_address() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        allow_recording(&mut contract);
+        let expected = FeeResult {
+            fee: 0.into(),
+            net_amount: 1000.into(),
+            fee_recipient: "alice.near".parse().unwrap(),
+            rule: "invalid_address".to_string(),
+            rejected: true,
+            invalid_address: true,
+            percent: None,
+        };
+
+        assert_eq!(
+            contract.record_and_calculate_fees(1000.into(), &usdt, &aurora, Address::zero()),
+            expected
+        );
+        assert_eq!(
+            contract.calculate_native_fees(1000.into(), Address::zero()),
+            expected
+        );
+        assert_eq!(
+            contract.calculate_fees_batch(vec![(
+                1000.into(),
+                usdt.clone(),
+                aurora,
+                "0x0000000000000000000000000000000000000000".to_string(),
+            )]),
+            vec![expected]
+        );
+        // The invalid forwards aren't recorded.
+        assert_eq!(contract.get_token_stats(&usdt), None);
+    }
+
+    #[test]
+    fn test_max_amount() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.set_max_amount(usdt.clone(), Some(1000.into()));
+        assert_eq!(contract.get_max_amount(&usdt), Some(1000.into()));
+        assert_eq!(contract.max_amounts(), vec![(&usdt, 1000.into())]);
+        assert_eq!(
+            contract.calculate_fees(1001.into(), &usdt, &aurora, receiver()),
+            FeeResult {
+                fee: 0.into(),
+                net_amount: 1001.into(),
+                fee_recipient: "alice.near".parse().unwrap(),
+                rule: "above_max_amount".to_string(),
+                rejected: true,
+                invalid_address: false,
+                percent: None,
+            }
+        );
+        assert!(
+            !contract
+                .calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .rejected
+        );
+
+        contract.set_max_amount(usdt.clone(), None);
+        assert_eq!(contract.get_max_amount(&usdt), None);
+        assert!(contract.max_amounts().is_empty());
+        assert!(
+            !contract
+                .calculate_fees(1001.into(), &usdt, &aurora, receiver())
+                .rejected
+        );
+    }
+
+    #[test]
+    fn test_min_amount() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let silo: AccountId = "silo.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.set_min_amount(silo.clone(), usdt.clone(), Some(1000.into()));
+        assert_eq!(contract.get_min_amount(&silo, &usdt), Some(1000.into()));
+        assert_eq!(contract.get_min_amount(&aurora, &usdt), None);
+        assert_eq!(
+            contract.network_min_amounts(&silo),
+            vec![(&usdt, 1000.into())]
+        );
+        assert_eq!(
+            contract.calculate_fees(999.into(), &usdt, &silo, receiver()),
+            FeeResult {
+                fee: 0.into(),
+                net_amount: 999.into(),
+                fee_recipient: "alice.near".parse().unwrap(),
+                rule: "below_min_amount".to_string(),
+                rejected: true,
+                invalid_address: false,
+                percent: None,
+            }
+        );
+        assert!(
+            !contract
+                .calculate_fees(1000.into(), &usdt, &silo, receiver())
+                .rejected
+        );
+        assert!(
+            !contract
+                .calculate_fees(999.into(), &usdt, &aurora, receiver())
+                .rejected
+        );
+
+        contract.set_min_amount(silo.clone(), usdt.clone(), None);
+        assert_eq!(contract.get_min_amount(&silo, &usdt), None);
+        assert!(contract.network_min_amounts(&silo).is_empty());
+        assert!(contract.min_amounts.is_empty());
+    }
+
+    #[test]
+    fn test_native_fees() {
+        let mut contract = FeesCalculator::new(vec![]);
+        let exempt_address = Address::decode("1111111111111111111111111111111111111111").unwrap();
+
+        assert_eq!(contract.get_native_fee(), Some("5.00".to_string()));
+        assert_eq!(
+            contract.calculate_native_fees(1000.into(), receiver()),
+            FeeResult {
+                fee: 50.into(),
+                net_amount: 950.into(),
+                fee_recipient: "alice.near".parse().unwrap(),
+                rule: "native_fee".to_string(),
+                rejected: false,
+                invalid_address: false,
+                percent: None,
+            }
+        );
+
+        // The native fee doesn't depend on the global one.
+        contract.set_fee_percent(Some("1".to_string())).unwrap();
+        contract.set_native_fee(Some("2.5".to_string()));
+        contract.set_native_flat_fee(Some(10.into()));
+        assert_eq!(contract.get_native_fee(), Some("2.50".to_string()));
+        assert_eq!(contract.get_native_flat_fee(), Some(10.into()));
+        assert_eq!(
+            contract.calculate_native_fees(1000.into(), receiver()).fee,
+            35.into()
+        );
+
+        contract.add_exempt_address(exempt_address.encode());
+        assert_eq!(
+            contract
+                .calculate_native_fees(1000.into(), exempt_address)
+                .rule,
+            "exempt_address"
+        );
+
+        contract.set_native_fee(None);
+        contract.set_native_flat_fee(None);
+        assert_eq!(contract.get_native_fee(), None);
+        assert_eq!(contract.get_native_flat_fee(), None);
+        assert_eq!(
+            contract.calculate_native_fees(1000.into(), receiver()).fee,
+            0.into()
+        );
+    }
+
+    #[test]
+    fn test_suspend_token() {
+        let aurora = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        contract.set_token_flat_fee(usdt.clone(), Some(100.into()));
+
+        contract.suspend_token(usdt.clone());
+        assert!(contract.is_token_suspended(&usdt));
+        assert_eq!(contract.suspended_tokens(), vec![&usdt]);
+        assert_eq!(contract.supported_tokens(), vec![&usdt]);
+        assert_eq!(
+            contract.calculate_fees(1000.into(), &usdt, &aurora, receiver()),
+            FeeResult {
+                fee: 0.into(),
+                net_amount: 1000.into(),
+                fee_recipient: "alice.near".parse().unwrap(),
+                rule: "suspended_token".to_string(),
+                rejected: false,
+                invalid_address: false,
+                percent: None,
+            }
+        );
+
+        contract.resume_token(&usdt);
+        assert!(!contract.is_token_suspended(&usdt));
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            150.into()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is already suspended")]
+    fn test_suspend_already_suspended_token() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        contract.suspend_token(usdt.clone());
+        contract.suspend_token(usdt);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing to resume, token: usdt.near isn't suspended")]
+    fn test_resume_not_suspended_token() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        contract.resume_token(&usdt);
+    }
+
+    #[test]
+    #[should_panic(expected = "Promotion must start before it ends")]
+    fn test_set_inverted_promotion() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.set_promotion(usdt, 200.into(), 100.into());
+    }
+
+    #[test]
+    fn test_fee_history() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        allow_recording(&mut contract);
+
+        contract.set_fee_percent(Some("2".to_string())).unwrap();
+        contract.set_token_fee(usdt.clone(), Some("1".to_string()));
+        contract.remove_token_fee(&usdt);
+        contract.set_token_fee_caps(usdt.clone(), Some(10.into()), None);
+        contract.schedule_fee_change(None, 100.into());
+
+        testing_env!(VMContextBuilder::new().block_timestamp(150).build());
+        let _ = contract.record_and_calculate_fees(1000.into(), &usdt, &aurora, receiver());
+
+        assert_eq!(contract.fee_history_count(), 5);
+        assert_eq!(
+            contract.fee_history(None, Some(2)),
+            vec![
+                &FeeHistoryEntry {
+                    account_id: "bob.near".parse().unwrap(),
+                    timestamp: 0.into(),
+                    parameter: "fee_percent".to_string(),
+                    target_id: None,
+                    old_value: Some("5.00".to_string()),
+                    new_value: Some("2.00".to_string()),
+                },
+                &FeeHistoryEntry {
+                    account_id: "bob.near".parse().unwrap(),
+                    timestamp: 0.into(),
+                    parameter: "token_fee".to_string(),
+                    target_id: Some(usdt.clone()),
+                    old_value: None,
+                    new_value: Some("1.00".to_string()),
+                },
+            ]
+        );
+
+        let entries = contract.fee_history(Some(2), None);
+        assert_eq!(entries[0].old_value, Some("1.00".to_string()));
+        assert_eq!(entries[0].new_value, None);
+        assert_eq!(entries[1].parameter, "token_fee_caps");
+        assert_eq!(
+            entries[1].new_value,
+            Some(r#"{"min":"10","max":null}"#.to_string())
+        );
+        assert_eq!(entries[2].timestamp, 100.into());
+        assert_eq!(entries[2].old_value, Some("2.00".to_string()));
+        assert_eq!(entries[2].new_value, Some("0.00".to_string()));
+    }
+
+    #[test]
+    fn test_cancel_fee_change() {
+        let mut contract = FeesCalculator::new(vec![]);
+
+        contract.schedule_fee_change(None, 100.into());
+        contract.cancel_fee_change();
+        assert_eq!(contract.get_pending_fee_change(), None);
+
+        testing_env!(VMContextBuilder::new().block_timestamp(100).build());
+        assert_eq!(contract.get_fee_percent(), Some("5.00".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing to cancel, there is no pending fee change")]
+    fn test_cancel_effective_fee_change() {
+        let mut contract = FeesCalculator::new(vec![]);
+
+        contract.schedule_fee_change(None, 100.into());
+        testing_env!(VMContextBuilder::new().block_timestamp(100).build());
+        contract.cancel_fee_change();
+    }
+
+    #[test]
+    #[should_panic(expected = "Effective timestamp must be in the future")]
+    fn test_schedule_fee_change_in_past() {
+        let mut contract = FeesCalculator::new(vec![]);
+
+        testing_env!(VMContextBuilder::new().block_timestamp(100).build());
+        contract.schedule_fee_change(None, 100.into());
+    }
+
+    #[test]
+    fn test_calculate_fees_result() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        assert_eq!(
+            contract.calculate_fees(1000.into(), &usdt, &aurora, receiver()),
+            FeeResult {
+                fee: 50.into(),
+                net_amount: 950.into(),
+                fee_recipient: "alice.near".parse().unwrap(),
+                rule: "global_fee".to_string(),
+                rejected: false,
+                invalid_address: false,
+                percent: None,
+            }
+        );
+
+        contract.set_network_fee(aurora.clone(), Some("1".to_string()));
+        let result = contract.calculate_fees(1000.into(), &usdt, &aurora, receiver());
+        assert_eq!(result.net_amount, 990.into());
+        assert_eq!(result.rule, "network_fee");
+
+        let result = contract.calculate_fees(1000.into(), &wnear, &aurora, receiver());
+        assert_eq!(result.net_amount, 1000.into());
+        assert_eq!(result.rule, "unsupported_token");
+    }
+
+    #[test]
+    fn test_unbridged_surcharge() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let silo: AccountId = "silo.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        allow_recording(&mut contract);
+
+        // The fee stays the same while the surcharge isn't set.
+        assert_eq!(
+            contract
+                .record_and_calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            50.into()
+        );
+        assert!(!contract.is_unbridged_surcharge_charged(&usdt, &aurora));
+
+        contract.set_unbridged_surcharge(usdt.clone(), Some(100.into()));
+        assert_eq!(contract.get_unbridged_surcharge(&usdt), Some(100.into()));
+
+        // The quotes neither include the surcharge nor mark it as charged.
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            50.into()
+        );
+        assert!(!contract.is_unbridged_surcharge_charged(&usdt, &aurora));
+        assert_eq!(
+            contract.record_and_calculate_fees(1000.into(), &usdt, &aurora, receiver()),
+            FeeResult {
+                fee: 150.into(),
+                net_amount: 850.into(),
+                fee_recipient: "alice.near".parse().unwrap(),
+                rule: "unbridged_surcharge".to_string(),
+                rejected: false,
+                invalid_address: false,
+                percent: None,
+            }
+        );
+        assert!(contract.is_unbridged_surcharge_charged(&usdt, &aurora));
+        assert!(!contract.is_unbridged_surcharge_charged(&usdt, &silo));
+
+        // The surcharge is charged only once per network.
+        assert_eq!(
+            contract
+                .record_and_calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            50.into()
+        );
+        assert_eq!(
+            contract
+                .record_and_calculate_fees(1000.into(), &usdt, &silo, receiver())
+                .fee,
+            150.into()
+        );
+
+        // The surcharge isn't charged for the network, which the token is bridged to.
+        contract.set_token_bridged(usdt.clone(), aurora.clone(), false);
+        contract.set_token_bridged(usdt.clone(), silo.clone(), true);
+        assert!(!contract.is_unbridged_surcharge_charged(&usdt, &aurora));
+        assert!(contract.is_unbridged_surcharge_charged(&usdt, &silo));
+        assert_eq!(
+            contract
+                .record_and_calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            150.into()
+        );
+
+        contract.set_unbridged_surcharge(usdt.clone(), None);
+        assert_eq!(contract.get_unbridged_surcharge(&usdt), None);
+    }
+
+    #[test]
+    fn test_calculate_fees_with_referral() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let partner: AccountId = "partner.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.add_referrer(partner.clone());
+        contract.set_referral_share(2000.into());
+
+        assert_eq!(
+            contract.calculate_fees_with_referral(
+                1000.into(),
+                &usdt,
+                &aurora,
+                receiver(),
+                Some(partner.clone())
+            ),
+            FeeSplit {
+                treasury: 40.into(),
+                referrer: 10.into(),
+                referrer_id: Some(partner.clone()),
+            }
+        );
+        assert_eq!(
+            contract.calculate_fees_with_referral(
+                1000.into(),
+                &usdt,
+                &aurora,
+                receiver(),
+                Some(aurora.clone())
+            ),
+            FeeSplit {
+                treasury: 50.into(),
+                referrer: 0.into(),
+                referrer_id: None,
+            }
+        );
+
+        contract.remove_referrer(&partner);
+        assert!(contract.referrers().is_empty());
+    }
+
+    #[test]
+    fn test_rounding_mode() {
+        assert_eq!(RoundingMode::Floor.div(15, 10), 1);
+        assert_eq!(RoundingMode::Ceil.div(15, 10), 2);
+        assert_eq!(RoundingMode::HalfUp.div(15, 10), 2);
+        assert_eq!(RoundingMode::Floor.div(14, 10), 1);
+        assert_eq!(RoundingMode::Ceil.div(11, 10), 2);
+        assert_eq!(RoundingMode::HalfUp.div(14, 10), 1);
+        assert_eq!(RoundingMode::Ceil.div(20, 10), 2);
+        assert_eq!(RoundingMode::HalfUp.div(u128::MAX, u128::MAX), 1);
+
+        // 5% of 1010 is 50.5
+        assert_eq!(
+            calculate_percent_fee(1010.into(), 50_000.into(), RoundingMode::Floor),
+            50
+        );
+        assert_eq!(
+            calculate_percent_fee(1010.into(), 50_000.into(), RoundingMode::HalfUp),
+            51
+        );
+        assert_eq!(
+            calculate_percent_fee(1001.into(), 50_000.into(), RoundingMode::Ceil),
+            51
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only forwarders of known factories can record forwards")]
+    fn test_record_by_unknown_forwarder() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        contract.grant_role(Role::Factory, "factory.near".parse().unwrap());
+
+        let _ = contract.record_and_calculate_fees(1000.into(), &usdt, &aurora, receiver());
+    }
+
+    #[test]
+    fn test_record_and_calculate_fees() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let silo: AccountId = "silo.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let address = receiver();
+        let mut contract = FeesCalculator::new(vec![usdt.clone(), wnear.clone()]);
+        allow_recording(&mut contract);
+        contract.set_min_amount(silo.clone(), usdt.clone(), Some(100.into()));
+
+        let result = contract.record_and_calculate_fees(1000.into(), &usdt, &aurora, address);
+        assert_eq!(result.fee, 50.into());
+        let _ = contract.record_and_calculate_fees(2000.into(), &usdt, &silo, address);
+        let _ = contract.record_and_calculate_fees(100.into(), &wnear, &silo, address);
+        // The rejected amount isn't recorded.
+        let result = contract.record_and_calculate_fees(99.into(), &usdt, &silo, address);
+        assert!(result.rejected);
+
+        assert_eq!(
+            contract.get_token_stats(&usdt),
+            Some(&Stats {
+                forwards: 2,
+                volume: 3000.into(),
+                fees: 150.into(),
+            })
+        );
+        assert_eq!(
+            contract.get_network_stats(&silo),
+            Some(&Stats {
+                forwards: 2,
+                volume: 2100.into(),
+                fees: 105.into(),
+            })
+        );
+        assert_eq!(contract.token_stats_count(), 2);
+        assert_eq!(contract.network_stats_count(), 2);
+        assert_eq!(
+            contract
+                .token_stats(Some(1), Some(10))
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            vec![&wnear]
+        );
+        assert_eq!(
+            contract
+                .network_stats(None, Some(1))
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            vec![&aurora]
+        );
+    }
+
+    #[test]
+    fn test_near_fee() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let address = receiver();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        allow_recording(&mut contract);
+
+        contract.set_near_fee(Some(1000.into()));
+        assert_eq!(contract.get_near_fee(), Some(1000.into()));
+
+        // The deposit is too low, so the fee is deducted from the tokens.
+        testing_env!(VMContextBuilder::new()
+            .attached_deposit(NearToken::from_yoctonear(999))
+            .build());
+        let result = contract.record_and_calculate_fees(1000.into(), &usdt, &aurora, address);
+        assert_eq!(result.fee, 50.into());
+        assert_eq!(result.rule, "global_fee");
+
+        testing_env!(VMContextBuilder::new()
+            .attached_deposit(NearToken::from_yoctonear(1500))
+            .build());
+        assert_eq!(
+            contract.record_and_calculate_fees(1000.into(), &usdt, &aurora, address),
+            FeeResult {
+                fee: 0.into(),
+                net_amount: 1000.into(),
+                fee_recipient: "alice.near".parse().unwrap(),
+                rule: "near_fee".to_string(),
+                rejected: false,
+                invalid_address: false,
+                percent: None,
+            }
+        );
+        assert_eq!(contract.get_token_stats(&usdt).unwrap().fees, 50.into());
+
+        contract.set_near_fee(None);
+        assert_eq!(contract.get_near_fee(), None);
+        assert_eq!(
+            contract
+                .record_and_calculate_fees(1000.into(), &usdt, &aurora, address)
+                .fee,
+            50.into()
+        );
+    }
+
+    #[test]
+    fn test_contract_source_metadata() {
+        let metadata: ContractSourceMetadata =
+            serde_json::from_str(super::CONTRACT_SOURCE_METADATA).unwrap();
+
+        assert_eq!(metadata.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metadata.link, env!("CARGO_PKG_REPOSITORY"));
+        assert!(metadata
+            .standards
+            .iter()
+            .any(|standard| standard.standard == "nep330"));
+    }
+
+    #[test]
+    fn test_fee_recipients() {
+        let aurora = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let treasury: AccountId = "treasury.near".parse().unwrap();
+        let owner: AccountId = "alice.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone(), wnear.clone()]);
+
+        assert_eq!(contract.get_fee_recipient(&usdt), owner);
+
+        contract.set_fee_recipient(usdt.clone(), Some(treasury.clone()));
+        assert_eq!(contract.get_fee_recipient(&usdt), treasury);
+        assert_eq!(contract.fee_recipients(), vec![(&usdt, &treasury)]);
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee_recipient,
+            treasury
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &wnear, &aurora, receiver())
+                .fee_recipient,
+            owner
+        );
+
+        contract.set_fee_recipient(usdt.clone(), None);
+        assert!(contract.fee_recipients().is_empty());
+        assert_eq!(contract.get_fee_recipient(&usdt), owner);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set fee recipient")]
+    fn test_set_fee_recipient_by_not_owner() {
+        let mut contract = FeesCalculator::new(vec![]);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("carol.near".parse().unwrap())
+            .build());
+        contract.set_fee_recipient("usdt.near".parse().unwrap(), None);
+    }
+
+    #[test]
+    fn test_set_rounding_mode() {
+        let aurora = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        assert_eq!(contract.get_rounding_mode(), RoundingMode::Floor);
+        assert_eq!(
+            contract
+                .calculate_fees(1019.into(), &usdt, &aurora, receiver())
+                .fee,
+            50.into()
+        );
+
+        contract.set_rounding_mode(RoundingMode::Ceil);
+        assert_eq!(contract.get_rounding_mode(), RoundingMode::Ceil);
+        assert_eq!(
+            contract
+                .calculate_fees(1001.into(), &usdt, &aurora, receiver())
+                .fee,
+            51.into()
+        );
+        assert_eq!(
+            contract.fee_history(None, None).last().map(|entry| (
+                entry.parameter.as_str(),
+                entry.old_value.as_deref(),
+                entry.new_value.as_deref()
+            )),
+            Some(("rounding_mode", Some("floor"), Some("ceil")))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set rounding mode")]
+    fn test_set_rounding_mode_by_not_owner() {
+        let mut contract = FeesCalculator::new(vec![]);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("carol.near".parse().unwrap())
+            .build());
+        contract.set_rounding_mode(RoundingMode::Ceil);
+    }
+
+    #[test]
+    fn test_calculate_share() {
+        assert_eq!(calculate_share(50, 2000), 10);
+        assert_eq!(calculate_share(9, 5000), 4);
+        assert_eq!(calculate_share(u128::MAX, 10000), u128::MAX);
+        assert_eq!(calculate_share(u128::MAX, 0), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Referral share can't be more than 100%")]
+    fn test_set_too_high_referral_share() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_referral_share(10001.into());
+    }
+
+    #[test]
+    fn test_set_fee_bps() {
+        let mut contract = FeesCalculator::new(vec![]);
+
+        contract.set_fee_bps(250.into());
+        assert_eq!(contract.get_fee_bps(), Some(250.into()));
+        assert_eq!(contract.get_fee_percent(), Some("2.50".to_string()));
+
+        contract.set_fee_percent(Some("0.01".to_string())).unwrap();
+        assert_eq!(contract.get_fee_bps(), Some(1.into()));
+
+        contract.set_fee_bps(0.into());
+        assert_eq!(contract.get_fee_bps(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid fee bps: provided percent is more than 10%")]
+    fn test_set_too_high_fee_bps() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_fee_bps(1001.into());
+    }
+
+    #[test]
+    fn test_events() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+
+        contract.set_fee_percent(Some("2.5".to_string())).unwrap();
+        add_token(&mut contract, &usdt);
+        contract.set_token_flat_fee(usdt, Some(100.into()));
+
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"aurora-forwarder-fees","version":"1.0.0","event":"set_fee_percent","data":{"percent":"2.50"}}"#,
+                r#"EVENT_JSON:{"standard":"aurora-forwarder-fees","version":"1.0.0","event":"add_supported_token","data":{"token_id":"usdt.near"}}"#,
+                r#"EVENT_JSON:{"standard":"aurora-forwarder-fees","version":"1.0.0","event":"set_token_flat_fee","data":{"token_id":"usdt.near","fee":"100"}}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pause() {
+        let aurora = "aurora".parse().unwrap();
+        let target_address = receiver();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.pause();
+        assert!(contract.is_paused());
+
+        contract.unpause();
+        assert!(!contract.is_paused());
+
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            50.into()
+        );
+    }
+
+    #[test]
+    fn test_network_supported_tokens() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let silo: AccountId = "silo.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.add_network_supported_token(silo.clone(), wnear.clone());
+
+        assert_eq!(
+            contract.network_supported_tokens(&silo, None, None),
+            Some(vec![&wnear])
+        );
+        assert_eq!(contract.network_supported_tokens(&aurora, None, None), None);
+        assert!(contract.is_token_supported(&usdt, &aurora));
+        assert!(!contract.is_token_supported(&usdt, &silo));
+        assert!(contract.is_network_supported(&aurora));
+        assert!(contract.is_network_supported(&silo));
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &silo, receiver())
+                .rule,
+            "unsupported_token"
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &wnear, &silo, receiver())
+                .fee,
+            50.into()
+        );
+
+        // The empty list of the network still overrides the global one.
+        contract.remove_network_supported_token(&silo, &wnear);
+        assert_eq!(
+            contract.network_supported_tokens(&silo, None, None),
+            Some(vec![])
+        );
+        assert!(!contract.is_token_supported(&usdt, &silo));
+        assert!(!contract.is_network_supported(&silo));
+
+        contract.remove_network_supported_tokens(&silo);
+        assert_eq!(contract.network_supported_tokens(&silo, None, None), None);
+        assert!(contract.is_token_supported(&usdt, &silo));
+        assert!(contract.is_network_supported(&silo));
+    }
+
+    #[test]
+    fn test_supported_networks() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let silo: AccountId = "silo.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        allow_recording(&mut contract);
+        contract.set_unbridged_surcharge(usdt.clone(), Some(100.into()));
+
+        // All networks are supported while the registry is empty.
+        assert!(contract.is_network_supported(&silo));
+
+        contract.add_supported_network(aurora.clone());
+        assert_eq!(contract.supported_networks(), vec![&aurora]);
+        assert!(contract.is_network_supported(&aurora));
+        assert!(!contract.is_network_supported(&silo));
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            50.into()
+        );
+        assert_eq!(
+            contract.record_and_calculate_fees(1000.into(), &usdt, &silo, receiver()),
+            FeeResult {
+                fee: 0.into(),
+                net_amount: 1000.into(),
+                fee_recipient: "alice.near".parse().unwrap(),
+                rule: "unsupported_network".to_string(),
+                rejected: false,
+                invalid_address: false,
+                percent: None,
+            }
+        );
+
+        contract.remove_supported_network(&aurora);
+        assert!(contract.supported_networks().is_empty());
+        assert!(contract.is_network_supported(&silo));
+    }
+
+    #[test]
+    #[should_panic(expected = "Network is already present")]
+    fn test_add_duplicate_supported_network() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.add_supported_network("aurora".parse().unwrap());
+        contract.add_supported_network("aurora".parse().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing to remove, token: usdt.near hasn't been added")]
+    fn test_remove_missing_network_supported_token() {
+        let silo: AccountId = "silo.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.remove_network_supported_token(&silo, &usdt);
+    }
+
+    #[test]
+    fn test_get_config() {
+        let owner: AccountId = "owner.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(owner.clone())
+            .build());
+        let mut contract = FeesCalculator::new(vec![usdt, wnear]);
+
+        contract.pause();
+        contract.set_free_forwards(3);
+        contract.add_exempt_address(receiver().encode());
+
+        assert_eq!(
+            contract.get_config(),
+            Config {
+                owner,
+                proposed_owner: None,
+                percent: Some("5.00".to_string()),
+                pending_fee_change: None,
+                fee_increase_delay: 0,
+                max_fee_delta: None,
+                paused: true,
+                supported_tokens_count: 2,
+                exempt_addresses_count: 1,
+                free_forwards: 3,
+                referral_share: 0.into(),
+                referrers_count: 0,
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Fees contract is paused")]
+    fn test_calculate_fees_while_paused() {
+        let aurora = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.pause();
+        let _ = contract.calculate_fees(1000.into(), &usdt, &aurora, receiver());
+    }
+
+    #[test]
+    fn test_exempt_addresses() {
+        let aurora = "aurora".parse().unwrap();
+        let treasury = "0x17ffdf6becbbc34d5c7d3bf4a0ed4a680395d057";
+        let ops = "0x41e60a647bc61097ed52f15855fcf24a9dacdbe4";
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.add_exempt_address(treasury.to_string());
+        contract.add_exempt_address(ops.trim_start_matches("0x").to_string());
+
+        assert_eq!(contract.exempt_addresses(None, None), vec![treasury, ops]);
+        assert_eq!(contract.exempt_addresses(Some(1), Some(1)), vec![ops]);
+        assert!(contract.exempt_addresses(Some(2), None).is_empty());
+        assert_eq!(
+            contract
+                .calculate_fees(
+                    1000.into(),
+                    &usdt,
+                    &aurora,
+                    Address::decode(&treasury[2..]).unwrap()
+                )
+                .fee,
+            0.into()
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, receiver())
+                .fee,
+            50.into()
+        );
+
+        contract.remove_exempt_address(treasury.to_string());
+
+        assert_eq!(
+            contract
+                .calculate_fees(
+                    1000.into(),
+                    &usdt,
+                    &aurora,
+                    Address::decode(&treasury[2..]).unwrap()
+                )
+                .fee,
+            50.into()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Couldn't parse address")]
+    fn test_add_invalid_exempt_address() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.add_exempt_address("0x1234".to_string());
+    }
+
+    #[test]
+    fn test_free_forwards() {
+        let aurora = "aurora".parse().unwrap();
+        let target_address = receiver();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let alice_fwd: AccountId = "alice.factory.near".parse().unwrap();
+        let bob_fwd: AccountId = "bob.factory.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        contract.grant_role(Role::Factory, "factory.near".parse().unwrap());
+
+        contract.set_free_forwards(2);
+
+        assert_eq!(contract.get_free_forwards(), 2);
+        assert_eq!(contract.remaining_free_forwards(&alice_fwd), 2);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice_fwd.clone())
+            .build());
+
+        // Fee calculations for unsupported tokens aren't counted.
+        assert_eq!(
+            contract
+                .record_and_calculate_fees(1000.into(), &aurora, &aurora, target_address)
+                .fee,
+            0.into()
+        );
+        assert_eq!(contract.remaining_free_forwards(&alice_fwd), 2);
+
+        // The quotes report the free forward without consuming it.
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .rule,
+            "free_forward"
+        );
+        assert_eq!(contract.remaining_free_forwards(&alice_fwd), 2);
+
+        for _ in 0..2 {
+            assert_eq!(
+                contract
+                    .record_and_calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                    .fee,
+                0.into()
+            );
+        }
+
+        assert_eq!(contract.remaining_free_forwards(&alice_fwd), 0);
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, target_address)
+                .fee,
+            50.into()
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob_fwd.clone())
+            .build());
+
+        assert_eq!(
+            contract
+                .calculate_native_fees(1000.into(), target_address)
+                .rule,
+            "free_forward"
+        );
+        assert_eq!(
+            contract
+                .record_and_calculate_native_fees(1000.into(), target_address)
+                .fee,
+            0.into()
+        );
+        assert_eq!(contract.remaining_free_forwards(&bob_fwd), 1);
+    }
+
+    #[test]
+    fn test_add_supported_token_with_metadata() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+
+        let _ = contract.add_supported_token(usdt.clone()).unwrap();
+        assert!(contract.supported_tokens().is_empty());
+        assert_eq!(contract.get_token_metadata(&usdt), None);
+
+        assert!(contract.on_ft_metadata(usdt.clone(), Ok(ft_metadata("ft-1.0.0"))));
+        assert_eq!(contract.supported_tokens(), vec![&usdt]);
+        assert_eq!(
+            contract.get_token_metadata(&usdt),
+            Some(&TokenMetadata {
+                symbol: "USDT".to_string(),
+                decimals: 6
+            })
+        );
+
+        // The token has already been added by a concurrent request.
+        assert!(!contract.on_ft_metadata(usdt.clone(), Ok(ft_metadata("ft-1.0.0"))));
+        assert_eq!(contract.supported_tokens(), vec![&usdt]);
+    }
+
+    #[test]
+    fn test_add_already_present_token() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+        let error = contract.add_supported_token(usdt).map(drop).unwrap_err();
+        assert_eq!(error, FeesError::TokenAlreadyPresent);
+        assert_eq!(error.to_string(), "Token is already present");
+    }
+
+    #[test]
+    fn test_remove_missing_token() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        let error = contract.remove_supported_token(&usdt).unwrap_err();
+        assert_eq!(error, FeesError::TokenNotAdded(usdt));
+        assert_eq!(
+            error.to_string(),
+            "Nothing to remove, token: usdt.near hasn't been added"
+        );
+    }
+
+    #[test]
+    fn test_add_supported_tokens_by_fee_setter() {
+        let fee_setter: AccountId = "fee-setter.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.grant_role(Role::FeeSetter, fee_setter.clone());
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(fee_setter)
+            .build());
+        assert_eq!(
+            contract
+                .add_supported_tokens(vec!["usdt.near".parse().unwrap()])
+                .unwrap_err()
+                .to_string(),
+            "Only owner or token manager can add tokens"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Account: alice.near is not a NEP-141 token")]
+    fn test_add_not_nep141_token() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.on_ft_metadata(alice, Err(PromiseError::Failed));
+    }
+
+    #[test]
+    #[should_panic(expected = "Account: nft.near is not a NEP-141 token")]
+    fn test_add_token_with_wrong_spec() {
+        let nft: AccountId = "nft.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.on_ft_metadata(nft, Ok(ft_metadata("nft-1.0.0")));
+    }
+
+    #[test]
+    fn test_token_registration() {
+        let carol: AccountId = "carol.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_listing_bond(Some(1000.into()));
+        assert_eq!(contract.get_listing_bond(), Some(1000.into()));
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol.clone())
+            .attached_deposit(NearToken::from_yoctonear(1500))
+            .build());
+        contract.register_token(usdt.clone());
+        contract.register_token(wnear.clone());
+
+        let registration = TokenRegistration {
+            account_id: carol,
+            bond: 1500.into(),
+        };
+        assert_eq!(contract.get_token_registration(&usdt), Some(&registration));
+        assert_eq!(
+            contract.token_registrations(None, None),
+            vec![(&usdt, &registration), (&wnear, &registration)]
+        );
+
+        testing_env!(VMContextBuilder::new().build());
+        let _ = contract.approve_token(usdt.clone());
+        assert!(contract.on_ft_metadata(usdt.clone(), Ok(ft_metadata("ft-1.0.0"))));
+        assert_eq!(contract.supported_tokens(), vec![&usdt]);
+
+        let _ = contract.reject_token(&wnear, true);
+        assert!(contract.token_registrations(None, None).is_empty());
+        assert_eq!(contract.supported_tokens(), vec![&usdt]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be at least the listing bond: 1000")]
+    fn test_register_token_with_low_bond() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.set_listing_bond(Some(1000.into()));
+
+        testing_env!(VMContextBuilder::new()
+            .attached_deposit(NearToken::from_yoctonear(999))
+            .build());
+        contract.register_token("usdt.near".parse().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token registration is disabled")]
+    fn test_register_token_without_bond() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.register_token("usdt.near".parse().unwrap());
+    }
+
+    #[test]
+    #[allow(clippy::similar_names)]
+    fn test_batch_supported_tokens() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let usdc: AccountId = "usdc.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        assert_eq!(
+            contract
+                .add_supported_tokens(vec![
+                    usdt.clone(),
+                    usdc.clone(),
+                    wnear.clone(),
+                    usdc.clone()
+                ])
+                .unwrap(),
+            vec![false, true, true, false]
+        );
+        assert_eq!(contract.supported_tokens(), vec![&usdt]);
+        assert!(contract.on_ft_metadata(usdc.clone(), Ok(ft_metadata("ft-1.0.0"))));
+        assert!(contract.on_ft_metadata(wnear.clone(), Ok(ft_metadata("ft-1.0.0"))));
+        assert_eq!(contract.supported_tokens(), vec![&usdt, &usdc, &wnear]);
+        assert_eq!(contract.supported_tokens_count(), 3);
+        assert_eq!(
+            contract.supported_tokens_paged(Some(1), Some(1)),
+            vec![&usdc]
+        );
+        assert_eq!(
+            contract.supported_tokens_paged(Some(1), None),
+            vec![&usdc, &wnear]
+        );
+        assert!(contract
+            .supported_tokens_paged(Some(3), Some(10))
+            .is_empty());
+        assert_eq!(
+            contract
+                .remove_supported_tokens(vec![usdc.clone(), usdc, wnear])
+                .unwrap(),
+            vec![true, false, true]
+        );
+        assert_eq!(contract.supported_tokens(), vec![&usdt]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only proposed owner can accept ownership")]
+    fn test_accept_ownership_by_not_proposed_owner() {
+        let mut contract = FeesCalculator::new(vec![]);
+        contract.propose_owner(Some("new-owner.near".parse().unwrap()));
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("alice.near".parse().unwrap())
+            .build());
+        contract.accept_ownership();
     }
 
     #[test]
-    #[should_panic(
-        expected = "Couldn't parse percent: provided percent could contain only 2 decimals"
-    )]
     fn test_set_percent_with_many_decimals() {
         let mut contract = FeesCalculator::new(vec![]);
-        contract.set_fee_percent(Some("6.123".to_string()));
+        let error = contract
+            .set_fee_percent(Some("6.12345".to_string()))
+            .unwrap_err();
+        assert_eq!(
+            error,
+            FeesError::InvalidPercent(ParseError::TooManyDecimals)
+        );
+        assert_eq!(
+            error.to_string(),
+            "Couldn't parse percent: provided percent could contain only 4 decimals"
+        );
+    }
+
+    #[test]
+    fn test_four_decimal_percent() {
+        let aurora = "aurora".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.set_fee_percent(Some("0.125".to_string())).unwrap();
+
+        assert_eq!(contract.get_fee_percent(), Some("0.125".to_string()));
+        assert_eq!(contract.get_fee_bps(), Some(12.into()));
+        assert_eq!(
+            contract
+                .calculate_fees(100_000.into(), &usdt, &aurora, receiver())
+                .fee,
+            125.into()
+        );
+
+        contract
+            .set_fee_percent(Some("0.0001".to_string()))
+            .unwrap();
+        assert_eq!(contract.get_fee_percent(), Some("0.0001".to_string()));
+        assert_eq!(contract.get_fee_bps(), Some(0.into()));
     }
 
     #[test]
-    #[should_panic(expected = "Couldn't parse percent: provided percent is more than 10%")]
     fn test_set_too_high_percents() {
         let mut contract = FeesCalculator::new(vec![]);
-        contract.set_fee_percent(Some("12.12".to_string()));
+        assert_eq!(
+            contract.set_fee_percent(Some("12.12".to_string())),
+            Err(FeesError::InvalidPercent(ParseError::TooHighPercent))
+        );
     }
 }
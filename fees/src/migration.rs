@@ -0,0 +1,2407 @@
+use aurora_engine_types::types::Address;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::store::{LookupMap, LookupSet, Vector};
+use near_sdk::{env, AccountId};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::multisig::{Multisig, Proposal};
+use crate::oracle::TokenPrice;
+use crate::storage::{Statistics, SupportedTokens};
+use crate::{
+    FeeCaps, FeeHistoryEntry, FeesCalculator, KeyPrefix, PendingFeeChange, Promotion, Role,
+    RoundingMode, Tier, TokenMetadata, TokenRegistration, DEFAULT_MAX_PRICE_AGE,
+};
+
+const STATE_KEY: &[u8] = b"STATE";
+
+/// The version of the contract state. The version is stored as the first field of the state,
+/// so the layout of the state could be detected by the `migrate`. The states of the versions
+/// prior to the `V1` aren't prefixed with the version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh", use_discriminant = true)]
+#[repr(u8)]
+pub enum StateVersion {
+    V1 = 1,
+    V2 = 2,
+    V3 = 3,
+    V4 = 4,
+    V5 = 5,
+    V6 = 6,
+    V7 = 7,
+    V8 = 8,
+    V9 = 9,
+    V10 = 10,
+    V11 = 11,
+    V12 = 12,
+    V13 = 13,
+    V14 = 14,
+    V15 = 15,
+    V16 = 16,
+    V17 = 17,
+    V18 = 18,
+    V19 = 19,
+    V20 = 20,
+    V21 = 21,
+    V22 = 22,
+    V23 = 23,
+    V24 = 24,
+    V25 = 25,
+    V26 = 26,
+}
+
+impl StateVersion {
+    pub const LATEST: Self = Self::V26;
+}
+
+/// The state of the contract of any known version.
+enum VersionedFeesCalculator {
+    V0(FeesCalculatorV0),
+    V1(FeesCalculatorV1),
+    V2(FeesCalculatorV2),
+    V3(FeesCalculatorV3),
+    V4(FeesCalculatorV4),
+    V5(FeesCalculatorV5),
+    V6(FeesCalculatorV6),
+    V7(FeesCalculatorV7),
+    V8(FeesCalculatorV8),
+    V9(FeesCalculatorV9),
+    V10(FeesCalculatorV10),
+    V11(FeesCalculatorV11),
+    V12(FeesCalculatorV12),
+    V13(FeesCalculatorV13),
+    V14(FeesCalculatorV14),
+    V15(FeesCalculatorV15),
+    V16(FeesCalculatorV16),
+    V17(FeesCalculatorV17),
+    V18(FeesCalculatorV18),
+    V19(FeesCalculatorV19),
+    V20(FeesCalculatorV20),
+    V21(FeesCalculatorV21),
+    V22(FeesCalculatorV22),
+    V23(FeesCalculatorV23),
+    V24(FeesCalculatorV24),
+    V25(FeesCalculatorV25),
+    V26(FeesCalculator),
+}
+
+impl VersionedFeesCalculator {
+    /// Reads the state trying the layouts from the latest version to the oldest one.
+    fn read() -> Option<Self> {
+        let state = env::storage_read(STATE_KEY)?;
+
+        FeesCalculator::try_from_slice(&state)
+            .map(Self::V26)
+            .or_else(|_| FeesCalculatorV25::try_from_slice(&state).map(Self::V25))
+            .or_else(|_| FeesCalculatorV24::try_from_slice(&state).map(Self::V24))
+            .or_else(|_| FeesCalculatorV23::try_from_slice(&state).map(Self::V23))
+            .or_else(|_| FeesCalculatorV22::try_from_slice(&state).map(Self::V22))
+            .or_else(|_| FeesCalculatorV21::try_from_slice(&state).map(Self::V21))
+            .or_else(|_| FeesCalculatorV20::try_from_slice(&state).map(Self::V20))
+            .or_else(|_| FeesCalculatorV19::try_from_slice(&state).map(Self::V19))
+            .or_else(|_| FeesCalculatorV18::try_from_slice(&state).map(Self::V18))
+            .or_else(|_| FeesCalculatorV17::try_from_slice(&state).map(Self::V17))
+            .or_else(|_| FeesCalculatorV16::try_from_slice(&state).map(Self::V16))
+            .or_else(|_| FeesCalculatorV15::try_from_slice(&state).map(Self::V15))
+            .or_else(|_| FeesCalculatorV14::try_from_slice(&state).map(Self::V14))
+            .or_else(|_| FeesCalculatorV13::try_from_slice(&state).map(Self::V13))
+            .or_else(|_| FeesCalculatorV12::try_from_slice(&state).map(Self::V12))
+            .or_else(|_| FeesCalculatorV11::try_from_slice(&state).map(Self::V11))
+            .or_else(|_| FeesCalculatorV10::try_from_slice(&state).map(Self::V10))
+            .or_else(|_| FeesCalculatorV9::try_from_slice(&state).map(Self::V9))
+            .or_else(|_| FeesCalculatorV8::try_from_slice(&state).map(Self::V8))
+            .or_else(|_| FeesCalculatorV7::try_from_slice(&state).map(Self::V7))
+            .or_else(|_| FeesCalculatorV6::try_from_slice(&state).map(Self::V6))
+            .or_else(|_| FeesCalculatorV5::try_from_slice(&state).map(Self::V5))
+            .or_else(|_| FeesCalculatorV4::try_from_slice(&state).map(Self::V4))
+            .or_else(|_| FeesCalculatorV3::try_from_slice(&state).map(Self::V3))
+            .or_else(|_| FeesCalculatorV2::try_from_slice(&state).map(Self::V2))
+            .or_else(|_| FeesCalculatorV1::try_from_slice(&state).map(Self::V1))
+            .or_else(|_| FeesCalculatorV0::try_from_slice(&state).map(Self::V0))
+            .ok()
+    }
+
+    /// Migrates the state version by version up to the latest one. The versions are migrated
+    /// in the loop, so the large states don't pile up on the stack.
+    fn into_latest(self) -> FeesCalculator {
+        let mut state = self;
+
+        loop {
+            state = match state {
+                Self::V0(state) => Self::V1(state.into()),
+                Self::V1(state) => Self::V2(state.into()),
+                Self::V2(state) => Self::V3(state.into()),
+                Self::V3(state) => Self::V4(state.into()),
+                Self::V4(state) => Self::V5(state.into()),
+                Self::V5(state) => Self::V6(state.into()),
+                Self::V6(state) => Self::V7(state.into()),
+                Self::V7(state) => Self::V8(state.into()),
+                Self::V8(state) => Self::V9(state.into()),
+                Self::V9(state) => Self::V10(state.into()),
+                Self::V10(state) => Self::V11(state.into()),
+                Self::V11(state) => Self::V12(state.into()),
+                Self::V12(state) => Self::V13(state.into()),
+                Self::V13(state) => Self::V14(state.into()),
+                Self::V14(state) => Self::V15(state.into()),
+                Self::V15(state) => Self::V16(state.into()),
+                Self::V16(state) => Self::V17(state.into()),
+                Self::V17(state) => Self::V18(state.into()),
+                Self::V18(state) => Self::V19(state.into()),
+                Self::V19(state) => Self::V20(state.into()),
+                Self::V20(state) => Self::V21(state.into()),
+                Self::V21(state) => Self::V22(state.into()),
+                Self::V22(state) => Self::V23(state.into()),
+                Self::V23(state) => Self::V24(state.into()),
+                Self::V24(state) => Self::V25(state.into()),
+                Self::V25(state) => Self::V26(state.into()),
+                Self::V26(state) => return state,
+            };
+        }
+    }
+}
+
+/// The state of the contract, where the supported tokens were stored inline in the main state.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV0 {
+    percent: Option<U64>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: BTreeSet<AccountId>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+}
+
+/// The state of the contract before the supported tokens could be set per network.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV1 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+}
+
+/// The state of the contract before the promotions.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV2 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+}
+
+/// The state of the contract before the log of the fee changes.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV3 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+}
+
+/// The state of the contract before the unbridged surcharges.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV4 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// The state of the contract, where the percents were stored in basis points.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV5 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// The state of the contract before the metadata of the supported tokens.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV6 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// The state of the contract before the suspension of the tokens.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV7 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// The state of the contract before the fees for the native NEAR.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV8 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// The state of the contract before the minimum amounts of the networks.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV9 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// The state of the contract before the USD flat fee.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV10 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// The state of the contract before the rounding mode.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV11 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// The state of the contract before the fee recipients of the tokens.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV12 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// The state of the contract before the forwarding statistics.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV13 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// The state of the contract before the dust thresholds.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV14 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// Add the fee in NEAR attached to the forward.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV15 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    dust_thresholds: BTreeMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// Add the fee percents of the accounts.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV16 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    near_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    dust_thresholds: BTreeMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// Add the time-limited delegations of the fee setting.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV17 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    account_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    near_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    dust_thresholds: BTreeMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// Add the registry of supported networks.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV18 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    fee_delegations: BTreeMap<AccountId, U64>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    account_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    near_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    dust_thresholds: BTreeMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// Add the multisig approval of the fee changes.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV19 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    fee_delegations: BTreeMap<AccountId, U64>,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    supported_networks: BTreeSet<AccountId>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    account_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    near_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    dust_thresholds: BTreeMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// Add the delay of the fee increases.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV20 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    fee_delegations: BTreeMap<AccountId, U64>,
+    multisig: Option<Multisig>,
+    proposals: BTreeMap<u64, Proposal>,
+    next_proposal_id: u64,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    supported_networks: BTreeSet<AccountId>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    account_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    near_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    dust_thresholds: BTreeMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// Add the registration of tokens by third parties.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV21 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    fee_increase_delay: u64,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    fee_delegations: BTreeMap<AccountId, U64>,
+    multisig: Option<Multisig>,
+    proposals: BTreeMap<u64, Proposal>,
+    next_proposal_id: u64,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    supported_networks: BTreeSet<AccountId>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    account_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    near_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    dust_thresholds: BTreeMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// Add the max delta of the fee changes.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV22 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    fee_increase_delay: u64,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    fee_delegations: BTreeMap<AccountId, U64>,
+    multisig: Option<Multisig>,
+    proposals: BTreeMap<u64, Proposal>,
+    next_proposal_id: u64,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    listing_bond: Option<U128>,
+    token_registrations: BTreeMap<AccountId, TokenRegistration>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    supported_networks: BTreeSet<AccountId>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    account_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    near_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    dust_thresholds: BTreeMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// Add the default fee of the unlisted tokens.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV23 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    fee_increase_delay: u64,
+    max_fee_delta: Option<U64>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    fee_delegations: BTreeMap<AccountId, U64>,
+    multisig: Option<Multisig>,
+    proposals: BTreeMap<u64, Proposal>,
+    next_proposal_id: u64,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    listing_bond: Option<U128>,
+    token_registrations: BTreeMap<AccountId, TokenRegistration>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    supported_networks: BTreeSet<AccountId>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    account_fees: BTreeMap<AccountId, Option<U64>>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    near_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    dust_thresholds: BTreeMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// Add the maximum amounts of the tokens.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV24 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    fee_increase_delay: u64,
+    max_fee_delta: Option<U64>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    fee_delegations: BTreeMap<AccountId, U64>,
+    multisig: Option<Multisig>,
+    proposals: BTreeMap<u64, Proposal>,
+    next_proposal_id: u64,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    listing_bond: Option<U128>,
+    token_registrations: BTreeMap<AccountId, TokenRegistration>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    supported_networks: BTreeSet<AccountId>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    account_fees: BTreeMap<AccountId, Option<U64>>,
+    unlisted_token_percent: Option<U64>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    near_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    dust_thresholds: BTreeMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+/// Move the per-token maps to the lookup maps.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct FeesCalculatorV25 {
+    version: StateVersion,
+    percent: Option<U64>,
+    pending_fee_change: Option<PendingFeeChange>,
+    fee_increase_delay: u64,
+    max_fee_delta: Option<U64>,
+    paused: bool,
+    owner: AccountId,
+    proposed_owner: Option<AccountId>,
+    roles: BTreeMap<Role, BTreeSet<AccountId>>,
+    fee_delegations: BTreeMap<AccountId, U64>,
+    multisig: Option<Multisig>,
+    proposals: BTreeMap<u64, Proposal>,
+    next_proposal_id: u64,
+    supported_tokens: SupportedTokens,
+    token_metadata: BTreeMap<AccountId, TokenMetadata>,
+    listing_bond: Option<U128>,
+    token_registrations: BTreeMap<AccountId, TokenRegistration>,
+    suspended_tokens: BTreeSet<AccountId>,
+    network_supported_tokens: BTreeMap<AccountId, SupportedTokens>,
+    supported_networks: BTreeSet<AccountId>,
+    token_fees: BTreeMap<AccountId, Option<U64>>,
+    network_fees: BTreeMap<AccountId, Option<U64>>,
+    account_fees: BTreeMap<AccountId, Option<U64>>,
+    unlisted_token_percent: Option<U64>,
+    native_percent: Option<U64>,
+    native_flat_fee: Option<U128>,
+    near_fee: Option<U128>,
+    min_amounts: BTreeMap<AccountId, BTreeMap<AccountId, U128>>,
+    max_amounts: BTreeMap<AccountId, U128>,
+    price_oracle: Option<AccountId>,
+    max_price_age: u64,
+    token_prices: BTreeMap<AccountId, TokenPrice>,
+    usd_flat_fee: Option<U128>,
+    rounding_mode: RoundingMode,
+    fee_recipients: BTreeMap<AccountId, AccountId>,
+    token_stats: Statistics,
+    network_stats: Statistics,
+    token_fee_tiers: BTreeMap<AccountId, Vec<Tier>>,
+    token_flat_fees: BTreeMap<AccountId, U128>,
+    token_fee_caps: BTreeMap<AccountId, FeeCaps>,
+    dust_thresholds: BTreeMap<AccountId, U128>,
+    promotions: BTreeMap<AccountId, Promotion>,
+    unbridged_surcharges: BTreeMap<AccountId, U128>,
+    charged_surcharges: LookupSet<(AccountId, AccountId)>,
+    exempt_addresses: BTreeSet<Address>,
+    free_forwards: u64,
+    forwards_count: LookupMap<AccountId, u64>,
+    referrers: BTreeSet<AccountId>,
+    referral_share: u64,
+    fee_history: Vector<FeeHistoryEntry>,
+}
+
+impl From<FeesCalculatorV0> for FeesCalculatorV1 {
+    fn from(state: FeesCalculatorV0) -> Self {
+        let mut supported_tokens = SupportedTokens::new();
+        supported_tokens.extend(state.supported_tokens);
+
+        Self {
+            version: StateVersion::V1,
+            percent: state.percent,
+            pending_fee_change: None,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: BTreeSet::new(),
+            referral_share: 0,
+        }
+    }
+}
+
+impl From<FeesCalculatorV1> for FeesCalculatorV2 {
+    fn from(state: FeesCalculatorV1) -> Self {
+        Self {
+            version: StateVersion::V2,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            network_supported_tokens: BTreeMap::new(),
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+        }
+    }
+}
+
+impl From<FeesCalculatorV2> for FeesCalculatorV3 {
+    fn from(state: FeesCalculatorV2) -> Self {
+        Self {
+            version: StateVersion::V3,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: BTreeMap::new(),
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+        }
+    }
+}
+
+impl From<FeesCalculatorV3> for FeesCalculatorV4 {
+    fn from(state: FeesCalculatorV3) -> Self {
+        Self {
+            version: StateVersion::V4,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: state.promotions,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: Vector::new(KeyPrefix::FeeHistory),
+        }
+    }
+}
+
+impl From<FeesCalculatorV4> for FeesCalculatorV5 {
+    fn from(state: FeesCalculatorV4) -> Self {
+        Self {
+            version: StateVersion::V5,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: state.promotions,
+            unbridged_surcharges: BTreeMap::new(),
+            charged_surcharges: LookupSet::new(KeyPrefix::ChargedSurcharges),
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV5> for FeesCalculatorV6 {
+    fn from(state: FeesCalculatorV5) -> Self {
+        let bps_to_percent = |percent: Option<U64>| percent.map(|U64(bps)| U64(bps * 100));
+
+        Self {
+            version: StateVersion::V6,
+            percent: bps_to_percent(state.percent),
+            pending_fee_change: state.pending_fee_change.map(|change| PendingFeeChange {
+                percent: bps_to_percent(change.percent),
+                effective_at: change.effective_at,
+            }),
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state
+                .token_fees
+                .into_iter()
+                .map(|(token_id, percent)| (token_id, bps_to_percent(percent)))
+                .collect(),
+            network_fees: state
+                .network_fees
+                .into_iter()
+                .map(|(network_id, percent)| (network_id, bps_to_percent(percent)))
+                .collect(),
+            token_fee_tiers: state
+                .token_fee_tiers
+                .into_iter()
+                .map(|(token_id, tiers)| {
+                    let tiers = tiers
+                        .into_iter()
+                        .map(|tier| Tier {
+                            up_to: tier.up_to,
+                            percent: bps_to_percent(tier.percent),
+                        })
+                        .collect();
+                    (token_id, tiers)
+                })
+                .collect(),
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV6> for FeesCalculatorV7 {
+    fn from(state: FeesCalculatorV6) -> Self {
+        Self {
+            version: StateVersion::V7,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            token_metadata: BTreeMap::new(),
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV7> for FeesCalculatorV8 {
+    fn from(state: FeesCalculatorV7) -> Self {
+        Self {
+            version: StateVersion::V8,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: BTreeSet::new(),
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV8> for FeesCalculatorV9 {
+    fn from(state: FeesCalculatorV8) -> Self {
+        Self {
+            version: StateVersion::V9,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            native_percent: state.percent,
+            native_flat_fee: None,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV9> for FeesCalculatorV10 {
+    fn from(state: FeesCalculatorV9) -> Self {
+        Self {
+            version: StateVersion::V10,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            min_amounts: BTreeMap::new(),
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV10> for FeesCalculatorV11 {
+    fn from(state: FeesCalculatorV10) -> Self {
+        Self {
+            version: StateVersion::V11,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: None,
+            max_price_age: DEFAULT_MAX_PRICE_AGE,
+            token_prices: BTreeMap::new(),
+            usd_flat_fee: None,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV11> for FeesCalculatorV12 {
+    fn from(state: FeesCalculatorV11) -> Self {
+        Self {
+            version: StateVersion::V12,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: RoundingMode::Floor,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV12> for FeesCalculatorV13 {
+    fn from(state: FeesCalculatorV12) -> Self {
+        Self {
+            version: StateVersion::V13,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: BTreeMap::new(),
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV13> for FeesCalculatorV14 {
+    fn from(state: FeesCalculatorV13) -> Self {
+        Self {
+            version: StateVersion::V14,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: Statistics::new(KeyPrefix::TokenStats, KeyPrefix::TokenStatsList),
+            network_stats: Statistics::new(KeyPrefix::NetworkStats, KeyPrefix::NetworkStatsList),
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV14> for FeesCalculatorV15 {
+    fn from(state: FeesCalculatorV14) -> Self {
+        Self {
+            version: StateVersion::V15,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            dust_thresholds: BTreeMap::new(),
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV15> for FeesCalculatorV16 {
+    fn from(state: FeesCalculatorV15) -> Self {
+        Self {
+            version: StateVersion::V16,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            near_fee: None,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            dust_thresholds: state.dust_thresholds,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV16> for FeesCalculatorV17 {
+    fn from(state: FeesCalculatorV16) -> Self {
+        Self {
+            version: StateVersion::V17,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            account_fees: BTreeMap::new(),
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            near_fee: state.near_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            dust_thresholds: state.dust_thresholds,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV17> for FeesCalculatorV18 {
+    fn from(state: FeesCalculatorV17) -> Self {
+        Self {
+            version: StateVersion::V18,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            fee_delegations: BTreeMap::new(),
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            account_fees: state.account_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            near_fee: state.near_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            dust_thresholds: state.dust_thresholds,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV18> for FeesCalculatorV19 {
+    fn from(state: FeesCalculatorV18) -> Self {
+        Self {
+            version: StateVersion::V19,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            fee_delegations: state.fee_delegations,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            supported_networks: BTreeSet::new(),
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            account_fees: state.account_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            near_fee: state.near_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            dust_thresholds: state.dust_thresholds,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV19> for FeesCalculatorV20 {
+    fn from(state: FeesCalculatorV19) -> Self {
+        Self {
+            version: StateVersion::V20,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            fee_delegations: state.fee_delegations,
+            multisig: None,
+            proposals: BTreeMap::new(),
+            next_proposal_id: 0,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            supported_networks: state.supported_networks,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            account_fees: state.account_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            near_fee: state.near_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            dust_thresholds: state.dust_thresholds,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV20> for FeesCalculatorV21 {
+    fn from(state: FeesCalculatorV20) -> Self {
+        Self {
+            version: StateVersion::V21,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            fee_increase_delay: 0,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            fee_delegations: state.fee_delegations,
+            multisig: state.multisig,
+            proposals: state.proposals,
+            next_proposal_id: state.next_proposal_id,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            supported_networks: state.supported_networks,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            account_fees: state.account_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            near_fee: state.near_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            dust_thresholds: state.dust_thresholds,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV21> for FeesCalculatorV22 {
+    fn from(state: FeesCalculatorV21) -> Self {
+        Self {
+            version: StateVersion::V22,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            fee_increase_delay: state.fee_increase_delay,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            fee_delegations: state.fee_delegations,
+            multisig: state.multisig,
+            proposals: state.proposals,
+            next_proposal_id: state.next_proposal_id,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            listing_bond: None,
+            token_registrations: BTreeMap::new(),
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            supported_networks: state.supported_networks,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            account_fees: state.account_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            near_fee: state.near_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            dust_thresholds: state.dust_thresholds,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV22> for FeesCalculatorV23 {
+    fn from(state: FeesCalculatorV22) -> Self {
+        Self {
+            version: StateVersion::V23,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            fee_increase_delay: state.fee_increase_delay,
+            max_fee_delta: None,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            fee_delegations: state.fee_delegations,
+            multisig: state.multisig,
+            proposals: state.proposals,
+            next_proposal_id: state.next_proposal_id,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            listing_bond: state.listing_bond,
+            token_registrations: state.token_registrations,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            supported_networks: state.supported_networks,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            account_fees: state.account_fees,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            near_fee: state.near_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            dust_thresholds: state.dust_thresholds,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV23> for FeesCalculatorV24 {
+    fn from(state: FeesCalculatorV23) -> Self {
+        Self {
+            version: StateVersion::V24,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            fee_increase_delay: state.fee_increase_delay,
+            max_fee_delta: state.max_fee_delta,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            fee_delegations: state.fee_delegations,
+            multisig: state.multisig,
+            proposals: state.proposals,
+            next_proposal_id: state.next_proposal_id,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            listing_bond: state.listing_bond,
+            token_registrations: state.token_registrations,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            supported_networks: state.supported_networks,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            account_fees: state.account_fees,
+            unlisted_token_percent: None,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            near_fee: state.near_fee,
+            min_amounts: state.min_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            dust_thresholds: state.dust_thresholds,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV24> for FeesCalculatorV25 {
+    fn from(state: FeesCalculatorV24) -> Self {
+        Self {
+            version: StateVersion::V25,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            fee_increase_delay: state.fee_increase_delay,
+            max_fee_delta: state.max_fee_delta,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            fee_delegations: state.fee_delegations,
+            multisig: state.multisig,
+            proposals: state.proposals,
+            next_proposal_id: state.next_proposal_id,
+            supported_tokens: state.supported_tokens,
+            token_metadata: state.token_metadata,
+            listing_bond: state.listing_bond,
+            token_registrations: state.token_registrations,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            supported_networks: state.supported_networks,
+            token_fees: state.token_fees,
+            network_fees: state.network_fees,
+            account_fees: state.account_fees,
+            unlisted_token_percent: state.unlisted_token_percent,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            near_fee: state.near_fee,
+            min_amounts: state.min_amounts,
+            max_amounts: BTreeMap::new(),
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: state.token_prices,
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: state.token_fee_tiers,
+            token_flat_fees: state.token_flat_fees,
+            token_fee_caps: state.token_fee_caps,
+            dust_thresholds: state.dust_thresholds,
+            promotions: state.promotions,
+            unbridged_surcharges: state.unbridged_surcharges,
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+impl From<FeesCalculatorV25> for FeesCalculator {
+    fn from(state: FeesCalculatorV25) -> Self {
+        Self {
+            version: StateVersion::V26,
+            percent: state.percent,
+            pending_fee_change: state.pending_fee_change,
+            fee_increase_delay: state.fee_increase_delay,
+            max_fee_delta: state.max_fee_delta,
+            paused: state.paused,
+            owner: state.owner,
+            proposed_owner: state.proposed_owner,
+            roles: state.roles,
+            fee_delegations: state.fee_delegations,
+            multisig: state.multisig,
+            proposals: state.proposals,
+            next_proposal_id: state.next_proposal_id,
+            supported_tokens: state.supported_tokens,
+            token_metadata: lookup_map(KeyPrefix::TokenMetadata, state.token_metadata),
+            listing_bond: state.listing_bond,
+            token_registrations: state.token_registrations,
+            suspended_tokens: state.suspended_tokens,
+            network_supported_tokens: state.network_supported_tokens,
+            supported_networks: state.supported_networks,
+            token_fees: lookup_map(KeyPrefix::TokenFees, state.token_fees),
+            network_fees: state.network_fees,
+            account_fees: state.account_fees,
+            unlisted_token_percent: state.unlisted_token_percent,
+            native_percent: state.native_percent,
+            native_flat_fee: state.native_flat_fee,
+            near_fee: state.near_fee,
+            min_amounts: state.min_amounts,
+            max_amounts: state.max_amounts,
+            price_oracle: state.price_oracle,
+            max_price_age: state.max_price_age,
+            token_prices: lookup_map(KeyPrefix::TokenPrices, state.token_prices),
+            usd_flat_fee: state.usd_flat_fee,
+            rounding_mode: state.rounding_mode,
+            fee_recipients: state.fee_recipients,
+            token_stats: state.token_stats,
+            network_stats: state.network_stats,
+            token_fee_tiers: lookup_map(KeyPrefix::TokenFeeTiers, state.token_fee_tiers),
+            token_flat_fees: lookup_map(KeyPrefix::TokenFlatFees, state.token_flat_fees),
+            token_fee_caps: lookup_map(KeyPrefix::TokenFeeCaps, state.token_fee_caps),
+            dust_thresholds: lookup_map(KeyPrefix::DustThresholds, state.dust_thresholds),
+            promotions: state.promotions,
+            unbridged_surcharges: lookup_map(
+                KeyPrefix::UnbridgedSurcharges,
+                state.unbridged_surcharges,
+            ),
+            charged_surcharges: state.charged_surcharges,
+            exempt_addresses: state.exempt_addresses,
+            free_forwards: state.free_forwards,
+            forwards_count: state.forwards_count,
+            referrers: state.referrers,
+            referral_share: state.referral_share,
+            fee_history: state.fee_history,
+        }
+    }
+}
+
+fn lookup_map<V>(prefix: KeyPrefix, map: BTreeMap<AccountId, V>) -> LookupMap<AccountId, V>
+where
+    V: BorshSerialize + BorshDeserialize,
+{
+    let mut result = LookupMap::new(prefix);
+    result.extend(map);
+    result
+}
+
+pub fn migrate() -> FeesCalculator {
+    VersionedFeesCalculator::read()
+        .unwrap_or_else(|| env::panic_str("Couldn't read the state"))
+        .into_latest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FeesCalculatorV0, FeesCalculatorV1, StateVersion};
+    use crate::storage::SupportedTokens;
+    use crate::{FeesCalculator, KeyPrefix};
+    use aurora_engine_types::types::Address;
+    use near_sdk::json_types::U64;
+    use near_sdk::store::LookupMap;
+    use near_sdk::{env, AccountId};
+    use std::collections::{BTreeMap, BTreeSet};
+
+    #[test]
+    fn test_migrate_from_v0() {
+        let aurora = "aurora".parse().unwrap();
+        let owner: AccountId = "owner.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+
+        env::state_write(&FeesCalculatorV0 {
+            percent: Some(U64(250)),
+            paused: false,
+            owner: owner.clone(),
+            proposed_owner: None,
+            roles: BTreeMap::new(),
+            supported_tokens: BTreeSet::from([usdt.clone()]),
+            token_fees: BTreeMap::from([(usdt.clone(), Some(U64(100)))]),
+            network_fees: BTreeMap::new(),
+            token_fee_tiers: BTreeMap::new(),
+            token_flat_fees: BTreeMap::new(),
+            token_fee_caps: BTreeMap::new(),
+            exempt_addresses: BTreeSet::new(),
+            free_forwards: 0,
+            forwards_count: LookupMap::new(KeyPrefix::ForwardsCount),
+        });
+
+        let contract = FeesCalculator::migrate();
+
+        assert_eq!(contract.get_owner(), &owner);
+        assert_eq!(contract.get_fee_percent(), Some("2.50".to_string()));
+        assert_eq!(contract.supported_tokens(), vec![&usdt]);
+        assert_eq!(
+            contract.get_token_fee_percent(&usdt),
+            Some("1.00".to_string())
+        );
+        assert_eq!(
+            contract
+                .calculate_fees(1000.into(), &usdt, &aurora, Address::from_array([1; 20]))
+                .fee,
+            10.into()
+        );
+    }
+
+    #[test]
+    fn test_migrate_from_v1() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let owner: AccountId = "owner.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut supported_tokens = SupportedTokens::new();
+        supported_tokens.extend([usdt.clone()]);
+
+        env::state_write(&FeesCalculatorV1 {
+            version: StateVersion::V1,
+            percent: Some(U64(250)),
+            pending_fee_change: None,
+            paused: false,
+            owner: owner.clone(),
+            proposed_owner: None,
+            roles: BTreeMap::new(),
+            supported_tokens,
+            token_fees: BTreeMap::new(),
+            network_fees: BTreeMap::new(),
+            token_fee_tiers: BTreeMap::new(),
+            token_flat_fees: BTreeMap::new(),
+            token_fee_caps: BTreeMap::new(),
+            exempt_addresses: BTreeSet::new(),
+            free_forwards: 0,
+            forwards_count: LookupMap::new(KeyPrefix::ForwardsCount),
+            referrers: BTreeSet::new(),
+            referral_share: 1000,
+        });
+
+        let contract = FeesCalculator::migrate();
+
+        assert_eq!(contract.version, StateVersion::LATEST);
+        assert_eq!(contract.get_owner(), &owner);
+        assert_eq!(contract.get_referral_share(), U64(1000));
+        assert_eq!(contract.get_fee_percent(), Some("2.50".to_string()));
+        assert!(contract.is_token_supported(&usdt, &aurora));
+        assert_eq!(contract.network_supported_tokens(&aurora, None, None), None);
+    }
+
+    #[test]
+    fn test_migrate_latest() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut contract = FeesCalculator::new(vec![usdt.clone()]);
+
+        contract.set_fee_percent(Some("1".to_string())).unwrap();
+        env::state_write(&contract);
+        // The supported tokens are flushed to the storage on drop.
+        drop(contract);
+
+        let contract = FeesCalculator::migrate();
+
+        assert_eq!(contract.version, StateVersion::LATEST);
+        assert_eq!(contract.get_fee_percent(), Some("1.00".to_string()));
+        assert_eq!(contract.supported_tokens(), vec![&usdt]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Couldn't read the state")]
+    fn test_migrate_unknown_state() {
+        env::storage_write(b"STATE", b"unknown");
+        let _ = FeesCalculator::migrate();
+    }
+}
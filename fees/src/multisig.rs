@@ -0,0 +1,144 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
+use std::collections::BTreeSet;
+
+/// The methods changing the fees or the supported tokens, which could be proposed while
+/// the multisig is enabled.
+pub const PROPOSABLE_METHODS: &[&str] = &[
+    "set_fee_percent",
+    "set_fee_bps",
+    "schedule_fee_change",
+    "cancel_fee_change",
+    "set_fee_increase_delay",
+    "set_max_fee_delta",
+    "set_unlisted_token_fee",
+    "set_native_fee",
+    "set_native_flat_fee",
+    "set_near_fee",
+    "set_unbridged_surcharge",
+    "set_token_bridged",
+    "set_free_forwards",
+    "set_referral_share",
+    "add_supported_token",
+    "remove_supported_token",
+    "add_supported_tokens",
+    "remove_supported_tokens",
+    "set_listing_bond",
+    "approve_token",
+    "reject_token",
+    "add_network_supported_token",
+    "remove_network_supported_token",
+    "remove_network_supported_tokens",
+    "suspend_token",
+    "resume_token",
+    "set_token_fee",
+    "remove_token_fee",
+    "set_token_fee_tiers",
+    "remove_token_fee_tiers",
+    "set_token_flat_fee",
+    "set_usd_flat_fee",
+    "set_dust_threshold",
+    "set_token_fee_caps",
+    "set_network_fee",
+    "remove_network_fee",
+    "set_account_fee",
+    "remove_account_fee",
+    "set_min_amount",
+    "set_max_amount",
+    "set_multisig",
+];
+
+/// The signers of the multisig and the number of their confirmations required to execute
+/// a proposal.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Multisig {
+    pub signers: BTreeSet<AccountId>,
+    pub threshold: u32,
+}
+
+impl Multisig {
+    /// Returns `true` if the threshold is not zero and doesn't exceed the number of signers.
+    pub fn is_valid(&self) -> bool {
+        self.threshold > 0
+            && usize::try_from(self.threshold)
+                .is_ok_and(|threshold| threshold <= self.signers.len())
+    }
+
+    pub fn is_signer(&self, account_id: &AccountId) -> bool {
+        self.signers.contains(account_id)
+    }
+}
+
+/// The proposed call of the method of the fees contract, which is made by the contract itself
+/// after the proposal has been confirmed by the required number of signers.
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Proposal {
+    pub method: String,
+    /// The JSON arguments of the method.
+    pub args: String,
+    pub proposer: AccountId,
+    pub confirmations: BTreeSet<AccountId>,
+}
+
+impl Proposal {
+    /// Returns `true` if the proposal has been confirmed by the required number of signers.
+    /// The confirmations of the accounts, which aren't signers anymore, aren't counted.
+    pub fn is_approved(&self, multisig: &Multisig) -> bool {
+        let confirmations = self
+            .confirmations
+            .iter()
+            .filter(|account_id| multisig.is_signer(account_id))
+            .count();
+
+        u32::try_from(confirmations).is_ok_and(|confirmations| confirmations >= multisig.threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Multisig, Proposal};
+    use near_sdk::AccountId;
+
+    fn multisig(threshold: u32) -> Multisig {
+        Multisig {
+            signers: ["alice.near", "bob.near", "carol.near"]
+                .iter()
+                .map(|id| id.parse().unwrap())
+                .collect(),
+            threshold,
+        }
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(!multisig(0).is_valid());
+        assert!(multisig(1).is_valid());
+        assert!(multisig(3).is_valid());
+        assert!(!multisig(4).is_valid());
+    }
+
+    #[test]
+    fn test_is_approved() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let mut proposal = Proposal {
+            method: "set_fee_percent".to_string(),
+            args: r#"{"percent":"1"}"#.to_string(),
+            proposer: alice.clone(),
+            confirmations: std::iter::once(alice).collect(),
+        };
+
+        assert!(!proposal.is_approved(&multisig(2)));
+
+        // The confirmation of the account, which isn't a signer, isn't counted.
+        proposal.confirmations.insert("dave.near".parse().unwrap());
+        assert!(!proposal.is_approved(&multisig(2)));
+
+        proposal.confirmations.insert("bob.near".parse().unwrap());
+        assert!(proposal.is_approved(&multisig(2)));
+    }
+}
@@ -0,0 +1,129 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{ext_contract, AccountId};
+
+use crate::{RoundingMode, NANOS_PER_SEC};
+
+const CENTS_PER_USD: u128 = 100;
+
+#[ext_contract(ext_oracle)]
+#[allow(clippy::module_name_repetitions)]
+pub trait ExtOracle {
+    fn get_price_data(&self, asset_ids: Option<Vec<AccountId>>) -> PriceData;
+}
+
+/// The prices returned by the `get_price_data` of the price oracle.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceData {
+    /// The block timestamp in nanoseconds when the prices have been requested.
+    pub timestamp: U64,
+    pub recency_duration_sec: u32,
+    pub prices: Vec<AssetOptionalPrice>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AssetOptionalPrice {
+    pub asset_id: AccountId,
+    pub price: Option<Price>,
+}
+
+/// The price of the smallest unit of the asset in USD is `multiplier / 10^decimals`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Price {
+    pub multiplier: U128,
+    pub decimals: u8,
+}
+
+/// The latest price of the token received from the price oracle.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize, BorshSerialize, Deserialize, Serialize,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenPrice {
+    pub multiplier: U128,
+    pub decimals: u8,
+    /// The timestamp of the price data in nanoseconds.
+    pub timestamp: U64,
+}
+
+impl TokenPrice {
+    /// Returns `true` if the price is older than `max_age` seconds at the `now` timestamp.
+    pub const fn is_stale(&self, now: u64, max_age: u64) -> bool {
+        now.saturating_sub(self.timestamp.0) > max_age.saturating_mul(NANOS_PER_SEC)
+    }
+
+    /// Converts the amount in USD cents to the token units. Returns `None` if the price is zero
+    /// or the conversion overflows.
+    pub fn usd_cents_to_tokens(&self, cents: u128, rounding: RoundingMode) -> Option<u128> {
+        let denominator = self.multiplier.0.checked_mul(CENTS_PER_USD)?;
+
+        if denominator == 0 {
+            return None;
+        }
+
+        let numerator = 10u128
+            .checked_pow(u32::from(self.decimals))?
+            .checked_mul(cents)?;
+
+        Some(rounding.div(numerator, denominator))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenPrice;
+    use crate::RoundingMode;
+
+    #[test]
+    fn test_usd_cents_to_tokens() {
+        // The price of USDT with 6 decimals, so 1 USDT is 1 USD.
+        let usdt = TokenPrice {
+            multiplier: 10000.into(),
+            decimals: 10,
+            timestamp: 0.into(),
+        };
+        assert_eq!(
+            usdt.usd_cents_to_tokens(100, RoundingMode::Floor),
+            Some(1_000_000)
+        );
+        assert_eq!(
+            usdt.usd_cents_to_tokens(1, RoundingMode::Floor),
+            Some(10_000)
+        );
+
+        // The price of wNEAR with 24 decimals, so 1 NEAR is 5 USD.
+        let wnear = TokenPrice {
+            multiplier: 50000.into(),
+            decimals: 28,
+            timestamp: 0.into(),
+        };
+        assert_eq!(
+            wnear.usd_cents_to_tokens(100, RoundingMode::Floor),
+            Some(200_000_000_000_000_000_000_000)
+        );
+
+        let zero = TokenPrice {
+            multiplier: 0.into(),
+            decimals: 10,
+            timestamp: 0.into(),
+        };
+        assert_eq!(zero.usd_cents_to_tokens(100, RoundingMode::Floor), None);
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let price = TokenPrice {
+            multiplier: 10000.into(),
+            decimals: 10,
+            timestamp: 1_000_000_000.into(),
+        };
+
+        assert!(!price.is_stale(61_000_000_000, 60));
+        assert!(price.is_stale(61_000_000_001, 60));
+    }
+}
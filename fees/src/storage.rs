@@ -0,0 +1,201 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::store::{LookupMap, LookupSet, Vector};
+use near_sdk::AccountId;
+
+use crate::{KeyPrefix, Stats};
+
+/// On-chain storage of the supported tokens. The set is used for the membership checks,
+/// which happen on every fee calculation, and the list is used for the views only.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct SupportedTokens {
+    set: LookupSet<AccountId>,
+    list: Vector<AccountId>,
+}
+
+impl SupportedTokens {
+    pub fn new() -> Self {
+        Self {
+            set: LookupSet::new(KeyPrefix::SupportedTokens),
+            list: Vector::new(KeyPrefix::SupportedTokensList),
+        }
+    }
+
+    /// Creates the storage of the tokens supported on the specific network.
+    pub fn for_network(network_id: &AccountId) -> Self {
+        Self {
+            set: LookupSet::new(KeyPrefix::NetworkSupportedTokens(network_id.clone())),
+            list: Vector::new(KeyPrefix::NetworkSupportedTokensList(network_id.clone())),
+        }
+    }
+
+    pub fn contains(&self, token_id: &AccountId) -> bool {
+        self.set.contains(token_id)
+    }
+
+    /// Adds the token and returns `true` if it hasn't been present before.
+    pub fn insert(&mut self, token_id: &AccountId) -> bool {
+        let is_added = self.set.insert(token_id.clone());
+
+        if is_added {
+            self.list.push(token_id.clone());
+        }
+
+        is_added
+    }
+
+    /// Removes the token and returns `true` if it has been present before.
+    pub fn remove(&mut self, token_id: &AccountId) -> bool {
+        let is_removed = self.set.remove(token_id);
+
+        if is_removed {
+            if let Some(index) = self.list.iter().position(|id| id == token_id) {
+                self.list
+                    .swap_remove(u32::try_from(index).unwrap_or(u32::MAX));
+            }
+        }
+
+        is_removed
+    }
+
+    /// Removes all the tokens from the storage.
+    pub fn clear(&mut self) {
+        for token_id in self.list.drain(..) {
+            self.set.remove(&token_id);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AccountId> {
+        self.list.iter()
+    }
+
+    pub fn len(&self) -> u64 {
+        u64::from(self.list.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+}
+
+impl Extend<AccountId> for SupportedTokens {
+    fn extend<T: IntoIterator<Item = AccountId>>(&mut self, iter: T) {
+        for token_id in iter {
+            self.insert(&token_id);
+        }
+    }
+}
+
+/// On-chain storage of the forwarding statistics per token or per network. The list of the ids
+/// is used for the paginated views.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct Statistics {
+    stats: LookupMap<AccountId, Stats>,
+    ids: Vector<AccountId>,
+}
+
+impl Statistics {
+    pub fn new(stats_prefix: KeyPrefix, ids_prefix: KeyPrefix) -> Self {
+        Self {
+            stats: LookupMap::new(stats_prefix),
+            ids: Vector::new(ids_prefix),
+        }
+    }
+
+    /// Adds the forwarded amount and the fee to the statistics of the id.
+    pub fn record(&mut self, id: &AccountId, amount: u128, fee: u128) {
+        if let Some(stats) = self.stats.get_mut(id) {
+            stats.record(amount, fee);
+        } else {
+            let mut stats = Stats::default();
+            stats.record(amount, fee);
+            self.stats.insert(id.clone(), stats);
+            self.ids.push(id.clone());
+        }
+    }
+
+    pub fn get(&self, id: &AccountId) -> Option<&Stats> {
+        self.stats.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&AccountId, &Stats)> {
+        self.ids
+            .iter()
+            .filter_map(|id| self.stats.get(id).map(|stats| (id, stats)))
+    }
+
+    pub fn len(&self) -> u64 {
+        u64::from(self.ids.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Statistics, SupportedTokens};
+    use crate::{KeyPrefix, Stats};
+    use near_sdk::AccountId;
+
+    #[test]
+    #[allow(clippy::similar_names)]
+    fn test_insert_remove() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let usdc: AccountId = "usdc.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let mut tokens = SupportedTokens::new();
+
+        tokens.extend([usdt.clone(), usdc.clone(), wnear.clone()]);
+
+        assert!(!tokens.insert(&usdc));
+        assert!(tokens.contains(&usdc));
+        assert_eq!(tokens.len(), 3);
+
+        assert!(tokens.remove(&usdt));
+        assert!(!tokens.remove(&usdt));
+        assert!(!tokens.contains(&usdt));
+        assert_eq!(tokens.iter().collect::<Vec<_>>(), vec![&wnear, &usdc]);
+    }
+
+    #[test]
+    fn test_network_tokens() {
+        let aurora: AccountId = "aurora".parse().unwrap();
+        let silo: AccountId = "silo.near".parse().unwrap();
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let mut aurora_tokens = SupportedTokens::for_network(&aurora);
+        let silo_tokens = SupportedTokens::for_network(&silo);
+
+        assert!(aurora_tokens.insert(&usdt));
+        assert!(!silo_tokens.contains(&usdt));
+        assert!(!SupportedTokens::new().contains(&usdt));
+
+        aurora_tokens.clear();
+
+        assert!(!aurora_tokens.contains(&usdt));
+        assert_eq!(aurora_tokens.len(), 0);
+    }
+
+    #[test]
+    fn test_statistics() {
+        let usdt: AccountId = "usdt.near".parse().unwrap();
+        let wnear: AccountId = "wrap.near".parse().unwrap();
+        let mut stats = Statistics::new(KeyPrefix::TokenStats, KeyPrefix::TokenStatsList);
+
+        stats.record(&usdt, 1000, 50);
+        stats.record(&wnear, 10, 1);
+        stats.record(&usdt, 2000, 100);
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(
+            stats.get(&usdt),
+            Some(&Stats {
+                forwards: 2,
+                volume: 3000.into(),
+                fees: 150.into(),
+            })
+        );
+        assert_eq!(
+            stats.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![&usdt, &wnear]
+        );
+    }
+}
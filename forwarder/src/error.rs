@@ -7,6 +7,7 @@ pub enum ContractError {
     PrivateCallError,
     BadUtf8String,
     BadNumber,
+    TooLongInput,
 }
 
 impl AsRef<[u8]> for ContractError {
@@ -19,6 +20,7 @@ impl AsRef<[u8]> for ContractError {
             Self::PrivateCallError => b"ERR_PRIVATE_CALL",
             Self::BadUtf8String => b"ERR_BAD_UTF8_STRING",
             Self::BadNumber => b"ERR_BAD_NUMBER",
+            Self::TooLongInput => b"ERR_TOO_LONG_INPUT",
         }
     }
 }
@@ -1,18 +1,27 @@
 #![cfg_attr(target_arch = "wasm32", no_std)]
 #![allow(clippy::module_name_repetitions, clippy::as_conversions)]
 
-use borsh::BorshDeserialize;
+use arrayvec::{ArrayString, ArrayVec};
 use core::alloc::{GlobalAlloc, Layout};
 
-use crate::error::ContractError;
 use crate::params::{
-    ft_balance_args, ft_transfer_args, ft_transfer_call_args, FeesParams, FinishForwardParams,
-    ForwardParams, State,
+    ft_balance_args, ft_transfer_args, ft_transfer_call_args, is_network_supported_args,
+    near_withdraw_args, nft_transfer_args, nft_transfer_call_args, storage_deposit_args,
+    storage_unregister_args, storage_withdraw_args, AccountList, ChargeFeeParams, EstimateParams,
+    FailureReason, FeeCache, FeesParams, FeesResult, FinishForwardParams, ForwardArgs,
+    ForwardParams, ForwardSchedule, ForwardStats, GasConfig, MaxForwardAmountParams, Memo,
+    MinForwardAmountParams, MsgFormat, MsgFormatParams, NativeFeesParams, NearBaseTokenParams,
+    RateLimit, RateWindow, ResolveForwardParams, RetryEntry, RetryQueue, ScheduleParams, Splits,
+    State, StaticFee, SweepParams, TargetAddress, TargetAddressProofParams, TransferParams,
+    UnregisterStorageParams, WithdrawParams, XccPayload,
+};
+use crate::runtime::{
+    ecrecover, keccak256, log_utf8, panic_utf8, Env, PromiseHandler, PromiseId, Runtime, SdkExpect,
+    SdkUnwrap, IO,
 };
-use crate::runtime::{panic_utf8, Env, PromiseHandler, Runtime, SdkExpect, SdkUnwrap, IO};
 use crate::types::{
-    AccountId, PromiseAction, PromiseBatchAction, PromiseCreateArgs, PromiseResult,
-    PromiseWithCallbackArgs, Vec,
+    AccountId, Address, GasWeight, PromiseAction, PromiseBatchAction, PromiseCreateArgs,
+    PromiseResult, PromiseWithCallbackArgs, Vec,
 };
 
 mod error;
@@ -24,16 +33,53 @@ mod types;
 #[global_allocator]
 static ALLOCATOR: NoopAllocator = NoopAllocator;
 
-const MINIMUM_BALANCE: u128 = 310_000_000_000_000_000_000_000;
+// Covers the storage staking of the code of about 210 KB and the state.
+const MINIMUM_BALANCE: u128 = 2_300_000_000_000_000_000_000_000;
+// The deposit for the storage registration on the token, the excess is refunded.
+const STORAGE_BALANCE_BOUND: u128 = 1_250_000_000_000_000_000_000;
 const ZERO_YOCTO: u128 = 0;
+// The cost of the storage staking per byte.
+const STORAGE_PRICE_PER_BYTE: u128 = 10_000_000_000_000_000_000;
 
 const CALCULATE_FEES_GAS: u64 = 4_000_000_000_000;
 const NEAR_DEPOSIT_GAS: u64 = 2_000_000_000_000;
+const NEAR_WITHDRAW_GAS: u64 = 5_000_000_000_000;
+const DEPOSIT_NATIVE_CALLBACK_GAS: u64 = 10_000_000_000_000;
 const FT_BALANCE_GAS: u64 = 2_000_000_000_000;
 const FT_TRANSFER_GAS: u64 = 3_000_000_000_000;
 const FT_TRANSFER_CALL_GAS: u64 = 80_000_000_000_000;
-const CALCULATE_FEES_CALLBACK_GAS: u64 = 120_000_000_000_000;
-const FINISH_FORWARD_GAS: u64 = 100_000_000_000_000;
+const STORAGE_BALANCE_OF_GAS: u64 = 2_000_000_000_000;
+const STORAGE_DEPOSIT_GAS: u64 = 5_000_000_000_000;
+const RESOLVE_FORWARD_GAS: u64 = 5_000_000_000_000;
+const CHARGE_FEE_GAS: u64 = 5_000_000_000_000;
+const RELEASE_LOCK_GAS: u64 = 3_000_000_000_000;
+const NFT_TRANSFER_CALL_GAS: u64 = 30_000_000_000_000;
+const NFT_FORWARD_CALLBACK_GAS: u64 = 5_000_000_000_000;
+const NFT_TRANSFER_GAS: u64 = 10_000_000_000_000;
+const IS_NETWORK_SUPPORTED_GAS: u64 = 3_000_000_000_000;
+const SET_TARGET_NETWORK_GAS: u64 = 5_000_000_000_000;
+const SWEEP_CALLBACK_GAS: u64 = 10_000_000_000_000;
+const STORAGE_UNREGISTER_GAS: u64 = 5_000_000_000_000;
+const CLOSE_CALLBACK_GAS: u64 = 35_000_000_000_000;
+const CLOSE_FINISH_GAS: u64 = 10_000_000_000_000;
+const UNTRACK_TOKEN_CALLBACK_GAS: u64 = 5_000_000_000_000;
+const STORAGE_WITHDRAW_GAS: u64 = 5_000_000_000_000;
+const UNREGISTER_STORAGE_CALLBACK_GAS: u64 = 30_000_000_000_000;
+// The max length of the arguments of the `nft_on_transfer`.
+const MAX_NFT_ARGS_LEN: usize = 1024;
+// The max length of the arguments of the `ft_on_transfer`, which the sender is read from.
+const MAX_FT_ARGS_LEN: usize = 1024;
+// The gas of the execution of the `forward_batch` itself.
+const FORWARD_BATCH_GAS: u64 = 5_000_000_000_000;
+/// The weight of the calls of the forwarding chain in the distribution of the unused prepaid
+/// gas, so the gas of the config is the minimum and the deeper calls get the rest of it.
+const FORWARD_GAS_WEIGHT: GasWeight = GasWeight(1);
+// The gas of the downstream calls if it hasn't been configured by the owner.
+const DEFAULT_GAS_CONFIG: GasConfig = GasConfig {
+    calculate_fees: CALCULATE_FEES_GAS,
+    ft_transfer: FT_TRANSFER_GAS,
+    ft_transfer_call: FT_TRANSFER_CALL_GAS,
+};
 
 // Key is used for upgrading the smart contract.
 // base58 representation of the key is: "ed25519:BaiF3VUJf5pxB9ezVtzH4SejpdYc7EA3SqrKczsj1wno";
@@ -43,6 +89,16 @@ const UPDATER_PK: [u8; 33] = [
 ];
 // In case we get near as a token id it means we need to transfer native NEAR tokens.
 const NEAR: &str = "near";
+/// The method of the target network, which deposits the attached NEAR to the address as
+/// the base token.
+const NATIVE_DEPOSIT_METHOD: &str = "deposit_near";
+// The JSON serialized amount of the tokens returned from the `ft_on_transfer`, which
+// means that all the transferred tokens have been used.
+const NO_UNUSED_TOKENS: &[u8] = b"\"0\"";
+// The JSON serialized results of the `nft_on_transfer`, which mean whether the NFT should be
+// returned to the sender.
+const RETURN_NFT: &[u8] = b"true";
+const KEEP_NFT: &[u8] = b"false";
 
 #[no_mangle]
 pub extern "C" fn new() {
@@ -54,6 +110,7 @@ pub extern "C" fn new() {
 
     let state: State = io.read_input_borsh().sdk_unwrap();
     state.save(&mut io);
+    params::track_token(&mut io, &state.wnear_contract_id);
 
     let current_account_id = io.current_account_id();
     let promise = PromiseBatchAction {
@@ -68,61 +125,406 @@ pub extern "C" fn new() {
     io.promise_return(promise_id);
 }
 
+/// Converts the state to the layout of the current version of the contract. It's called by
+/// the owner right after the new code is deployed and does nothing if the state is up to date.
+#[no_mangle]
+pub extern "C" fn migrate() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN MIGRATE");
+    }
+
+    if State::migrate(&mut io) {
+        let event = params::event_json("migrate", &[("version", env!("CARGO_PKG_VERSION"))])
+            .sdk_expect("TOO LONG EVENT");
+        log_utf8(event.as_bytes());
+    }
+}
+
+/// Forwards the balance of the token or the requested amount of it. The one yocto isn't
+/// required, so the relayers could submit the forwarding by the delegate action (NEP-366)
+/// on behalf of the keeper or the user without NEAR for the gas. The contract doesn't keep
+/// the nonces of the delegated senders: the replay of the delegate action is rejected by
+/// the runtime, and the forwarding which is submitted twice only forwards the balance
+/// left by the first one. The borsh serialized `ForwardArgs` is expected.
 #[no_mangle]
 pub extern "C" fn forward() {
-    let io = Runtime;
+    let mut io = Runtime;
+    assert_not_paused(&io);
+    assert_allowed_caller(&io);
 
-    io.assert_one_yocto().sdk_unwrap();
+    let near_fee = io.attached_deposit_above_optional_one_yocto();
+    assert_near_fee_allowed(&io, near_fee);
 
-    let params: ForwardParams = io.read_input_borsh().sdk_unwrap();
+    let args: ForwardArgs = io.read_input_borsh().sdk_unwrap();
 
-    if params.token_id.as_str() == NEAR {
-        forward_native_token(io);
+    if args.token_id.as_str() == NEAR {
+        forward_native_token(io, args.amount);
     } else {
-        forward_nep141_token(io, params.token_id);
+        lock_forward(&mut io, &args.token_id);
+        let params = ForwardParams {
+            token_id: args.token_id,
+            near_fee,
+            amount: args.amount,
+            sender_id: None,
+            memo: args.memo,
+        };
+        let promise_id = forward_nep141_token(&mut io, &params);
+        io.promise_return(promise_id);
     }
 }
 
+/// Forwards the whole balance of the scheduled token if the interval of its schedule has
+/// elapsed since the last triggered forwarding and does nothing otherwise, so the keepers
+/// could call it as often as they like. The borsh serialized token id is expected.
 #[no_mangle]
-pub extern "C" fn calculate_fees_callback() {
+pub extern "C" fn trigger_scheduled() {
     let mut io = Runtime;
-    io.assert_private_call().sdk_unwrap();
+    assert_not_paused(&io);
+    assert_allowed_caller(&io);
 
-    let params: ForwardParams = io.read_input_borsh().sdk_unwrap();
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+    let mut schedule = ForwardSchedule::load(&io, &token_id).sdk_expect("NO SCHEDULE");
+    let now = io.block_timestamp();
+
+    if !schedule.is_due(now) {
+        return;
+    }
+
+    schedule.last_triggered_at = now;
+    ForwardSchedule::save(Some(&schedule), &mut io, &token_id);
+
+    if token_id.as_str() == NEAR {
+        forward_native_token(io, None);
+    } else {
+        lock_forward(&mut io, &token_id);
+        let params = ForwardParams {
+            token_id,
+            near_fee: ZERO_YOCTO,
+            amount: None,
+            sender_id: None,
+            memo: None,
+        };
+        let promise_id = forward_nep141_token(&mut io, &params);
+        io.promise_return(promise_id);
+    }
+}
+
+/// Forwards the whole balances of the several NEP-141 tokens in one transaction. Each
+/// forwarding is independent, so the failure of one of them doesn't affect the others.
+/// The attached gas should cover the forwarding of every token, which could be reduced
+/// by the gas config to fit more tokens into the transaction.
+#[no_mangle]
+pub extern "C" fn forward_batch() {
+    let mut io = Runtime;
+    assert_not_paused(&io);
+    assert_allowed_caller(&io);
+
+    if io.attached_deposit_above_optional_one_yocto() > 0 {
+        panic_utf8(b"NEAR FEE IS NOT SUPPORTED IN BATCH");
+    }
+
+    let params: AccountList = io.read_input_borsh().sdk_unwrap();
+
+    if params.0.iter().any(|token_id| token_id.as_str() == NEAR) {
+        panic_utf8(b"NATIVE NEAR IS NOT SUPPORTED IN BATCH");
+    }
+
+    if params.has_duplicates() {
+        panic_utf8(b"DUPLICATE TOKEN IN BATCH");
+    }
+
+    let forward_gas = FT_BALANCE_GAS + gas_config(&io).calculate_fees_callback();
+    let required_gas = u64::try_from(params.0.len())
+        .ok()
+        .and_then(|len| forward_gas.checked_mul(len))
+        .and_then(|gas| gas.checked_add(FORWARD_BATCH_GAS))
+        .sdk_expect("TOO MANY TOKENS");
+
+    if io.prepaid_gas().saturating_sub(io.used_gas()) < required_gas {
+        panic_utf8(b"NOT ENOUGH GAS FOR BATCH");
+    }
+
+    for token_id in params.0 {
+        lock_forward(&mut io, &token_id);
+        let params = ForwardParams {
+            token_id,
+            near_fee: ZERO_YOCTO,
+            amount: None,
+            sender_id: None,
+            memo: None,
+        };
+        let _ = forward_nep141_token(&mut io, &params);
+    }
+}
+
+/// Forwards the whole balances of the tracked tokens, which are registered at the creation
+/// of the forwarder and by the owner, so the keepers need only one call. The tokens
+/// with the zero balance and the ones which couldn't be locked for the forwarding are skipped.
+/// The tokens which don't fit into the attached gas go first in the next call.
+#[no_mangle]
+pub extern "C" fn forward_all() {
+    let mut io = Runtime;
+    assert_not_paused(&io);
+    assert_allowed_caller(&io);
+
+    if io.attached_deposit_above_optional_one_yocto() > 0 {
+        panic_utf8(b"NEAR FEE IS NOT SUPPORTED IN BATCH");
+    }
+
+    let len = params::tracked_tokens_len(&io);
+    let cursor = params::forward_all_cursor(&io);
+    let required_gas =
+        FT_BALANCE_GAS + gas_config(&io).calculate_fees_callback() + FORWARD_BATCH_GAS;
+
+    for offset in 0..len {
+        let idx = params::forward_all_index(cursor, offset, len);
+
+        if io.prepaid_gas().saturating_sub(io.used_gas()) < required_gas {
+            if offset == 0 {
+                panic_utf8(b"NOT ENOUGH GAS FOR BATCH");
+            }
+
+            params::set_forward_all_cursor(&mut io, idx);
+            return;
+        }
+
+        let Some(token_id) = params::tracked_token(&io, idx) else {
+            continue;
+        };
+
+        if try_lock_forward(&mut io, &token_id).is_ok() {
+            let params = ForwardParams {
+                token_id,
+                near_fee: ZERO_YOCTO,
+                amount: None,
+                sender_id: None,
+                memo: None,
+            };
+            let _ = forward_nep141_token(&mut io, &params);
+        }
+    }
+}
+
+/// Returns the borsh serialized page of the tracked tokens. The borsh serialized index of
+/// the first token of the page is expected.
+#[no_mangle]
+pub extern "C" fn get_tracked_tokens() {
+    let mut io = Runtime;
+    let from_idx: u32 = io.read_input_borsh().sdk_unwrap();
+    let tokens = params::tracked_tokens(&io, from_idx);
+
+    io.return_output(&types::to_borsh(&tokens).sdk_unwrap());
+}
+
+/// The receiver of the `ft_transfer_call` of the NEP-141 token. Forwards the whole balance
+/// of the token in the same receipt chain, so there is no need to call the `forward`.
+/// The forwarding isn't returned, so the transferred tokens are kept even if it fails
+/// and could be forwarded later by the `forward`. The tokens are kept without forwarding
+/// while the forwarder is paused, the forwarding of the token is in progress or its rate
+/// limit has been reached. The sender is attributed in the event of the forwarding and
+/// the memo of the transfer is passed through it.
+/// The tokens withdrawn from the target network are paid out to the withdraw beneficiary
+/// instead if it's set. The blacklisted tokens are refused, so they are refunded to
+/// the sender.
+#[no_mangle]
+pub extern "C" fn ft_on_transfer() {
+    let mut io = Runtime;
+    let token_id = io.predecessor_account_id();
+
+    if AccountList::load_blacklist(&io).contains(&token_id) {
+        panic_utf8(b"TOKEN IS BLACKLISTED");
+    }
+
+    let mut buf = [0; MAX_FT_ARGS_LEN];
+    let args = params::read_input_str(&io, &mut buf).ok();
+    let sender_id = args
+        .and_then(|args| params::json_string_field(args, "sender_id"))
+        .and_then(|sender_id| AccountId::new(sender_id).ok());
+    let memo = args
+        .and_then(|args| params::json_string_field(args, "memo"))
+        .and_then(Memo::new);
     let state = State::load(&io).sdk_expect("No state");
-    let amount: u128 = match io.promise_result(0).sdk_expect("No promise result") {
-        PromiseResult::Successful(v) => params::vec_to_number(&v).sdk_unwrap(),
-        _ => panic_utf8(b"FEE RESULT IS NOT READY"),
+
+    if sender_id == Some(state.target_network) {
+        if let Some(beneficiary_id) = params::withdraw_beneficiary(&io) {
+            let amount = args
+                .and_then(|args| params::json_string_field(args, "amount"))
+                .and_then(|amount| amount.parse().ok())
+                .sdk_expect("INVALID AMOUNT");
+
+            if !params::is_paused(&io) {
+                let _ = pay_out_withdrawal(&mut io, token_id, amount, beneficiary_id);
+            }
+
+            io.return_output(NO_UNUSED_TOKENS);
+            return;
+        }
+    }
+
+    let params = ForwardParams {
+        token_id,
+        near_fee: ZERO_YOCTO,
+        amount: None,
+        sender_id,
+        memo,
+    };
+
+    if !params::is_paused(&io) && try_lock_forward(&mut io, &params.token_id).is_ok() {
+        let _ = forward_nep141_token(&mut io, &params);
+    }
+
+    io.return_output(NO_UNUSED_TOKENS);
+}
+
+/// Wraps the NEAR held by the forwarder, including the attached deposit, into wNEAR and
+/// forwards it to the target address. The attached NEAR is kept without forwarding while
+/// the forwarder is paused. The NEAR reserved for the storage isn't forwarded.
+#[no_mangle]
+pub extern "C" fn forward_near() {
+    let mut io = Runtime;
+    assert_allowed_caller(&io);
+
+    if !params::is_paused(&io) {
+        forward_native_token(io, None);
+        return;
+    }
+
+    let amount = io.attached_deposit();
+    add_pending_native(&mut io, amount);
+}
+
+/// Transfers the NEAR above the required reserve, e.g. the storage refunds or the over-funding,
+/// to the owner. The reserve is the minimum balance of the forwarder or the cost of its
+/// storage if it's higher, and the native NEAR waiting to be forwarded. The NEAR isn't skimmed
+/// while its forwarding is in progress, since the failed deposit refunds it.
+#[no_mangle]
+pub extern "C" fn skim_excess_near() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+    let predecessor_id = io.predecessor_account_id();
+
+    if !state.is_owner(predecessor_id) && !AccountList::load_keepers(&io).contains(&predecessor_id)
+    {
+        panic_utf8(b"ONLY OWNER OR KEEPER CAN SKIM");
+    }
+
+    if params::is_forward_locked(&io, &state.wnear_contract_id, io.block_timestamp()) {
+        panic_utf8(b"FORWARD IS IN PROGRESS");
+    }
+
+    let native_id = native_token_id();
+    let reserve = reserved_balance(&io).saturating_add(params::pending_amount(&io, &native_id));
+    let amount = io.account_balance().saturating_sub(reserve);
+
+    if amount == 0 {
+        panic_utf8(b"NO EXCESS NEAR");
+    }
+
+    let event = params::event_json(
+        "skim_excess_near",
+        &[
+            ("amount", params::amount_to_str(amount).as_str()),
+            ("receiver_id", state.owner_id.as_str()),
+        ],
+    )
+    .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    let promise = PromiseBatchAction {
+        target_account_id: state.owner_id,
+        actions: [PromiseAction::Transfer { amount }],
     };
 
+    let promise_id = unsafe { io.promise_create_batch(&promise) };
+    io.promise_return(promise_id);
+}
+
+/// Sets the keepers, which are allowed to call the `forward`, the `forward_batch` and
+/// the `retry` besides the owner. The borsh serialized empty list makes them permissionless.
+#[no_mangle]
+pub extern "C" fn set_keepers() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET KEEPERS");
+    }
+
+    let keepers: AccountList = io.read_input_borsh().sdk_unwrap();
+
+    if keepers.has_duplicates() {
+        panic_utf8(b"DUPLICATE KEEPER");
+    }
+
+    keepers.save_keepers(&mut io).sdk_unwrap();
+}
+
+/// Returns the borsh serialized list of the keepers.
+#[no_mangle]
+pub extern "C" fn get_keepers() {
+    let mut io = Runtime;
+    let keepers = AccountList::load_keepers(&io);
+
+    io.return_output(&types::to_borsh(&keepers).sdk_unwrap());
+}
+
+/// Adds the token to the tracked tokens, whose balances are forwarded by the `forward_all`.
+/// The borsh serialized token id is expected.
+#[no_mangle]
+pub extern "C" fn track_token() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN TRACK TOKEN");
+    }
+
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+
+    if AccountList::load_blacklist(&io).contains(&token_id) {
+        panic_utf8(b"TOKEN IS BLACKLISTED");
+    }
+
+    let event = params::event_json("track_token", &[("token_id", token_id.as_str())])
+        .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    params::track_token(&mut io, &token_id);
+}
+
+/// Removes the token from the tracked tokens once the forwarder is checked to hold none of
+/// it, so the `close` doesn't miss the tokens left on the forwarder. The borsh serialized
+/// token id is expected.
+#[no_mangle]
+pub extern "C" fn untrack_token() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN UNTRACK TOKEN");
+    }
+
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
     let promise_id = unsafe {
         let promise_id = io.promise_create_call(&PromiseCreateArgs {
-            target_account_id: state.fees_contract_id,
-            method: "calculate_fees",
-            args: types::to_borsh(&FeesParams {
-                amount,
-                token_id: &params.token_id,
-                target_network: &state.target_network,
-                target_address: state.target_address,
-            })
-            .sdk_unwrap(),
+            target_account_id: token_id,
+            method: "ft_balance_of",
+            args: ft_balance_args(&io.current_account_id()),
             attached_balance: ZERO_YOCTO,
-            attached_gas: CALCULATE_FEES_GAS,
+            attached_gas: FT_BALANCE_GAS,
         });
 
         io.promise_attach_callback(
             promise_id,
             &PromiseCreateArgs {
                 target_account_id: io.current_account_id(),
-                method: "finish_forward_callback",
-                args: types::to_borsh(&FinishForwardParams {
-                    amount,
-                    token_id: params.token_id,
-                    promise_idx: 0,
-                })
-                .sdk_unwrap(),
-                attached_balance: 2,
-                attached_gas: FINISH_FORWARD_GAS,
+                method: "untrack_token_callback",
+                args: types::to_borsh(&token_id).sdk_unwrap(),
+                attached_balance: ZERO_YOCTO,
+                attached_gas: UNTRACK_TOKEN_CALLBACK_GAS,
             },
         )
     };
@@ -130,123 +532,170 @@ pub extern "C" fn calculate_fees_callback() {
     io.promise_return(promise_id);
 }
 
+/// Removes the token from the tracked tokens if the balance of the forwarder is zero.
 #[no_mangle]
-pub extern "C" fn finish_forward_callback() {
+pub extern "C" fn untrack_token_callback() {
     let mut io = Runtime;
     io.assert_private_call().sdk_unwrap();
 
-    let params: FinishForwardParams = io.read_input_borsh().sdk_unwrap();
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+
+    match balance_result(&io, 0) {
+        Some(0) => {}
+        Some(_) => panic_utf8(b"TOKEN BALANCE IS NOT ZERO"),
+        None => panic_utf8(b"BALANCE IS NOT READY"),
+    }
+
+    let event = params::event_json("untrack_token", &[("token_id", token_id.as_str())])
+        .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    params::untrack_token(&mut io, &token_id);
+}
+
+/// Adds the token to the blacklist, so it's refused by the `ft_on_transfer` and isn't
+/// forwarded, and stops tracking it. The borsh serialized token id is expected.
+#[no_mangle]
+pub extern "C" fn blacklist_token() {
+    let mut io = Runtime;
     let state = State::load(&io).sdk_expect("No state");
-    let fee: u128 = match io
-        .promise_result(params.promise_idx)
-        .sdk_expect("No promise result")
-    {
-        PromiseResult::Successful(v) => u128::try_from_slice(&v)
-            .map_err(|_| ContractError::BorshDeserializeError)
-            .sdk_unwrap(),
-        _ => panic_utf8(b"FEE RESULT IS NOT READY"),
-    };
 
-    let amount = params.amount.saturating_sub(fee);
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN BLACKLIST TOKEN");
+    }
 
-    let mut promise_id = unsafe {
-        io.promise_create_call(&PromiseCreateArgs {
-            target_account_id: params.token_id,
-            method: "ft_transfer_call",
-            args: ft_transfer_call_args(&state.target_network, amount, state.target_address),
-            attached_balance: 1,
-            attached_gas: FT_TRANSFER_CALL_GAS,
-        })
-    };
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+    let mut blacklist = AccountList::load_blacklist(&io);
 
-    if fee > 0 {
-        promise_id = unsafe {
-            io.promise_attach_callback(
-                promise_id,
-                &PromiseCreateArgs {
-                    target_account_id: params.token_id,
-                    method: "ft_transfer",
-                    args: ft_transfer_args(&state.fees_contract_id, fee),
-                    attached_balance: 1,
-                    attached_gas: FT_TRANSFER_GAS,
-                },
-            )
-        };
+    if blacklist.contains(&token_id) {
+        panic_utf8(b"TOKEN IS ALREADY BLACKLISTED");
     }
 
-    io.promise_return(promise_id);
+    if blacklist.0.try_push(token_id).is_err() {
+        panic_utf8(b"BLACKLIST IS FULL");
+    }
+
+    let event = params::event_json("blacklist_token", &[("token_id", token_id.as_str())])
+        .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    blacklist.save_blacklist(&mut io).sdk_unwrap();
+    params::untrack_token(&mut io, &token_id);
 }
 
-fn forward_native_token<I: IO + Env + PromiseHandler>(mut io: I) {
-    let amount = io
-        .account_balance()
-        .checked_sub(MINIMUM_BALANCE)
-        .filter(|a| *a > 0)
-        .expect("Too low balance");
+/// Removes the token from the blacklist. The borsh serialized token id is expected.
+#[no_mangle]
+pub extern "C" fn unblacklist_token() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
 
-    let state = State::load(&io).unwrap();
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN UNBLACKLIST TOKEN");
+    }
 
-    let promise_id = unsafe {
-        let promise_id = io.promise_create_and_combine(&[
-            PromiseCreateArgs {
-                target_account_id: state.wnear_contract_id,
-                method: "near_deposit",
-                args: Vec::new(),
-                attached_balance: amount,
-                attached_gas: NEAR_DEPOSIT_GAS,
-            },
-            PromiseCreateArgs {
-                target_account_id: state.fees_contract_id,
-                method: "calculate_fees",
-                args: types::to_borsh(&FeesParams {
-                    amount,
-                    token_id: &state.wnear_contract_id,
-                    target_network: &state.target_network,
-                    target_address: state.target_address,
-                })
-                .sdk_unwrap(),
-                attached_balance: ZERO_YOCTO,
-                attached_gas: CALCULATE_FEES_GAS,
-            },
-        ]);
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+    let mut blacklist = AccountList::load_blacklist(&io);
 
-        io.promise_attach_callback(
-            promise_id,
-            &PromiseCreateArgs {
-                target_account_id: io.current_account_id(),
-                method: "finish_forward_callback",
-                args: types::to_borsh(&FinishForwardParams {
-                    amount,
-                    token_id: state.wnear_contract_id,
-                    promise_idx: 1,
-                })
-                .sdk_unwrap(),
-                attached_balance: 2,
-                attached_gas: FINISH_FORWARD_GAS,
-            },
+    if !blacklist.contains(&token_id) {
+        panic_utf8(b"TOKEN IS NOT BLACKLISTED");
+    }
+
+    blacklist.0.retain(|account_id| *account_id != token_id);
+
+    let event = params::event_json("unblacklist_token", &[("token_id", token_id.as_str())])
+        .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    blacklist.save_blacklist(&mut io).sdk_unwrap();
+}
+
+/// Returns the borsh serialized list of the blacklisted tokens.
+#[no_mangle]
+pub extern "C" fn get_blacklist() {
+    let mut io = Runtime;
+    let blacklist = AccountList::load_blacklist(&io);
+
+    io.return_output(&types::to_borsh(&blacklist).sdk_unwrap());
+}
+
+/// Pauses the forwarding, e.g. while the target network is under maintenance. The tokens
+/// are still accepted and could be forwarded after the `unpause`.
+// The symbol isn't exported on the host, where it would override the `pause` of the libc.
+#[cfg_attr(target_arch = "wasm32", no_mangle)]
+pub extern "C" fn pause() {
+    set_paused(true);
+}
+
+/// Resumes the forwarding paused by the `pause`.
+#[no_mangle]
+pub extern "C" fn unpause() {
+    set_paused(false);
+}
+
+/// Returns the borsh serialized `true` if the forwarding is paused.
+#[no_mangle]
+pub extern "C" fn is_paused() {
+    let mut io = Runtime;
+    let paused = params::is_paused(&io);
+
+    io.return_output(&types::to_borsh(&paused).sdk_unwrap());
+}
+
+/// The receiver of the `nft_transfer_call` of the NEP-171 NFT. Transfers the NFT to the NFT
+/// bridge with the target address as the message or returns it to the sender with the event
+/// if the NFT bridge isn't configured. The NFT refused by the bridge is returned to the sender
+/// by the `nft_forward_callback`.
+#[no_mangle]
+pub extern "C" fn nft_on_transfer() {
+    let mut io = Runtime;
+    let mut buf = [0; MAX_NFT_ARGS_LEN];
+    let args = params::read_input_str(&io, &mut buf).sdk_unwrap();
+    let token_id = params::json_string_field(args, "token_id").sdk_expect("NO TOKEN ID");
+    let sender_id =
+        params::json_string_field(args, "previous_owner_id").sdk_expect("NO PREVIOUS OWNER");
+    let nft_contract_id = io.predecessor_account_id();
+
+    let Some(nft_bridge_id) = params::nft_bridge(&io) else {
+        let event = params::event_json(
+            "nft_returned",
+            &[
+                ("nft_contract_id", nft_contract_id.as_str()),
+                ("token_id", token_id),
+                ("sender_id", sender_id),
+            ],
         )
+        .sdk_expect("TOO LONG EVENT");
+        log_utf8(event.as_bytes());
+        io.return_output(RETURN_NFT);
+        return;
     };
 
-    io.promise_return(promise_id);
-}
+    let state = State::load(&io).sdk_expect("No state");
+    let transfer_args = nft_transfer_call_args(&nft_bridge_id, token_id, state.target_address)
+        .sdk_expect("TOO LONG TOKEN ID");
+    let callback_args = params::json_object(&[
+        ("nft_contract_id", Some(nft_contract_id.as_str())),
+        ("token_id", Some(token_id)),
+        ("sender_id", Some(sender_id)),
+    ])
+    .and_then(|json| Vec::try_from(json.as_bytes()).ok())
+    .sdk_expect("TOO LONG TOKEN ID");
 
-fn forward_nep141_token<I: IO + Env + PromiseHandler>(mut io: I, token_id: AccountId) {
-    let callback_args = types::to_borsh(&token_id).sdk_unwrap();
     let promise_id = unsafe {
         io.promise_create_with_callback(&PromiseWithCallbackArgs {
             base: PromiseCreateArgs {
-                target_account_id: token_id,
-                method: "ft_balance_of",
-                args: ft_balance_args(&io.current_account_id()),
-                attached_balance: ZERO_YOCTO,
-                attached_gas: FT_BALANCE_GAS,
+                target_account_id: nft_contract_id,
+                method: "nft_transfer_call",
+                args: transfer_args,
+                attached_balance: 1,
+                attached_gas: NFT_TRANSFER_CALL_GAS,
             },
             callback: PromiseCreateArgs {
                 target_account_id: io.current_account_id(),
-                method: "calculate_fees_callback",
+                method: "nft_forward_callback",
                 args: callback_args,
                 attached_balance: ZERO_YOCTO,
-                attached_gas: CALCULATE_FEES_CALLBACK_GAS,
+                attached_gas: NFT_FORWARD_CALLBACK_GAS,
             },
         })
     };
@@ -254,6 +703,2455 @@ fn forward_nep141_token<I: IO + Env + PromiseHandler>(mut io: I, token_id: Accou
     io.promise_return(promise_id);
 }
 
+/// Resolves the transfer of the NFT to the NFT bridge. The result is the result of
+/// the `nft_on_transfer`, so the NFT, which the bridge hasn't accepted, is returned to
+/// the sender by the NFT contract.
+#[no_mangle]
+pub extern "C" fn nft_forward_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let mut buf = [0; MAX_NFT_ARGS_LEN];
+    let args = params::read_input_str(&io, &mut buf).sdk_unwrap();
+    let field = |name| params::json_string_field(args, name).sdk_expect("INVALID NFT ARGS");
+    // The `nft_transfer_call` returns `true` if the receiver has kept the NFT.
+    let is_forwarded = matches!(
+        io.promise_result(0),
+        Some(PromiseResult::Successful(v)) if v.as_slice() == b"true"
+    );
+    let event = params::event_json(
+        if is_forwarded {
+            "nft_forwarded"
+        } else {
+            "nft_returned"
+        },
+        &[
+            ("nft_contract_id", field("nft_contract_id")),
+            ("token_id", field("token_id")),
+            ("sender_id", field("sender_id")),
+        ],
+    )
+    .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    io.return_output(if is_forwarded { KEEP_NFT } else { RETURN_NFT });
+}
+
+/// Transfers the NFT held by the forwarder, e.g. the one sent by the `nft_transfer` without
+/// the call, to the receiver. The JSON with the `nft_contract_id`, the `token_id` and
+/// the `receiver_id` is expected.
+#[no_mangle]
+pub extern "C" fn sweep_nft() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SWEEP NFT");
+    }
+
+    io.attached_deposit_above_one_yocto().sdk_unwrap();
+    let mut buf = [0; MAX_NFT_ARGS_LEN];
+    let args = params::read_input_str(&io, &mut buf).sdk_unwrap();
+    let account_field = |name| {
+        params::json_string_field(args, name)
+            .and_then(|account_id| AccountId::new(account_id).ok())
+            .sdk_expect("INVALID NFT ARGS")
+    };
+    let nft_contract_id = account_field("nft_contract_id");
+    let receiver_id = account_field("receiver_id");
+    let token_id = params::json_string_field(args, "token_id").sdk_expect("NO TOKEN ID");
+
+    let event = params::event_json(
+        "sweep_nft",
+        &[
+            ("nft_contract_id", nft_contract_id.as_str()),
+            ("token_id", token_id),
+            ("receiver_id", receiver_id.as_str()),
+        ],
+    )
+    .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    let promise_id = unsafe {
+        io.promise_create_call(&PromiseCreateArgs {
+            target_account_id: nft_contract_id,
+            method: "nft_transfer",
+            args: nft_transfer_args(&receiver_id, token_id).sdk_expect("TOO LONG TOKEN ID"),
+            attached_balance: 1,
+            attached_gas: NFT_TRANSFER_GAS,
+        })
+    };
+
+    io.promise_return(promise_id);
+}
+
+/// Transfers the whole balance of the token, e.g. the unsupported one sent by mistake,
+/// to the recovery account, which should be registered on the token.
+#[no_mangle]
+pub extern "C" fn sweep() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SWEEP");
+    }
+
+    io.attached_deposit_above_one_yocto().sdk_unwrap();
+    let params: SweepParams = io.read_input_borsh().sdk_unwrap();
+
+    let promise_id = unsafe {
+        io.promise_create_with_callback(&PromiseWithCallbackArgs {
+            base: PromiseCreateArgs {
+                target_account_id: params.token_id,
+                method: "ft_balance_of",
+                args: ft_balance_args(&io.current_account_id()),
+                attached_balance: ZERO_YOCTO,
+                attached_gas: FT_BALANCE_GAS,
+            },
+            callback: PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "sweep_callback",
+                args: types::to_borsh(&params).sdk_unwrap(),
+                attached_balance: ZERO_YOCTO,
+                attached_gas: SWEEP_CALLBACK_GAS,
+            },
+        })
+    };
+
+    io.promise_return(promise_id);
+}
+
+#[no_mangle]
+pub extern "C" fn sweep_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let params: SweepParams = io.read_input_borsh().sdk_unwrap();
+    let amount: u128 = match io.promise_result(0).sdk_expect("No promise result") {
+        PromiseResult::Successful(v) => params::vec_to_number(&v).sdk_unwrap(),
+        _ => panic_utf8(b"BALANCE IS NOT READY"),
+    };
+
+    if amount == 0 {
+        panic_utf8(b"NOTHING TO SWEEP");
+    }
+
+    let event = params::event_json(
+        "sweep",
+        &[
+            ("token_id", params.token_id.as_str()),
+            ("receiver_id", params.receiver_id.as_str()),
+            ("amount", params::amount_to_str(amount).as_str()),
+        ],
+    )
+    .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    let promise_id = unsafe {
+        io.promise_create_call(&PromiseCreateArgs {
+            target_account_id: params.token_id,
+            method: "ft_transfer",
+            args: ft_transfer_args(&params.receiver_id, amount, None),
+            attached_balance: 1,
+            attached_gas: FT_TRANSFER_GAS,
+        })
+    };
+
+    io.promise_return(promise_id);
+}
+
+/// Sets the NFT bridge, which the received NFTs are transferred to. The borsh serialized
+/// `None` removes the bridge.
+#[no_mangle]
+pub extern "C" fn set_nft_bridge() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET NFT BRIDGE");
+    }
+
+    let nft_bridge_id: Option<AccountId> = io.read_input_borsh().sdk_unwrap();
+    params::set_nft_bridge(&mut io, nft_bridge_id.as_ref());
+}
+
+/// Sets the target address, which the tokens are forwarded to. The borsh serialized string
+/// of the hex of the address is expected. The mixed-case hex should have the valid EIP-55
+/// checksum.
+#[no_mangle]
+pub extern "C" fn set_target_address() {
+    let io = Runtime;
+    io.attached_deposit_above_one_yocto().sdk_unwrap();
+
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET TARGET ADDRESS");
+    }
+
+    let target: TargetAddress = io.read_input_borsh().sdk_unwrap();
+    update_target_address(io, state, &target);
+}
+
+/// Sets the target address authorized by the signature of the EIP-191 personal message by
+/// the current target address, so the holder of its key could redirect the forwarder without
+/// the NEAR account. The message contains the nonce, which is incremented by every change.
+#[no_mangle]
+pub extern "C" fn set_target_address_with_proof() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+    let params: TargetAddressProofParams = io.read_input_borsh().sdk_unwrap();
+    let nonce = params::address_nonce(&io);
+    let message =
+        params::address_proof_message(&io.current_account_id(), params.target.address, nonce)
+            .sdk_expect("TOO LONG MESSAGE");
+    let signer = ecrecover(&keccak256(message.as_bytes()), &params.signature);
+
+    if signer != Some(state.target_address) {
+        panic_utf8(b"INVALID ADDRESS PROOF");
+    }
+
+    params::set_address_nonce(&mut io, nonce.saturating_add(1));
+    update_target_address(io, state, &params.target);
+}
+
+/// Returns the borsh serialized nonce of the next change of the target address by
+/// the `set_target_address_with_proof`.
+#[no_mangle]
+pub extern "C" fn get_target_address_nonce() {
+    let mut io = Runtime;
+    let nonce = params::address_nonce(&io);
+
+    io.return_output(&types::to_borsh(&nonce).sdk_unwrap());
+}
+
+/// Validates the new target address and stores it with the event.
+fn update_target_address<I: IO>(mut io: I, mut state: State, target: &TargetAddress) {
+    let hash = keccak256(target.lowercase_hex().as_bytes());
+
+    if target.address.0 == [0; 20] || !target.is_checksum_valid(&hash) {
+        panic_utf8(b"INVALID TARGET ADDRESS");
+    }
+
+    let target_address = target.address;
+
+    let event = params::event_json(
+        "set_target_address",
+        &[
+            (
+                "old_address",
+                params::address_to_hex(state.target_address).as_str(),
+            ),
+            (
+                "new_address",
+                params::address_to_hex(target_address).as_str(),
+            ),
+        ],
+    )
+    .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    state.target_address = target_address;
+    state.save(&mut io);
+}
+
+/// Sets the NEAR account, which the tokens are transferred to by the `ft_transfer` instead of
+/// the target address on the target network. The borsh serialized `None` restores the
+/// forwarding to the target address.
+#[no_mangle]
+pub extern "C" fn set_near_receiver() {
+    let mut io = Runtime;
+    io.attached_deposit_above_one_yocto().sdk_unwrap();
+
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET NEAR RECEIVER");
+    }
+
+    let receiver_id: Option<AccountId> = io.read_input_borsh().sdk_unwrap();
+    let event = params::event_json(
+        "set_near_receiver",
+        &[(
+            "receiver_id",
+            receiver_id.as_ref().map_or("", AccountId::as_str),
+        )],
+    )
+    .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    params::set_near_receiver(&mut io, receiver_id.as_ref());
+}
+
+/// Returns the borsh serialized NEAR account, which the tokens are transferred to instead of
+/// the target address.
+#[no_mangle]
+pub extern "C" fn get_near_receiver() {
+    let mut io = Runtime;
+    let receiver_id = params::near_receiver(&io);
+
+    io.return_output(&types::to_borsh(&receiver_id).sdk_unwrap());
+}
+
+/// Sets the format of the `msg` of the `ft_transfer_call` to the target network, since
+/// the silos and the versions of the engine could expect the different formats.
+#[no_mangle]
+pub extern "C" fn set_msg_format() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET MSG FORMAT");
+    }
+
+    let params: MsgFormatParams = io.read_input_borsh().sdk_unwrap();
+    params::set_msg_format(&mut io, &params.target_network, params.format);
+    assert_xcc_payload_fits(&io, &state, XccPayload::load(&io).as_ref());
+}
+
+/// Returns the borsh serialized format of the `msg` for the target network.
+#[no_mangle]
+pub extern "C" fn get_msg_format() {
+    let mut io = Runtime;
+    let target_network: AccountId = io.read_input_borsh().sdk_unwrap();
+    let format = params::msg_format(&io, &target_network);
+
+    io.return_output(&types::to_borsh(&format).sdk_unwrap());
+}
+
+/// Sets the EVM calldata, which the engine calls the target address with after the deposit,
+/// e.g. to stake or swap the deposited tokens on arrival. It isn't passed to the deposits of
+/// ETH by the eth-connector, the wNEAR unwrapped as the base token and the splits to the other
+/// addresses. The borsh serialized `None` restores the plain deposits.
+#[no_mangle]
+pub extern "C" fn set_xcc_payload() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET XCC PAYLOAD");
+    }
+
+    let payload: Option<XccPayload> = io.read_input_borsh().sdk_unwrap();
+    assert_xcc_payload_fits(&io, &state, payload.as_ref());
+
+    XccPayload::save(payload.as_ref(), &mut io);
+}
+
+/// Returns the borsh serialized EVM calldata of the deposits.
+#[no_mangle]
+pub extern "C" fn get_xcc_payload() {
+    let mut io = Runtime;
+    let payload = XccPayload::load(&io);
+
+    io.return_output(&types::to_borsh(&payload).sdk_unwrap());
+}
+
+/// Sets the NEAR account, which the tokens withdrawn from the target network to
+/// the forwarder are paid out to, so the address of the forwarder could be used for
+/// the withdrawals. The borsh serialized `None` keeps the withdrawn tokens in the forwarder.
+#[no_mangle]
+pub extern "C" fn set_withdraw_beneficiary() {
+    let mut io = Runtime;
+    io.attached_deposit_above_one_yocto().sdk_unwrap();
+
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET WITHDRAW BENEFICIARY");
+    }
+
+    let beneficiary_id: Option<AccountId> = io.read_input_borsh().sdk_unwrap();
+    let event = params::event_json(
+        "set_withdraw_beneficiary",
+        &[(
+            "beneficiary_id",
+            beneficiary_id.as_ref().map_or("", AccountId::as_str),
+        )],
+    )
+    .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    params::set_withdraw_beneficiary(&mut io, beneficiary_id.as_ref());
+}
+
+/// Returns the borsh serialized NEAR account, which the withdrawn tokens are paid out to.
+#[no_mangle]
+pub extern "C" fn get_withdraw_beneficiary() {
+    let mut io = Runtime;
+    let beneficiary_id = params::withdraw_beneficiary(&io);
+
+    io.return_output(&types::to_borsh(&beneficiary_id).sdk_unwrap());
+}
+
+/// Pays out the tokens withdrawn from the target network by the `ft_transfer`, e.g. by
+/// the exit precompile of the engine, to the withdraw beneficiary. The withdrawals by
+/// the `ft_transfer_call` are paid out by the `ft_on_transfer` without this call.
+#[no_mangle]
+pub extern "C" fn withdraw() {
+    let mut io = Runtime;
+    assert_allowed_caller(&io);
+    assert_not_paused(&io);
+
+    let params: WithdrawParams = io.read_input_borsh().sdk_unwrap();
+    let beneficiary_id = params::withdraw_beneficiary(&io).sdk_expect("NO WITHDRAW BENEFICIARY");
+    let promise_id = pay_out_withdrawal(&mut io, params.token_id, params.amount, beneficiary_id);
+
+    io.promise_return(promise_id);
+}
+
+/// Sets whether the target network uses NEAR as its base token, so the forwarded wNEAR is
+/// unwrapped and deposited to the target address as the base token instead of the ERC-20.
+/// The NEAR refunded by the failed deposit is kept in the forwarder until the `forward_near`,
+/// so the fee of the forwarding is charged only for the deposited part and the refunded part
+/// is charged when it's forwarded again.
+#[no_mangle]
+pub extern "C" fn set_near_base_token() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET NEAR BASE TOKEN");
+    }
+
+    let params: NearBaseTokenParams = io.read_input_borsh().sdk_unwrap();
+    params::set_near_base_token(&mut io, &params.target_network, params.is_enabled);
+}
+
+/// Returns the borsh serialized `true` if the target network uses NEAR as its base token.
+#[no_mangle]
+pub extern "C" fn is_near_base_token() {
+    let mut io = Runtime;
+    let target_network: AccountId = io.read_input_borsh().sdk_unwrap();
+    let is_enabled = params::is_near_base_token(&io, &target_network);
+
+    io.return_output(&types::to_borsh(&is_enabled).sdk_unwrap());
+}
+
+/// Sets the lock contract of the bridge to Ethereum, which the tokens are transferred to by
+/// the `ft_transfer_call` with the target address as the recipient on Ethereum instead of
+/// the target network. The borsh serialized `None` restores the forwarding to the target
+/// network. The NEAR receiver takes precedence over the bridge.
+#[no_mangle]
+pub extern "C" fn set_eth_bridge() {
+    let mut io = Runtime;
+    io.attached_deposit_above_one_yocto().sdk_unwrap();
+
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET ETH BRIDGE");
+    }
+
+    let bridge_id: Option<AccountId> = io.read_input_borsh().sdk_unwrap();
+    let event = params::event_json(
+        "set_eth_bridge",
+        &[(
+            "bridge_id",
+            bridge_id.as_ref().map_or("", AccountId::as_str),
+        )],
+    )
+    .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    params::set_eth_bridge(&mut io, bridge_id.as_ref());
+    assert_xcc_payload_fits(&io, &state, XccPayload::load(&io).as_ref());
+}
+
+/// Returns the borsh serialized lock contract of the bridge to Ethereum.
+#[no_mangle]
+pub extern "C" fn get_eth_bridge() {
+    let mut io = Runtime;
+    let bridge_id = params::eth_bridge(&io);
+
+    io.return_output(&types::to_borsh(&bridge_id).sdk_unwrap());
+}
+
+/// Sets the target network, which the tokens are forwarded to. The network should be
+/// supported by the fees contract. The borsh serialized account id of the network is expected.
+#[no_mangle]
+pub extern "C" fn set_target_network() {
+    let mut io = Runtime;
+    io.attached_deposit_above_one_yocto().sdk_unwrap();
+
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET TARGET NETWORK");
+    }
+
+    let target_network: AccountId = io.read_input_borsh().sdk_unwrap();
+    let promise_id = unsafe {
+        io.promise_create_with_callback(&PromiseWithCallbackArgs {
+            base: PromiseCreateArgs {
+                target_account_id: state.fees_contract_id,
+                method: "is_network_supported",
+                args: is_network_supported_args(&target_network),
+                attached_balance: ZERO_YOCTO,
+                attached_gas: IS_NETWORK_SUPPORTED_GAS,
+            },
+            callback: PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "set_target_network_callback",
+                args: types::to_borsh(&target_network).sdk_unwrap(),
+                attached_balance: ZERO_YOCTO,
+                attached_gas: SET_TARGET_NETWORK_GAS,
+            },
+        })
+    };
+
+    io.promise_return(promise_id);
+}
+
+#[no_mangle]
+pub extern "C" fn set_target_network_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let target_network: AccountId = io.read_input_borsh().sdk_unwrap();
+    let is_supported = match io.promise_result(0).sdk_expect("No promise result") {
+        PromiseResult::Successful(v) => v.as_slice() == b"true",
+        _ => false,
+    };
+
+    if !is_supported {
+        panic_utf8(b"UNSUPPORTED TARGET NETWORK");
+    }
+
+    let mut state = State::load(&io).sdk_expect("No state");
+    let event = params::event_json(
+        "set_target_network",
+        &[
+            ("old_network", state.target_network.as_str()),
+            ("new_network", target_network.as_str()),
+        ],
+    )
+    .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    state.target_network = target_network;
+    assert_xcc_payload_fits(&io, &state, XccPayload::load(&io).as_ref());
+    state.save(&mut io);
+}
+
+/// Returns the JSON of the configuration of the forwarder and the version of the contract.
+#[no_mangle]
+pub extern "C" fn get_params() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    io.return_output(state.to_json(env!("CARGO_PKG_VERSION")).as_bytes());
+}
+
+/// Sets the minimum amount of the token, below which the forwarding is refused, because
+/// it costs more than the value moved. The threshold of the wNEAR applies to the native NEAR.
+/// Zero removes the threshold.
+#[no_mangle]
+pub extern "C" fn set_min_forward_amount() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET MIN FORWARD AMOUNT");
+    }
+
+    let params: MinForwardAmountParams = io.read_input_borsh().sdk_unwrap();
+    params::set_min_forward_amount(&mut io, &params.token_id, params.amount);
+}
+
+/// Sets the schedule of the recurring forwarding of the token triggered by
+/// the `trigger_scheduled`. Zero interval removes the schedule.
+#[no_mangle]
+pub extern "C" fn set_schedule() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET SCHEDULE");
+    }
+
+    let params: ScheduleParams = io.read_input_borsh().sdk_unwrap();
+    let schedule = (params.interval_hours > 0).then(|| ForwardSchedule {
+        interval_hours: params.interval_hours,
+        last_triggered_at: ForwardSchedule::load(&io, &params.token_id)
+            .map_or(0, |schedule| schedule.last_triggered_at),
+    });
+
+    ForwardSchedule::save(schedule.as_ref(), &mut io, &params.token_id);
+}
+
+/// Returns the borsh serialized schedule of the token.
+#[no_mangle]
+pub extern "C" fn get_schedule() {
+    let mut io = Runtime;
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+    let schedule = ForwardSchedule::load(&io, &token_id);
+
+    io.return_output(&types::to_borsh(&schedule).sdk_unwrap());
+}
+
+/// Sets the maximum amount of the token, which could be forwarded at once, since the target
+/// network could limit the amount of a deposit. The larger balance is forwarded in chunks by
+/// the several forwardings. The limit of the wNEAR applies to the native NEAR. Zero removes
+/// the limit.
+#[no_mangle]
+pub extern "C" fn set_max_forward_amount() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET MAX FORWARD AMOUNT");
+    }
+
+    let params: MaxForwardAmountParams = io.read_input_borsh().sdk_unwrap();
+    params::set_max_forward_amount(&mut io, &params.token_id, params.amount);
+}
+
+/// Returns the borsh serialized maximum amount of the token, which could be forwarded at once.
+#[no_mangle]
+pub extern "C" fn get_max_forward_amount() {
+    let mut io = Runtime;
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+    let amount = params::max_forward_amount(&io, &token_id);
+
+    io.return_output(&types::to_borsh(&amount).sdk_unwrap());
+}
+
+/// Returns the borsh serialized minimum amount of the token, which could be forwarded.
+#[no_mangle]
+pub extern "C" fn get_min_forward_amount() {
+    let mut io = Runtime;
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+    let amount = params::min_forward_amount(&io, &token_id);
+
+    io.return_output(&types::to_borsh(&amount).sdk_unwrap());
+}
+
+/// Sets the fixed fee in basis points, which is charged instead of the fee calculated by
+/// the fees contract, so the forwarding skips the call of the fees contract. The borsh
+/// serialized `None` restores the fees calculated by the fees contract.
+#[no_mangle]
+pub extern "C" fn set_static_fee() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET STATIC FEE");
+    }
+
+    let fee: Option<StaticFee> = io.read_input_borsh().sdk_unwrap();
+
+    if fee.is_some_and(|fee| !fee.is_valid()) {
+        panic_utf8(b"FEE IS ABOVE 100%");
+    }
+
+    StaticFee::save(fee.as_ref(), &mut io);
+}
+
+/// Sets the limit of the forwardings of every token per the time window. The borsh
+/// serialized `None` removes the limit.
+#[no_mangle]
+pub extern "C" fn set_rate_limit() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET RATE LIMIT");
+    }
+
+    let limit: Option<RateLimit> = io.read_input_borsh().sdk_unwrap();
+
+    if limit.is_some_and(|limit| !limit.is_valid()) {
+        panic_utf8(b"INVALID RATE LIMIT");
+    }
+
+    RateLimit::save(limit.as_ref(), &mut io);
+}
+
+/// Returns the borsh serialized rate limit.
+#[no_mangle]
+pub extern "C" fn get_rate_limit() {
+    let mut io = Runtime;
+    let limit = RateLimit::load(&io);
+
+    io.return_output(&types::to_borsh(&limit).sdk_unwrap());
+}
+
+/// Returns the borsh serialized static fee.
+#[no_mangle]
+pub extern "C" fn get_static_fee() {
+    let mut io = Runtime;
+    let fee = StaticFee::load(&io);
+
+    io.return_output(&types::to_borsh(&fee).sdk_unwrap());
+}
+
+/// Sets the fees contract, which calculates the fee if the call of the fees contract of
+/// the forwarder fails. The borsh serialized `None` removes it.
+#[no_mangle]
+pub extern "C" fn set_fallback_fees_contract() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET FALLBACK FEES CONTRACT");
+    }
+
+    let fees_contract_id: Option<AccountId> = io.read_input_borsh().sdk_unwrap();
+    params::set_fallback_fees_contract(&mut io, fees_contract_id.as_ref());
+}
+
+/// Returns the borsh serialized fallback fees contract.
+#[no_mangle]
+pub extern "C" fn get_fallback_fees_contract() {
+    let mut io = Runtime;
+    let fees_contract_id = params::fallback_fees_contract(&io);
+
+    io.return_output(&types::to_borsh(&fees_contract_id).sdk_unwrap());
+}
+
+/// Sets the fee in basis points, which is charged if neither the fees contract nor
+/// the fallback fees contract has calculated the fee. The borsh serialized `None` aborts
+/// the forwarding in this case.
+#[no_mangle]
+pub extern "C" fn set_fallback_fee() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET FALLBACK FEE");
+    }
+
+    let fee: Option<StaticFee> = io.read_input_borsh().sdk_unwrap();
+
+    if fee.is_some_and(|fee| !fee.is_valid()) {
+        panic_utf8(b"FEE IS ABOVE 100%");
+    }
+
+    StaticFee::save_fallback(fee.as_ref(), &mut io);
+}
+
+/// Returns the borsh serialized fallback fee.
+#[no_mangle]
+pub extern "C" fn get_fallback_fee() {
+    let mut io = Runtime;
+    let fee = StaticFee::load_fallback(&io);
+
+    io.return_output(&types::to_borsh(&fee).sdk_unwrap());
+}
+
+/// Sets the number of the blocks the fee percent reported by the fees contract is cached
+/// for, so the forwardings within them don't wait for the fees contract. Only the fees,
+/// which are the pure percentage of the amount, are cached. Zero disables the cache.
+#[no_mangle]
+pub extern "C" fn set_fee_cache_ttl() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET FEE CACHE TTL");
+    }
+
+    let ttl: u64 = io.read_input_borsh().sdk_unwrap();
+    params::set_fee_cache_ttl(&mut io, ttl);
+}
+
+/// Returns the borsh serialized number of the blocks the fee percent is cached for.
+#[no_mangle]
+pub extern "C" fn get_fee_cache_ttl() {
+    let mut io = Runtime;
+    let ttl = params::fee_cache_ttl(&io);
+
+    io.return_output(&types::to_borsh(&ttl).sdk_unwrap());
+}
+
+/// Sets the gas of the calls of the fees contract and the token within the bounds of
+/// the `GasConfig`.
+#[no_mangle]
+pub extern "C" fn set_gas_config() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET GAS CONFIG");
+    }
+
+    let config: GasConfig = io.read_input_borsh().sdk_unwrap();
+
+    if !config.is_valid() {
+        panic_utf8(b"GAS IS OUT OF BOUNDS");
+    }
+
+    config.save(&mut io);
+}
+
+/// Returns the borsh serialized gas of the calls of the fees contract and the token.
+#[no_mangle]
+pub extern "C" fn get_gas_config() {
+    let mut io = Runtime;
+    let config = gas_config(&io);
+
+    io.return_output(&types::to_borsh(&config).sdk_unwrap());
+}
+
+/// Sets the addresses the forwarded tokens are split between with their weights in basis
+/// points, which should sum up to 100%. The empty splits restore the forwarding to the target
+/// address.
+#[no_mangle]
+pub extern "C" fn set_splits() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN SET SPLITS");
+    }
+
+    let splits: Splits = io.read_input_borsh().sdk_unwrap();
+
+    if !splits.is_valid() {
+        panic_utf8(b"INVALID SPLITS");
+    }
+
+    splits.save(&mut io);
+}
+
+/// Returns the borsh serialized splits of the forwarded tokens.
+#[no_mangle]
+pub extern "C" fn get_splits() {
+    let mut io = Runtime;
+    let splits = Splits::load(&io);
+
+    io.return_output(&types::to_borsh(&splits).sdk_unwrap());
+}
+
+#[no_mangle]
+pub extern "C" fn calculate_fees_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let params: ForwardParams = io.read_input_borsh().sdk_unwrap();
+    let state = State::load(&io).sdk_expect("No state");
+    let gas = gas_config(&io);
+    let Some(balance) = balance_result(&io, 0) else {
+        abort_forward(&mut io, &state, &params.token_id, "balance_unavailable");
+        return;
+    };
+    let Some(amount) = params::forward_amount(balance, params.amount) else {
+        abort_forward(&mut io, &state, &params.token_id, "invalid_forward_amount");
+        return;
+    };
+    let amount = params::capped_amount(amount, params::max_forward_amount(&io, &params.token_id));
+
+    // Nothing to forward, e.g. the tracked token without the balance forwarded by
+    // the `forward_all`, so the lock is released right away.
+    if amount == 0 {
+        params::set_forward_lock(&mut io, &params.token_id, None);
+        return;
+    }
+
+    if amount < params::min_forward_amount(&io, &params.token_id) {
+        abort_forward(&mut io, &state, &params.token_id, "below_min_amount");
+        return;
+    }
+
+    log_forward_event(
+        "forward_started",
+        state.target_address,
+        &destination_account(&io, &state),
+        &params.token_id,
+        &[
+            ("amount", params::amount_to_str(amount).as_str()),
+            (
+                "sender_id",
+                params.sender_id.as_ref().map_or("", AccountId::as_str),
+            ),
+            ("memo", params.memo.as_ref().map_or("", Memo::as_str)),
+        ],
+    );
+
+    let finish_params = FinishForwardParams {
+        amount,
+        token_id: params.token_id,
+        promise_idx: 0,
+        cache_fee: params.near_fee == 0,
+        memo: params.memo,
+        is_native: false,
+        is_fallback: false,
+    };
+
+    // The static fee or the cached fee percent is applied in the same receipt without waiting
+    // for the fees contract. The fee paid by the attached NEAR requires the call anyway.
+    let local_fees = StaticFee::load(&io)
+        .map(|static_fee| static_fee.fees_result(amount))
+        .or_else(|| cached_fees(&mut io, &state, &params, amount));
+
+    if let Some(fees) = local_fees {
+        finish_forward(io, &state, &finish_params, &fees);
+        return;
+    }
+
+    let promise_id = unsafe {
+        let promise_id = io.promise_create_call(&PromiseCreateArgs {
+            target_account_id: state.fees_contract_id,
+            method: "record_and_calculate_fees",
+            args: types::to_borsh(&FeesParams {
+                amount,
+                token_id: &params.token_id,
+                target_network: &state.target_network,
+                target_address: state.target_address,
+            })
+            .sdk_unwrap(),
+            attached_balance: params.near_fee,
+            attached_gas: gas.calculate_fees,
+        });
+
+        io.promise_attach_callback_weighted(
+            promise_id,
+            &PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "finish_forward_callback",
+                args: types::to_borsh(&finish_params).sdk_unwrap(),
+                attached_balance: 2,
+                attached_gas: gas.finish_forward(),
+            },
+            FORWARD_GAS_WEIGHT,
+        )
+    };
+
+    io.promise_return(promise_id);
+}
+
+/// Returns the fee of the amount by the cached fee percent of the token if the cache hasn't
+/// expired. The forwarding is still recorded by the fees contract for the statistics, but
+/// the forwarding doesn't wait for it.
+fn cached_fees<I: IO + Env + PromiseHandler>(
+    io: &mut I,
+    state: &State,
+    params: &ForwardParams,
+    amount: u128,
+) -> Option<FeesResult> {
+    if params.near_fee > 0 {
+        return None;
+    }
+
+    let fees = FeeCache::load(io, &params.token_id)?.fees_result(
+        amount,
+        io.block_height(),
+        params::fee_cache_ttl(io),
+    )?;
+
+    let _ = unsafe {
+        io.promise_create_call(&PromiseCreateArgs {
+            target_account_id: state.fees_contract_id,
+            method: "record_and_calculate_fees",
+            args: types::to_borsh(&FeesParams {
+                amount,
+                token_id: &params.token_id,
+                target_network: &state.target_network,
+                target_address: state.target_address,
+            })
+            .sdk_unwrap(),
+            attached_balance: ZERO_YOCTO,
+            attached_gas: gas_config(io).calculate_fees,
+        })
+    };
+
+    Some(fees)
+}
+
+#[no_mangle]
+pub extern "C" fn finish_forward_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let params: FinishForwardParams = io.read_input_borsh().sdk_unwrap();
+    let state = State::load(&io).sdk_expect("No state");
+    let static_fee = StaticFee::load(&io);
+    let calculated = match static_fee {
+        Some(_) => None,
+        None => match io
+            .promise_result(params.promise_idx)
+            .sdk_expect("No promise result")
+        {
+            PromiseResult::Successful(v) => FeesResult::from_slice(&v).ok(),
+            _ => None,
+        },
+    };
+
+    // The failed fees contract is replaced by the fallback one and then by the fallback fee,
+    // so the forwarding isn't aborted.
+    if static_fee.is_none()
+        && calculated.is_none()
+        && !params.is_fallback
+        && request_fallback_fees(&mut io, &state, &params)
+    {
+        return;
+    }
+
+    // The fee percent reported by the fees contract refreshes the cache. The fees, which
+    // aren't the pure percentage of the amount, aren't reported, so they're never cached.
+    if let Some((percent, fees)) = calculated
+        .as_ref()
+        .filter(|fees| params.cache_fee && !fees.rejected && !fees.invalid_address)
+        .filter(|_| params::fee_cache_ttl(&io) > 0)
+        .and_then(|fees| fees.percent.map(|percent| (percent, fees)))
+    {
+        FeeCache {
+            percent,
+            fee_recipient: fees.fee_recipient,
+            block_height: io.block_height(),
+        }
+        .save(&mut io, &params.token_id);
+    }
+
+    let fees = match (static_fee, calculated) {
+        (Some(static_fee), _) => static_fee.fees_result(params.amount),
+        (None, Some(fees)) => fees,
+        (None, None) => {
+            let Some(fee) = StaticFee::load_fallback(&io) else {
+                abort_forward(&mut io, &state, &params.token_id, "fees_unavailable");
+                return;
+            };
+            fee.fees_result(params.amount)
+        }
+    };
+
+    finish_forward(io, &state, &params, &fees);
+}
+
+/// Requests the fee from the fallback fees contract if there is enough gas for another
+/// attempt. The NEAR fee isn't attached, since it's refunded by the failed fees contract.
+/// Returns `false` if the fallback fees contract isn't set.
+fn request_fallback_fees<I: IO + Env + PromiseHandler>(
+    io: &mut I,
+    state: &State,
+    params: &FinishForwardParams,
+) -> bool {
+    let Some(fees_contract_id) = params::fallback_fees_contract(io) else {
+        return false;
+    };
+    let gas = gas_config(io);
+
+    if io.prepaid_gas().saturating_sub(io.used_gas()) < gas.calculate_fees + gas.finish_forward() {
+        return false;
+    }
+
+    let (method, args) = if params.is_native {
+        (
+            "record_and_calculate_native_fees",
+            types::to_borsh(&NativeFeesParams {
+                amount: params.amount,
+                target_address: state.target_address,
+            }),
+        )
+    } else {
+        (
+            "record_and_calculate_fees",
+            types::to_borsh(&FeesParams {
+                amount: params.amount,
+                token_id: &params.token_id,
+                target_network: &state.target_network,
+                target_address: state.target_address,
+            }),
+        )
+    };
+    let promise_id = unsafe {
+        let promise_id = io.promise_create_call(&PromiseCreateArgs {
+            target_account_id: fees_contract_id,
+            method,
+            args: args.sdk_unwrap(),
+            attached_balance: ZERO_YOCTO,
+            attached_gas: gas.calculate_fees,
+        });
+
+        io.promise_attach_callback_weighted(
+            promise_id,
+            &PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "finish_forward_callback",
+                args: types::to_borsh(&FinishForwardParams {
+                    promise_idx: 0,
+                    is_fallback: true,
+                    ..*params
+                })
+                .sdk_unwrap(),
+                attached_balance: 2,
+                attached_gas: gas.finish_forward(),
+            },
+            FORWARD_GAS_WEIGHT,
+        )
+    };
+
+    io.promise_return(promise_id);
+    true
+}
+
+/// Checks the storage registration of the receivers and the balance of the token before
+/// the transfer of the net amount and the fee.
+fn finish_forward<I: IO + Env + PromiseHandler>(
+    mut io: I,
+    state: &State,
+    params: &FinishForwardParams,
+    fees: &FeesResult,
+) {
+    if fees.invalid_address {
+        abort_forward(&mut io, state, &params.token_id, "invalid_target_address");
+        return;
+    }
+
+    if fees.rejected {
+        abort_forward(&mut io, state, &params.token_id, "unsupported");
+        return;
+    }
+
+    let fee = fees.fee.min(params.amount);
+    let amount = params.amount.saturating_sub(fee);
+
+    // The receivers are checked for the storage registration on the token before the transfers
+    // and the balance is checked for the tokens, which deduct a transfer tax or rebase.
+    let gas = gas_config(&io);
+    let mut checks = token_receivers(destination_account(&io, state), fee, fees.fee_recipient)
+        .iter()
+        .map(|account_id| PromiseCreateArgs {
+            target_account_id: params.token_id,
+            method: "storage_balance_of",
+            args: ft_balance_args(account_id),
+            attached_balance: ZERO_YOCTO,
+            attached_gas: STORAGE_BALANCE_OF_GAS,
+        })
+        .collect::<ArrayVec<_, 3>>();
+    checks.push(PromiseCreateArgs {
+        target_account_id: params.token_id,
+        method: "ft_balance_of",
+        args: ft_balance_args(&io.current_account_id()),
+        attached_balance: ZERO_YOCTO,
+        attached_gas: FT_BALANCE_GAS,
+    });
+
+    let promise_id = unsafe {
+        let promise_id = io.promise_create_and_combine(&checks);
+
+        io.promise_attach_callback_weighted(
+            promise_id,
+            &PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "transfer_tokens_callback",
+                args: types::to_borsh(&TransferParams {
+                    amount,
+                    fee,
+                    fee_recipient: fees.fee_recipient,
+                    token_id: params.token_id,
+                    memo: params.memo,
+                })
+                .sdk_unwrap(),
+                attached_balance: ZERO_YOCTO,
+                attached_gas: gas.transfer_tokens(),
+            },
+            FORWARD_GAS_WEIGHT,
+        )
+    };
+
+    io.promise_return(promise_id);
+}
+
+/// Registers the target network and the fee recipient on the token if the results of their
+/// `storage_balance_of` are `null`, then transfers the net amount. The fee is transferred
+/// after the transfers to the target network have been resolved. If the actual balance of
+/// the token is lower than their sum, both are reduced proportionally. The lock of
+/// the forwarding is released at the end of the chain, after the transfer of the fee.
+#[no_mangle]
+pub extern "C" fn transfer_tokens_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let TransferParams {
+        amount,
+        fee,
+        fee_recipient,
+        token_id,
+        memo,
+    } = io.read_input_borsh().sdk_unwrap();
+    let state = State::load(&io).sdk_expect("No state");
+    let gas = gas_config(&io);
+
+    let near_receiver = params::near_receiver(&io);
+    let eth_bridge = params::eth_bridge(&io);
+    let receivers = token_receivers(destination_account(&io, &state), fee, fee_recipient);
+    let balance_idx = u64::try_from(receivers.len())
+        .ok()
+        .sdk_expect("TOO MANY RECEIVERS");
+    let Some(balance) = balance_result(&io, balance_idx) else {
+        abort_forward(&mut io, &state, &token_id, "balance_unavailable");
+        return;
+    };
+    let (amount, fee) = params::verified_amounts(balance, amount, fee);
+
+    let deposits = storage_deposits(&io, token_id, &receivers);
+
+    // The splits are ignored if the tokens are transferred to the NEAR account.
+    let splits = near_receiver.map_or_else(|| Splits::load(&io), |_| Splits::default());
+    let destinations = splits.destinations(state.target_address, amount);
+    let msg_format = transfer_msg_format(&io, &state, token_id, eth_bridge.is_some());
+    let transfer_receiver = eth_bridge.unwrap_or(state.target_network);
+    // The engine calls the target address with the payload after the deposit. The splits
+    // to the other addresses are deposited as is.
+    let payload = eth_bridge
+        .is_none()
+        .then(|| XccPayload::load(&io))
+        .flatten();
+    let splits_count = u64::try_from(destinations.len())
+        .ok()
+        .sdk_expect("TOO MANY SPLITS");
+    let transfer_gas = gas.ft_transfer_call / splits_count;
+    let promise_id =
+        (!deposits.is_empty()).then(|| unsafe { io.promise_create_and_combine(&deposits) });
+    // The wNEAR is unwrapped and deposited as the base token if the target network uses NEAR
+    // as its base token and the tokens are transferred to it.
+    let is_unwrapped = near_receiver.is_none()
+        && eth_bridge.is_none()
+        && token_id == state.wnear_contract_id
+        && params::is_near_base_token(&io, &state.target_network);
+
+    let mut promise_id = if is_unwrapped {
+        // The NEAR is attached to the deposits only after the wNEAR has been unwrapped.
+        let deposit_gas =
+            DEPOSIT_NATIVE_CALLBACK_GAS + gas.ft_transfer_call + RESOLVE_FORWARD_GAS * splits_count;
+        unwrap_near(&mut io, promise_id, token_id, amount, memo, deposit_gas)
+    } else {
+        chain_transfers(
+            &mut io,
+            promise_id,
+            token_id,
+            &destinations,
+            false,
+            memo,
+            |target_address, amount| {
+                near_receiver.map_or_else(
+                    || PromiseCreateArgs {
+                        target_account_id: token_id,
+                        method: "ft_transfer_call",
+                        args: ft_transfer_call_args(
+                            &transfer_receiver,
+                            amount,
+                            target_address,
+                            fee,
+                            msg_format,
+                            memo.as_ref(),
+                            payload
+                                .as_ref()
+                                .filter(|_| target_address == state.target_address),
+                        )
+                        .sdk_expect("TOO LONG TRANSFER ARGS"),
+                        attached_balance: 1,
+                        attached_gas: transfer_gas,
+                    },
+                    |receiver_id| PromiseCreateArgs {
+                        target_account_id: token_id,
+                        method: "ft_transfer",
+                        args: ft_transfer_args(&receiver_id, amount, memo.as_ref()),
+                        attached_balance: 1,
+                        attached_gas: gas.ft_transfer,
+                    },
+                )
+            },
+        )
+    };
+
+    // The fee is charged pro rata to the amount accepted by the target network.
+    if fee > 0 {
+        let charge = ChargeFeeParams {
+            amount,
+            fee,
+            fee_recipient,
+            token_id,
+            memo,
+        };
+        promise_id = attach_charge_fee(&mut io, promise_id, &charge, gas.ft_transfer);
+    }
+
+    let promise_id = attach_release_lock(&mut io, promise_id, token_id);
+    io.promise_return(promise_id);
+}
+
+/// Deposits the unwrapped NEAR to the target addresses as the base token of the target
+/// network, each deposit followed by its resolve callback. If the wNEAR hasn't been unwrapped,
+/// the forwarding is recorded as failed, so it's re-attempted by the `retry`.
+#[no_mangle]
+pub extern "C" fn deposit_native_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let (amount, memo): (u128, Option<Memo>) = io.read_input_borsh().sdk_unwrap();
+    let state = State::load(&io).sdk_expect("No state");
+    let token_id = state.wnear_contract_id;
+
+    if !matches!(io.promise_result(0), Some(PromiseResult::Successful(_))) {
+        let reason = FailureReason::TransferFailed;
+        log_forward_event(
+            "forward_failed",
+            state.target_address,
+            &state.target_network,
+            &token_id,
+            &[
+                ("amount", params::amount_to_str(amount).as_str()),
+                ("refunded_amount", params::amount_to_str(amount).as_str()),
+                ("reason", reason.as_str()),
+                ("memo", memo.as_ref().map_or("", Memo::as_str)),
+            ],
+        );
+
+        let mut retries = RetryQueue::load(&io, &token_id);
+        retries.push(RetryEntry {
+            amount,
+            reason,
+            timestamp: io.block_timestamp(),
+        });
+        retries.save(&mut io, &token_id);
+        params::set_pending_amount(&mut io, &token_id, amount);
+        params::set_native_refund_amount(&mut io, &token_id, 0);
+        return;
+    }
+
+    let destinations = Splits::load(&io).destinations(state.target_address, amount);
+    let deposit_gas = gas_config(&io).ft_transfer_call
+        / u64::try_from(destinations.len())
+            .ok()
+            .sdk_expect("TOO MANY SPLITS");
+    let promise_id = chain_transfers(
+        &mut io,
+        None,
+        token_id,
+        &destinations,
+        true,
+        memo,
+        |target_address, amount| PromiseCreateArgs {
+            target_account_id: state.target_network,
+            method: NATIVE_DEPOSIT_METHOD,
+            args: types::to_borsh(&target_address).sdk_unwrap(),
+            attached_balance: amount,
+            attached_gas: deposit_gas,
+        },
+    );
+
+    io.promise_return(promise_id);
+}
+
+/// Chains the transfers to the destinations after the promise, each one followed by its
+/// resolve callback.
+fn chain_transfers<I: IO + Env + PromiseHandler>(
+    io: &mut I,
+    mut promise_id: Option<PromiseId>,
+    token_id: AccountId,
+    destinations: &[(Address, u128)],
+    is_unwrapped: bool,
+    memo: Option<Memo>,
+    transfer: impl Fn(Address, u128) -> PromiseCreateArgs,
+) -> PromiseId {
+    for (idx, &(target_address, amount)) in destinations.iter().enumerate() {
+        let transfer = transfer(target_address, amount);
+        let resolve = PromiseCreateArgs {
+            target_account_id: io.current_account_id(),
+            method: "resolve_forward_callback",
+            args: types::to_borsh(&ResolveForwardParams {
+                amount,
+                token_id,
+                target_address,
+                is_first: idx == 0,
+                is_unwrapped,
+                memo,
+            })
+            .sdk_unwrap(),
+            attached_balance: ZERO_YOCTO,
+            attached_gas: RESOLVE_FORWARD_GAS,
+        };
+
+        promise_id = Some(unsafe {
+            let transfer_id = match promise_id {
+                Some(promise_id) => {
+                    io.promise_attach_callback_weighted(promise_id, &transfer, FORWARD_GAS_WEIGHT)
+                }
+                None => io.promise_create_call_weighted(&transfer, FORWARD_GAS_WEIGHT),
+            };
+            io.promise_attach_callback(transfer_id, &resolve)
+        });
+    }
+
+    promise_id.sdk_expect("NO DESTINATIONS")
+}
+
+/// Transfers the fee to the fee recipient pro rata to the amount used by the target network.
+/// The refunded remainder stays pending for the `retry`, or for the `forward_near` if
+/// the deposit of the unwrapped NEAR failed, without its part of the fee, which is charged
+/// when the remainder is forwarded again.
+#[no_mangle]
+pub extern "C" fn charge_fee_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let params: ChargeFeeParams = io.read_input_borsh().sdk_unwrap();
+    let used_amount = params
+        .amount
+        .saturating_sub(params::pending_amount(&io, &params.token_id))
+        .saturating_sub(params::native_refund_amount(&io, &params.token_id));
+    let fee = params::charged_fee(params.amount, used_amount, params.fee);
+
+    if fee == 0 {
+        return;
+    }
+
+    let state = State::load(&io).sdk_expect("No state");
+    log_forward_event(
+        "fee_charged",
+        state.target_address,
+        &destination_account(&io, &state),
+        &params.token_id,
+        &[
+            ("amount", params::amount_to_str(used_amount).as_str()),
+            ("fee", params::amount_to_str(fee).as_str()),
+            ("fee_recipient", params.fee_recipient.as_str()),
+            ("memo", params.memo.as_ref().map_or("", Memo::as_str)),
+        ],
+    );
+
+    let mut forward_stats = ForwardStats::load(&io, &params.token_id);
+    forward_stats.record(0, fee, io.block_timestamp(), false);
+    forward_stats.save(&mut io, &params.token_id);
+
+    let promise_id = unsafe {
+        io.promise_create_call(&PromiseCreateArgs {
+            target_account_id: params.token_id,
+            method: "ft_transfer",
+            args: ft_transfer_args(&params.fee_recipient, fee, params.memo.as_ref()),
+            attached_balance: 1,
+            attached_gas: gas_config(&io).ft_transfer,
+        })
+    };
+
+    io.promise_return(promise_id);
+}
+
+/// Records the amount of the tokens, which haven't been accepted by the target network, so
+/// they are tracked as pending until the next forwarding and the failure is added to the
+/// retry queue of the token. The forwarding of the whole balance includes them, so the
+/// successful forwarding clears the records. The callback follows every split, so the first
+/// one resets the pending amount and the next ones add to it. The retry queue is cleared
+/// only by the successful first split, so the failed re-forwarding keeps the earlier entries.
+#[no_mangle]
+pub extern "C" fn resolve_forward_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let params: ResolveForwardParams = io.read_input_borsh().sdk_unwrap();
+    let state = State::load(&io).sdk_expect("No state");
+    let (used_amount, reason) = match io.promise_result(0).sdk_expect("No promise result") {
+        // The deposit of the base token doesn't refund a part of the NEAR.
+        PromiseResult::Successful(_) if params.is_unwrapped => {
+            (params.amount, FailureReason::Refunded)
+        }
+        PromiseResult::Successful(v) => {
+            let used_amount = params::vec_to_number(&v).unwrap_or(params.amount);
+            let reason = if used_amount > 0 {
+                FailureReason::PartiallyRefunded
+            } else {
+                FailureReason::Refunded
+            };
+            (used_amount, reason)
+        }
+        _ => (0, FailureReason::TransferFailed),
+    };
+    let refunded_amount = params.amount.saturating_sub(used_amount);
+    // The failed deposit of the base token refunds the native NEAR, which isn't retried as
+    // the wNEAR and is forwarded by the `forward_near` instead.
+    let queued_amount = if params.is_unwrapped {
+        0
+    } else {
+        refunded_amount
+    };
+    let mut retries = RetryQueue::load(&io, &params.token_id);
+    // The splits of the same forwarding add up their refunds.
+    let pending_amount = if params.is_first {
+        0
+    } else {
+        params::pending_amount(&io, &params.token_id)
+    };
+
+    if refunded_amount == 0 {
+        log_forward_event(
+            "forward_succeeded",
+            params.target_address,
+            &destination_account(&io, &state),
+            &params.token_id,
+            &[
+                ("amount", params::amount_to_str(params.amount).as_str()),
+                ("memo", params.memo.as_ref().map_or("", Memo::as_str)),
+            ],
+        );
+
+        if params.is_first {
+            retries = RetryQueue::default();
+        }
+    } else if matches!(reason, FailureReason::PartiallyRefunded) {
+        // The refunded remainder is queued and re-forwarded by the `retry`.
+        log_forward_event(
+            "forward_partially_refunded",
+            params.target_address,
+            &destination_account(&io, &state),
+            &params.token_id,
+            &[
+                ("amount", params::amount_to_str(params.amount).as_str()),
+                ("used_amount", params::amount_to_str(used_amount).as_str()),
+                (
+                    "refunded_amount",
+                    params::amount_to_str(refunded_amount).as_str(),
+                ),
+                ("memo", params.memo.as_ref().map_or("", Memo::as_str)),
+            ],
+        );
+    } else {
+        log_forward_event(
+            "forward_failed",
+            params.target_address,
+            &destination_account(&io, &state),
+            &params.token_id,
+            &[
+                ("amount", params::amount_to_str(params.amount).as_str()),
+                (
+                    "refunded_amount",
+                    params::amount_to_str(refunded_amount).as_str(),
+                ),
+                ("reason", reason.as_str()),
+                ("memo", params.memo.as_ref().map_or("", Memo::as_str)),
+            ],
+        );
+    }
+
+    if queued_amount > 0 {
+        retries.push(RetryEntry {
+            amount: queued_amount,
+            reason,
+            timestamp: io.block_timestamp(),
+        });
+    }
+
+    record_native_refund(&mut io, &params, refunded_amount);
+
+    let mut forward_stats = ForwardStats::load(&io, &params.token_id);
+    forward_stats.record(used_amount, 0, io.block_timestamp(), params.is_first);
+    forward_stats.save(&mut io, &params.token_id);
+
+    retries.save(&mut io, &params.token_id);
+    params::set_pending_amount(
+        &mut io,
+        &params.token_id,
+        pending_amount.saturating_add(queued_amount),
+    );
+}
+
+/// Adds the native NEAR refunded by the failed deposit of the unwrapped NEAR to the pending
+/// native NEAR, which waits for the `forward_near`, and records it for the forwarding, so
+/// the `charge_fee_callback` doesn't charge the fee of it. The first split resets the record.
+fn record_native_refund<I: IO>(io: &mut I, params: &ResolveForwardParams, refunded_amount: u128) {
+    let native_refunded_amount = if params.is_unwrapped {
+        add_pending_native(io, refunded_amount);
+        refunded_amount
+    } else {
+        0
+    };
+    let native_refund_amount = if params.is_first {
+        0
+    } else {
+        params::native_refund_amount(io, &params.token_id)
+    };
+
+    params::set_native_refund_amount(
+        io,
+        &params.token_id,
+        native_refund_amount.saturating_add(native_refunded_amount),
+    );
+}
+
+/// Releases the lock of the forwarding of the token as the last callback of the chain,
+/// whatever the results of the transfers are.
+#[no_mangle]
+pub extern "C" fn release_lock_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+    params::set_forward_lock(&mut io, &token_id, None);
+}
+
+/// Re-attempts the failed forwardings of the token by forwarding the amount refunded by
+/// the target network, including the unused remainders of the partially refunded ones, or
+/// the whole balance if nothing is pending. The queue is kept until the re-forwarding
+/// succeeds, so the re-forwarding which fails or is aborted could be retried again.
+#[no_mangle]
+pub extern "C" fn retry() {
+    let mut io = Runtime;
+    assert_not_paused(&io);
+    assert_allowed_caller(&io);
+
+    let near_fee = io.attached_deposit_above_optional_one_yocto();
+    assert_near_fee_allowed(&io, near_fee);
+
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+
+    if RetryQueue::load(&io, &token_id).is_empty() {
+        panic_utf8(b"NO FAILED FORWARDS");
+    }
+
+    lock_forward(&mut io, &token_id);
+
+    let pending_amount = params::pending_amount(&io, &token_id);
+    let params = ForwardParams {
+        token_id,
+        near_fee,
+        amount: (pending_amount > 0).then_some(pending_amount),
+        sender_id: None,
+        memo: None,
+    };
+    let promise_id = forward_nep141_token(&mut io, &params);
+    io.promise_return(promise_id);
+}
+
+/// Returns the borsh serialized retry queue of the token for the keepers.
+#[no_mangle]
+pub extern "C" fn get_retries() {
+    let mut io = Runtime;
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+    let retries = RetryQueue::load(&io, &token_id);
+
+    io.return_output(&types::to_borsh(&retries).sdk_unwrap());
+}
+
+/// Previews the forwarding of the amount of the token or the native NEAR without moving
+/// the tokens or recording the fee. It's a view, so only the local state is read: the fee
+/// and the net amount are returned if the static fee is set and are `null` otherwise, so
+/// the fee is quoted by the `quote_fees` or the `calculate_fees` views of the fees contract.
+/// Returns the JSON with the fee, the net amount, the destination, the fees contract and
+/// the reason why the forwarding would be currently rejected or `null`.
+#[no_mangle]
+pub extern "C" fn estimate_forward() {
+    let mut io = Runtime;
+    let params: EstimateParams = io.read_input_borsh().sdk_unwrap();
+    let state = State::load(&io).sdk_expect("No state");
+    let fee = StaticFee::load(&io)
+        .map(|static_fee| static_fee.fees_result(params.amount).fee.min(params.amount));
+    let result = estimate_result(&io, &state, &params, fee);
+
+    io.return_output(result.as_bytes());
+}
+
+/// Builds the JSON of the estimation of the forwarding with the static fee or `None`
+/// if the fee is quoted by the fees contract.
+fn estimate_result<I: IO + Env>(
+    io: &I,
+    state: &State,
+    params: &EstimateParams,
+    fee: Option<u128>,
+) -> ArrayString<512> {
+    // The native NEAR is forwarded as wNEAR.
+    let token_id = if params.token_id.as_str() == NEAR {
+        state.wnear_contract_id
+    } else {
+        params.token_id
+    };
+    let rejection = if params::is_paused(io) {
+        Some("paused")
+    } else if params.amount == 0 || params.amount < params::min_forward_amount(io, &token_id) {
+        Some("below_min_amount")
+    } else if params::capped_amount(params.amount, params::max_forward_amount(io, &token_id))
+        < params.amount
+    {
+        Some("above_max_amount")
+    } else if params::is_forward_locked(io, &token_id, io.block_timestamp()) {
+        Some("in_progress")
+    } else {
+        None
+    };
+    let fee_str = fee.map(params::amount_to_str);
+    let net_amount_str = fee.map(|fee| params::amount_to_str(params.amount.saturating_sub(fee)));
+
+    params::json_object(&[
+        ("token_id", Some(params.token_id.as_str())),
+        (
+            "amount",
+            Some(params::amount_to_str(params.amount).as_str()),
+        ),
+        ("fee", fee_str.as_ref().map(ArrayString::as_str)),
+        (
+            "net_amount",
+            net_amount_str.as_ref().map(ArrayString::as_str),
+        ),
+        ("destination", Some(destination_account(io, state).as_str())),
+        (
+            "target_address",
+            Some(params::address_to_hex(state.target_address).as_str()),
+        ),
+        ("fees_contract_id", Some(state.fees_contract_id.as_str())),
+        ("rejection", rejection),
+    ])
+    .sdk_expect("TOO LONG ESTIMATE")
+}
+
+/// Returns the borsh serialized cumulative statistics of the forwardings of the token.
+#[no_mangle]
+pub extern "C" fn get_stats() {
+    let mut io = Runtime;
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+    let stats = ForwardStats::load(&io, &token_id);
+
+    io.return_output(&types::to_borsh(&stats).sdk_unwrap());
+}
+
+/// Returns the borsh serialized amount of the token, which has been refunded by the target
+/// network and is waiting to be forwarded again.
+/// The `near` returns the native NEAR waiting to be forwarded.
+#[no_mangle]
+pub extern "C" fn get_pending_amount() {
+    let mut io = Runtime;
+    let token_id: AccountId = io.read_input_borsh().sdk_unwrap();
+    let amount = params::pending_amount(&io, &token_id);
+
+    io.return_output(&types::to_borsh(&amount).sdk_unwrap());
+}
+
+fn set_paused(paused: bool) {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN PAUSE");
+    }
+
+    params::set_paused(&mut io, paused);
+
+    let event = params::event_json(if paused { "paused" } else { "unpaused" }, &[])
+        .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+}
+
+/// Checks that the caller is the owner or a keeper if the keepers are set. The predecessor
+/// of the delegate action (NEP-366) is its sender rather than the relayer, which signs only
+/// the outer transaction, so the relayer can't act as the keeper. The replay protection of
+/// the delegate actions is out of the scope of the check: the runtime rejects the delegate
+/// action with the used nonce of the access key of the sender or after its max block height.
+fn assert_allowed_caller<I: IO + Env>(io: &I) {
+    let keepers = AccountList::load_keepers(io);
+
+    if keepers.0.is_empty() {
+        return;
+    }
+
+    let predecessor_id = io.predecessor_account_id();
+    let state = State::load(io).sdk_expect("No state");
+
+    if !state.is_owner(predecessor_id) && !keepers.contains(&predecessor_id) {
+        panic_utf8(b"ONLY OWNER OR KEEPER CAN FORWARD");
+    }
+}
+
+/// Locks the forwarding of the token until its last callback, so the concurrent forwarding
+/// doesn't read the same balance, and counts it in the window of the rate limit. Returns
+/// the error if the token is blacklisted, the forwarding is already in progress or the rate
+/// limit has been reached.
+fn try_lock_forward<I: IO + Env>(io: &mut I, token_id: &AccountId) -> Result<(), &'static [u8]> {
+    if AccountList::load_blacklist(io).contains(token_id) {
+        return Err(b"TOKEN IS BLACKLISTED");
+    }
+
+    let now = io.block_timestamp();
+
+    if params::is_forward_locked(io, token_id, now) {
+        return Err(b"FORWARD IS IN PROGRESS");
+    }
+
+    if let Some(limit) = RateLimit::load(io) {
+        limit
+            .admit(RateWindow::load(io, token_id), now)
+            .ok_or(b"FORWARD RATE LIMIT EXCEEDED".as_slice())?
+            .save(io, token_id);
+    }
+
+    params::set_forward_lock(io, token_id, Some(now));
+    Ok(())
+}
+
+fn lock_forward<I: IO + Env>(io: &mut I, token_id: &AccountId) {
+    if let Err(e) = try_lock_forward(io, token_id) {
+        panic_utf8(e);
+    }
+}
+
+/// Checks that the arguments of the largest deposit with the EVM calldata and without the memo
+/// fit into the buffer, so the forwarding doesn't fail in the middle of the chain. It's checked
+/// on every change of the calldata, the target network, its format of the `msg` and the bridge.
+fn assert_xcc_payload_fits<I: IO>(io: &I, state: &State, payload: Option<&XccPayload>) {
+    // The calldata isn't passed to the bridge to Ethereum.
+    if payload.is_none() || params::eth_bridge(io).is_some() {
+        return;
+    }
+
+    if ft_transfer_call_args(
+        &state.target_network,
+        u128::MAX,
+        state.target_address,
+        u128::MAX,
+        params::msg_format(io, &state.target_network),
+        None,
+        payload,
+    )
+    .is_none()
+    {
+        panic_utf8(b"TOO LONG XCC PAYLOAD");
+    }
+}
+
+/// Aborts the forwarding in its callback by releasing the lock and logging the reason, since
+/// the panic would revert the release and keep the token locked until the lock expires.
+fn abort_forward<I: IO + Env>(io: &mut I, state: &State, token_id: &AccountId, reason: &str) {
+    params::set_forward_lock(io, token_id, None);
+    log_forward_event(
+        "forward_aborted",
+        state.target_address,
+        &destination_account(io, state),
+        token_id,
+        &[("reason", reason)],
+    );
+}
+
+/// Returns the balance of the token from the result of the `ft_balance_of` or `None` if
+/// the call has failed.
+fn balance_result<I: PromiseHandler>(io: &I, idx: u64) -> Option<u128> {
+    match io.promise_result(idx)? {
+        PromiseResult::Successful(v) => Some(params::vec_to_number(&v).sdk_unwrap()),
+        _ => None,
+    }
+}
+
+/// Returns the id the native NEAR is referred by, e.g. in the pending amounts.
+fn native_token_id() -> AccountId {
+    AccountId::new(NEAR).sdk_unwrap()
+}
+
+/// Adds the native NEAR waiting to be forwarded, so it's reserved from the skimming until
+/// it's forwarded.
+fn add_pending_native<I: IO>(io: &mut I, amount: u128) {
+    let native_id = native_token_id();
+    let pending_amount = params::pending_amount(io, &native_id);
+    params::set_pending_amount(io, &native_id, pending_amount.saturating_add(amount));
+}
+
+/// Returns the NEAR, which isn't forwarded or skimmed: the minimum balance of the forwarder or
+/// the cost of its storage if it's higher.
+fn reserved_balance<I: Env>(io: &I) -> u128 {
+    let storage_cost = u128::from(io.storage_usage()).saturating_mul(STORAGE_PRICE_PER_BYTE);
+    MINIMUM_BALANCE.max(storage_cost)
+}
+
+/// Checks that the NEAR fee isn't attached if the static fee is set, since it's paid to
+/// the fees contract, which isn't called.
+fn assert_near_fee_allowed<I: IO>(io: &I, near_fee: u128) {
+    if near_fee > 0 && StaticFee::load(io).is_some() {
+        panic_utf8(b"NEAR FEE IS NOT SUPPORTED WITH STATIC FEE");
+    }
+}
+
+fn assert_not_paused<I: IO>(io: &I) {
+    if params::is_paused(io) {
+        panic_utf8(b"FORWARDING IS PAUSED");
+    }
+}
+
+/// Deletes the forwarder with the remaining NEAR transferred to the owner.
+fn close_forwarder<I: IO + Env + PromiseHandler>(mut io: I, state: &State) {
+    let event = params::event_json("close", &[("beneficiary_id", state.owner_id.as_str())])
+        .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    let promise = PromiseBatchAction {
+        target_account_id: io.current_account_id(),
+        actions: [PromiseAction::DeleteAccount {
+            beneficiary_id: state.owner_id,
+        }],
+    };
+
+    let promise_id = unsafe { io.promise_create_batch(&promise) };
+    io.promise_return(promise_id);
+}
+
+/// Returns the gas of the downstream calls configured by the owner or the default one.
+fn gas_config<I: IO>(io: &I) -> GasConfig {
+    GasConfig::load(io).unwrap_or(DEFAULT_GAS_CONFIG)
+}
+
+/// Logs the NEP-297 event of the forwarding lifecycle of the token with the target address
+/// and the target network.
+fn log_forward_event(
+    event: &str,
+    target_address: Address,
+    target_network: &AccountId,
+    token_id: &AccountId,
+    fields: &[(&str, &str)],
+) {
+    let target_address = params::address_to_hex(target_address);
+    let mut data = ArrayVec::<(&str, &str), 7>::new();
+    data.push(("token_id", token_id.as_str()));
+    data.try_extend_from_slice(fields)
+        .ok()
+        .sdk_expect("TOO MANY EVENT FIELDS");
+    data.push(("target_address", target_address.as_str()));
+    data.push(("target_network", target_network.as_str()));
+
+    let event = params::event_json(event, &data).sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+}
+
+/// Returns the account the net amount is transferred to: the NEAR receiver if it's set,
+/// the lock contract of the bridge to Ethereum if it's set or the target network.
+fn destination_account<I: IO>(io: &I, state: &State) -> AccountId {
+    params::near_receiver(io)
+        .or_else(|| params::eth_bridge(io))
+        .unwrap_or(state.target_network)
+}
+
+/// Transfers the withdrawn tokens to the withdraw beneficiary without the fee.
+fn pay_out_withdrawal<I: IO + Env + PromiseHandler>(
+    io: &mut I,
+    token_id: AccountId,
+    amount: u128,
+    beneficiary_id: AccountId,
+) -> PromiseId {
+    if amount == 0 {
+        panic_utf8(b"ZERO WITHDRAWAL AMOUNT");
+    }
+
+    let event = params::event_json(
+        "withdrawal_paid_out",
+        &[
+            ("token_id", token_id.as_str()),
+            ("amount", params::amount_to_str(amount).as_str()),
+            ("beneficiary_id", beneficiary_id.as_str()),
+        ],
+    )
+    .sdk_expect("TOO LONG EVENT");
+    log_utf8(event.as_bytes());
+
+    let mut stats = ForwardStats::load(io, &token_id);
+    stats.record(amount, 0, io.block_timestamp(), true);
+    stats.save(io, &token_id);
+
+    unsafe {
+        io.promise_create_call(&PromiseCreateArgs {
+            target_account_id: token_id,
+            method: "ft_transfer",
+            args: ft_transfer_args(&beneficiary_id, amount, None),
+            attached_balance: 1,
+            attached_gas: gas_config(io).ft_transfer,
+        })
+    }
+}
+
+/// Returns the accounts receiving the tokens: the target network and the fee recipient if
+/// the fee isn't zero.
+fn token_receivers(
+    target_network: AccountId,
+    fee: u128,
+    fee_recipient: AccountId,
+) -> ArrayVec<AccountId, 2> {
+    let mut accounts = ArrayVec::new();
+    accounts.push(target_network);
+
+    if fee > 0 {
+        accounts.push(fee_recipient);
+    }
+
+    accounts
+}
+
+/// Returns the `storage_deposit` of the receivers whose `storage_balance_of` hasn't returned
+/// the registered balance.
+fn storage_deposits<I: PromiseHandler>(
+    io: &I,
+    token_id: AccountId,
+    receivers: &[AccountId],
+) -> ArrayVec<PromiseCreateArgs, 2> {
+    receivers
+        .iter()
+        .zip(0..)
+        .filter(|(_, idx)| {
+            !matches!(
+                io.promise_result(*idx),
+                Some(PromiseResult::Successful(v)) if v.as_slice() != b"null"
+            )
+        })
+        .map(|(account_id, _)| PromiseCreateArgs {
+            target_account_id: token_id,
+            method: "storage_deposit",
+            args: storage_deposit_args(account_id),
+            attached_balance: STORAGE_BALANCE_BOUND,
+            attached_gas: STORAGE_DEPOSIT_GAS,
+        })
+        .collect()
+}
+
+/// Returns the format of the `msg` of the `ft_transfer_call` to the target network.
+fn transfer_msg_format<I: IO>(
+    io: &I,
+    state: &State,
+    token_id: AccountId,
+    is_eth_bridge: bool,
+) -> MsgFormat {
+    // The NEP-141 of the engine itself is ETH, which is deposited as the base asset by
+    // the eth-connector instead of the ERC-20. The lock contract of the bridge expects
+    // the address on Ethereum only.
+    if is_eth_bridge {
+        MsgFormat::Address
+    } else if token_id == state.target_network {
+        MsgFormat::EthConnector
+    } else {
+        params::msg_format(io, &state.target_network)
+    }
+}
+
+/// Attaches the `charge_fee_callback`, which transfers the fee after the transfers to
+/// the target network.
+fn attach_charge_fee<I: Env + PromiseHandler>(
+    io: &mut I,
+    promise_id: PromiseId,
+    params: &ChargeFeeParams,
+    ft_transfer_gas: u64,
+) -> PromiseId {
+    unsafe {
+        io.promise_attach_callback(
+            promise_id,
+            &PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "charge_fee_callback",
+                args: types::to_borsh(params).sdk_unwrap(),
+                attached_balance: ZERO_YOCTO,
+                attached_gas: CHARGE_FEE_GAS + ft_transfer_gas,
+            },
+        )
+    }
+}
+
+fn attach_release_lock<I: Env + PromiseHandler>(
+    io: &mut I,
+    promise_id: PromiseId,
+    token_id: AccountId,
+) -> PromiseId {
+    unsafe {
+        io.promise_attach_callback(
+            promise_id,
+            &PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "release_lock_callback",
+                args: types::to_borsh(&token_id).sdk_unwrap(),
+                attached_balance: ZERO_YOCTO,
+                attached_gas: RELEASE_LOCK_GAS,
+            },
+        )
+    }
+}
+
+/// Unwraps the wNEAR after the optional storage deposits and deposits the NEAR by
+/// the `deposit_native_callback`.
+fn unwrap_near<I: Env + PromiseHandler>(
+    io: &mut I,
+    promise_id: Option<PromiseId>,
+    token_id: AccountId,
+    amount: u128,
+    memo: Option<Memo>,
+    deposit_gas: u64,
+) -> PromiseId {
+    let withdraw = PromiseCreateArgs {
+        target_account_id: token_id,
+        method: "near_withdraw",
+        args: near_withdraw_args(amount),
+        attached_balance: 1,
+        attached_gas: NEAR_WITHDRAW_GAS,
+    };
+    let deposit = PromiseCreateArgs {
+        target_account_id: io.current_account_id(),
+        method: "deposit_native_callback",
+        args: types::to_borsh(&(amount, memo)).sdk_unwrap(),
+        attached_balance: ZERO_YOCTO,
+        attached_gas: deposit_gas,
+    };
+
+    unsafe {
+        let withdraw_id = match promise_id {
+            Some(promise_id) => io.promise_attach_callback(promise_id, &withdraw),
+            None => io.promise_create_call(&withdraw),
+        };
+        io.promise_attach_callback_weighted(withdraw_id, &deposit, FORWARD_GAS_WEIGHT)
+    }
+}
+
+fn forward_native_token<I: IO + Env + PromiseHandler>(mut io: I, requested: Option<u128>) {
+    let balance = io
+        .account_balance()
+        .checked_sub(reserved_balance(&io))
+        .filter(|a| *a > 0)
+        .expect("Too low balance");
+    let state = State::load(&io).unwrap();
+    let gas = gas_config(&io);
+    let amount = params::forward_amount(balance, requested).map_or_else(
+        || panic_utf8(b"INVALID FORWARD AMOUNT"),
+        |amount| {
+            params::capped_amount(
+                amount,
+                params::max_forward_amount(&io, &state.wnear_contract_id),
+            )
+        },
+    );
+
+    if amount < params::min_forward_amount(&io, &state.wnear_contract_id) {
+        panic_utf8(b"AMOUNT IS BELOW FORWARD THRESHOLD");
+    }
+
+    lock_forward(&mut io, &state.wnear_contract_id);
+
+    // The forwarded NEAR includes the NEAR waiting to be forwarded.
+    let native_id = native_token_id();
+    let pending_amount = params::pending_amount(&io, &native_id);
+    params::set_pending_amount(&mut io, &native_id, pending_amount.saturating_sub(amount));
+
+    log_forward_event(
+        "forward_started",
+        state.target_address,
+        &destination_account(&io, &state),
+        &state.wnear_contract_id,
+        &[("amount", params::amount_to_str(amount).as_str())],
+    );
+
+    let mut calls = ArrayVec::<_, 2>::new();
+    calls.push(PromiseCreateArgs {
+        target_account_id: state.wnear_contract_id,
+        method: "near_deposit",
+        args: Vec::new(),
+        attached_balance: amount,
+        attached_gas: NEAR_DEPOSIT_GAS,
+    });
+
+    // The static fee is applied by the `finish_forward_callback` itself.
+    if StaticFee::load(&io).is_none() {
+        calls.push(PromiseCreateArgs {
+            target_account_id: state.fees_contract_id,
+            method: "record_and_calculate_native_fees",
+            args: types::to_borsh(&NativeFeesParams {
+                amount,
+                target_address: state.target_address,
+            })
+            .sdk_unwrap(),
+            attached_balance: ZERO_YOCTO,
+            attached_gas: gas.calculate_fees,
+        });
+    }
+
+    let promise_id = unsafe {
+        let promise_id = io.promise_create_and_combine(&calls);
+
+        io.promise_attach_callback_weighted(
+            promise_id,
+            &PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "finish_forward_callback",
+                args: types::to_borsh(&FinishForwardParams {
+                    amount,
+                    token_id: state.wnear_contract_id,
+                    promise_idx: 1,
+                    cache_fee: false,
+                    memo: None,
+                    is_native: true,
+                    is_fallback: false,
+                })
+                .sdk_unwrap(),
+                attached_balance: 2,
+                attached_gas: gas.finish_forward(),
+            },
+            FORWARD_GAS_WEIGHT,
+        )
+    };
+
+    io.promise_return(promise_id);
+}
+
+fn forward_nep141_token<I: IO + Env + PromiseHandler>(
+    io: &mut I,
+    params: &ForwardParams,
+) -> PromiseId {
+    let callback_args = types::to_borsh(params).sdk_unwrap();
+    let gas = gas_config(io);
+
+    unsafe {
+        let promise_id = io.promise_create_call(&PromiseCreateArgs {
+            target_account_id: params.token_id,
+            method: "ft_balance_of",
+            args: ft_balance_args(&io.current_account_id()),
+            attached_balance: ZERO_YOCTO,
+            attached_gas: FT_BALANCE_GAS,
+        });
+
+        io.promise_attach_callback_weighted(
+            promise_id,
+            &PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "calculate_fees_callback",
+                args: callback_args,
+                attached_balance: ZERO_YOCTO,
+                attached_gas: gas.calculate_fees_callback(),
+            },
+            FORWARD_GAS_WEIGHT,
+        )
+    }
+}
+
+/// Deletes the forwarder once it holds none of the tracked tokens. Every call checks that
+/// the balances of the next page of the tracked tokens are zero, unregisters the storage of
+/// the forwarder on them and stops tracking them, so the owner repeats the call until
+/// the forwarder is deleted and the storage deposits and the remaining NEAR are refunded to
+/// the owner. None of the tracked tokens or the native NEAR should be waiting to be forwarded
+/// or retried.
+// The symbol isn't exported on the host, where it would override the `close` of the libc.
+#[cfg_attr(target_arch = "wasm32", no_mangle)]
+pub extern "C" fn close() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN CLOSE");
+    }
+
+    io.attached_deposit_above_one_yocto().sdk_unwrap();
+    assert_nothing_pending(&io);
+    let tokens = params::tracked_tokens(&io, 0);
+
+    if tokens.0.is_empty() {
+        close_forwarder(io, &state);
+        return;
+    }
+
+    let checks = tokens
+        .0
+        .iter()
+        .map(|token_id| PromiseCreateArgs {
+            target_account_id: *token_id,
+            method: "ft_balance_of",
+            args: ft_balance_args(&io.current_account_id()),
+            attached_balance: ZERO_YOCTO,
+            attached_gas: FT_BALANCE_GAS,
+        })
+        .collect::<ArrayVec<_, 4>>();
+
+    let promise_id = unsafe {
+        let promise_id = io.promise_create_and_combine(&checks);
+
+        io.promise_attach_callback(
+            promise_id,
+            &PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "close_callback",
+                args: types::to_borsh(&tokens).sdk_unwrap(),
+                attached_balance: ZERO_YOCTO,
+                attached_gas: CLOSE_CALLBACK_GAS,
+            },
+        )
+    };
+
+    io.promise_return(promise_id);
+}
+
+/// Checks that none of the tracked tokens or the native NEAR is waiting to be forwarded,
+/// so the deletion of the forwarder doesn't lose them.
+fn assert_nothing_pending<I: IO + Env>(io: &I) {
+    let now = io.block_timestamp();
+    let is_pending = (0..params::tracked_tokens_len(io))
+        .filter_map(|idx| params::tracked_token(io, idx))
+        .any(|token_id| {
+            params::pending_amount(io, &token_id) > 0
+                || !RetryQueue::load(io, &token_id).is_empty()
+                || params::is_forward_locked(io, &token_id, now)
+        });
+
+    if is_pending || params::pending_amount(io, &native_token_id()) > 0 {
+        panic_utf8(b"FORWARD IS PENDING");
+    }
+}
+
+/// Unregisters the storage of the forwarder on the tokens if their balances are zero.
+#[no_mangle]
+pub extern "C" fn close_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let tokens: AccountList = io.read_input_borsh().sdk_unwrap();
+    let unregisters = tokens
+        .0
+        .iter()
+        .zip(0..)
+        .map(|(token_id, idx)| {
+            let balance = match io.promise_result(idx).sdk_expect("No promise result") {
+                PromiseResult::Successful(v) => params::vec_to_number::<u128>(&v).sdk_unwrap(),
+                _ => panic_utf8(b"BALANCE IS NOT READY"),
+            };
+
+            if balance > 0 {
+                panic_utf8(b"TOKEN BALANCE IS NOT ZERO");
+            }
+
+            PromiseCreateArgs {
+                target_account_id: *token_id,
+                method: "storage_unregister",
+                args: storage_unregister_args(false),
+                attached_balance: 1,
+                attached_gas: STORAGE_UNREGISTER_GAS,
+            }
+        })
+        .collect::<ArrayVec<_, 4>>();
+
+    let promise_id = unsafe {
+        let promise_id = io.promise_create_and_combine(&unregisters);
+
+        io.promise_attach_callback(
+            promise_id,
+            &PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "close_finish_callback",
+                args: types::to_borsh(&tokens).sdk_unwrap(),
+                attached_balance: ZERO_YOCTO,
+                attached_gas: CLOSE_FINISH_GAS,
+            },
+        )
+    };
+
+    io.promise_return(promise_id);
+}
+
+/// Unregisters the storage of the forwarder on the tokens, e.g. the blacklisted ones or
+/// before the forwarder is wound down, so the storage deposits are refunded to the NEAR
+/// balance of the forwarder. The tokens are no longer tracked once unregistered.
+#[no_mangle]
+pub extern "C" fn unregister_storage() {
+    let mut io = Runtime;
+    let state = State::load(&io).sdk_expect("No state");
+
+    if !state.is_owner(io.predecessor_account_id()) {
+        panic_utf8(b"ONLY OWNER CAN UNREGISTER STORAGE");
+    }
+
+    io.attached_deposit_above_one_yocto().sdk_unwrap();
+    let params: UnregisterStorageParams = io.read_input_borsh().sdk_unwrap();
+
+    if params.token_ids.0.is_empty() {
+        panic_utf8(b"NO TOKENS TO UNREGISTER");
+    }
+
+    let unregisters = params
+        .token_ids
+        .0
+        .iter()
+        .map(|token_id| PromiseCreateArgs {
+            target_account_id: *token_id,
+            method: "storage_unregister",
+            args: storage_unregister_args(params.force),
+            attached_balance: 1,
+            attached_gas: STORAGE_UNREGISTER_GAS,
+        })
+        .collect::<ArrayVec<_, 4>>();
+
+    let promise_id = unsafe {
+        let promise_id = io.promise_create_and_combine(&unregisters);
+
+        io.promise_attach_callback(
+            promise_id,
+            &PromiseCreateArgs {
+                target_account_id: io.current_account_id(),
+                method: "unregister_storage_callback",
+                args: types::to_borsh(&params.token_ids).sdk_unwrap(),
+                attached_balance: ZERO_YOCTO,
+                attached_gas: UNREGISTER_STORAGE_CALLBACK_GAS,
+            },
+        )
+    };
+
+    io.promise_return(promise_id);
+}
+
+/// Stops tracking the tokens the storage has been unregistered on. The storage of the rest
+/// of them is kept, e.g. because the forwarder holds the token, so only the deposit above
+/// the minimum is withdrawn.
+#[no_mangle]
+pub extern "C" fn unregister_storage_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let tokens: AccountList = io.read_input_borsh().sdk_unwrap();
+
+    for (token_id, idx) in tokens.0.iter().zip(0..) {
+        if matches!(io.promise_result(idx), Some(PromiseResult::Successful(_))) {
+            params::untrack_token(&mut io, token_id);
+
+            let event =
+                params::event_json("storage_unregistered", &[("token_id", token_id.as_str())])
+                    .sdk_expect("TOO LONG EVENT");
+            log_utf8(event.as_bytes());
+        } else {
+            let _ = unsafe {
+                io.promise_create_call(&PromiseCreateArgs {
+                    target_account_id: *token_id,
+                    method: "storage_withdraw",
+                    args: storage_withdraw_args(),
+                    attached_balance: 1,
+                    attached_gas: STORAGE_WITHDRAW_GAS,
+                })
+            };
+        }
+    }
+}
+
+/// Stops tracking the tokens if the storage has been unregistered on every one of them and
+/// deletes the forwarder once no tracked tokens are left.
+#[no_mangle]
+pub extern "C" fn close_finish_callback() {
+    let mut io = Runtime;
+    io.assert_private_call().sdk_unwrap();
+
+    let tokens: AccountList = io.read_input_borsh().sdk_unwrap();
+    let count = u64::try_from(tokens.0.len())
+        .ok()
+        .sdk_expect("TOO MANY TOKENS");
+    let unregistered =
+        (0..count).all(|idx| matches!(io.promise_result(idx), Some(PromiseResult::Successful(_))));
+
+    if !unregistered {
+        panic_utf8(b"STORAGE UNREGISTER FAILED");
+    }
+
+    for token_id in &tokens.0 {
+        params::untrack_token(&mut io, token_id);
+    }
+
+    let remaining = params::tracked_tokens_len(&io);
+
+    if remaining > 0 {
+        let event = params::event_json(
+            "close_progress",
+            &[(
+                "remaining",
+                params::amount_to_str(remaining.into()).as_str(),
+            )],
+        )
+        .sdk_expect("TOO LONG EVENT");
+        log_utf8(event.as_bytes());
+        return;
+    }
+
+    let state = State::load(&io).sdk_expect("No state");
+    close_forwarder(io, &state);
+}
+
 #[no_mangle]
 pub extern "C" fn destroy() {
     let mut io = Runtime;
@@ -1,15 +1,91 @@
-use arrayvec::ArrayString;
-use borsh::{BorshDeserialize, BorshSerialize};
+use arrayvec::{ArrayString, ArrayVec};
+use borsh::{io, BorshDeserialize, BorshSerialize};
 use core::str::FromStr;
+use hex::FromHex;
 
 use crate::error::ContractError;
 use crate::runtime::{StorageIntermediate, IO};
-use crate::types::{AccountId, Address, Vec};
+use crate::types::{AccountId, Address, Vec, VEC_CAPACITY};
 
-const STATE_STORAGE_KEY: &[u8] = b"FWD_STATE";
+// The state of the forwarders deployed before the versioned state.
+const LEGACY_STATE_STORAGE_KEY: &[u8] = b"FWD_STATE";
+const STATE_STORAGE_KEY: &[u8] = b"FWD_VERSIONED_STATE";
+const PENDING_STORAGE_PREFIX: &[u8] = b"FWD_PENDING";
+const NATIVE_REFUND_STORAGE_PREFIX: &[u8] = b"FWD_NATIVE_REFUND";
+const RETRY_STORAGE_PREFIX: &[u8] = b"FWD_RETRY";
+const NFT_BRIDGE_STORAGE_KEY: &[u8] = b"FWD_NFT_BRIDGE";
+const PAUSED_STORAGE_KEY: &[u8] = b"FWD_PAUSED";
+const NEAR_RECEIVER_STORAGE_KEY: &[u8] = b"FWD_NEAR_RECEIVER";
+const KEEPERS_STORAGE_KEY: &[u8] = b"FWD_KEEPERS";
+const BLACKLIST_STORAGE_KEY: &[u8] = b"FWD_BLACKLIST";
+const ETH_BRIDGE_STORAGE_KEY: &[u8] = b"FWD_ETH_BRIDGE";
+const WITHDRAW_BENEFICIARY_STORAGE_KEY: &[u8] = b"FWD_WITHDRAW_BENEFICIARY";
+const ADDRESS_NONCE_STORAGE_KEY: &[u8] = b"FWD_ADDRESS_NONCE";
+const STATS_STORAGE_PREFIX: &[u8] = b"FWD_STATS";
+const MIN_FORWARD_AMOUNT_STORAGE_PREFIX: &[u8] = b"FWD_MIN_AMOUNT";
+const MAX_FORWARD_AMOUNT_STORAGE_PREFIX: &[u8] = b"FWD_MAX_AMOUNT";
+const GAS_CONFIG_STORAGE_KEY: &[u8] = b"FWD_GAS";
+const SPLITS_STORAGE_KEY: &[u8] = b"FWD_SPLITS";
+const STATIC_FEE_STORAGE_KEY: &[u8] = b"FWD_STATIC_FEE";
+const FALLBACK_FEE_STORAGE_KEY: &[u8] = b"FWD_FALLBACK_FEE";
+const FALLBACK_FEES_CONTRACT_STORAGE_KEY: &[u8] = b"FWD_FALLBACK_FEES_CONTRACT";
+const FEE_CACHE_STORAGE_PREFIX: &[u8] = b"FWD_FEE_PERCENT_CACHE";
+const FEE_CACHE_TTL_STORAGE_KEY: &[u8] = b"FWD_FEE_CACHE_TTL";
+const MSG_FORMAT_STORAGE_PREFIX: &[u8] = b"FWD_MSG_FORMAT";
+const NEAR_BASE_TOKEN_STORAGE_PREFIX: &[u8] = b"FWD_NEAR_BASE_TOKEN";
+const XCC_PAYLOAD_STORAGE_KEY: &[u8] = b"FWD_XCC_PAYLOAD";
+const TRACKED_TOKENS_LEN_STORAGE_KEY: &[u8] = b"FWD_TRACKED_LEN";
+const TRACKED_TOKEN_STORAGE_PREFIX: &[u8] = b"FWD_TRACKED_TOKEN";
+const TRACKED_TOKEN_INDEX_STORAGE_PREFIX: &[u8] = b"FWD_TRACKED_IDX";
+const FORWARD_ALL_CURSOR_STORAGE_KEY: &[u8] = b"FWD_FORWARD_ALL_CURSOR";
+const FORWARD_LOCK_STORAGE_PREFIX: &[u8] = b"FWD_LOCK";
+const SCHEDULE_STORAGE_PREFIX: &[u8] = b"FWD_SCHEDULE";
+const RATE_LIMIT_STORAGE_KEY: &[u8] = b"FWD_RATE_LIMIT";
+const RATE_WINDOW_STORAGE_PREFIX: &[u8] = b"FWD_RATE_WINDOW";
+// The time in nanoseconds after which the lock of the forwarding is released, so the lock
+// isn't kept forever if a callback of the forwarding has failed before releasing it.
+const FORWARD_LOCK_TIMEOUT: u64 = 600_000_000_000;
+const NANOS_PER_HOUR: u64 = 3_600_000_000_000;
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+// The zero relayer fee of the eth-connector message encoded as 32 bytes of hex.
+const ZERO_CONNECTOR_FEE_HEX: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+const MAX_SPLITS: usize = 4;
+// The max number of the tokens passed to the `forward_batch` and the `close` or the keepers.
+const MAX_LISTED_ACCOUNTS: usize = 4;
+// The max number of the tracked tokens, which bounds the storage staked by the airdropped ones.
+const MAX_TRACKED_TOKENS: u32 = 32;
+// The number of the tracked tokens returned at once, so the longest account ids fit the buffer.
+const TRACKED_TOKENS_PAGE_LEN: usize = 3;
+// The sum of the weights of the splits in basis points.
+const TOTAL_WEIGHT: u16 = 10_000;
+// The 100% of the fee percent of the fees contract.
+const FEE_PERCENT_DENOMINATOR: u128 = 1_000_000;
+const TGAS: u64 = 1_000_000_000_000;
+// The well-known accounts, which are stored as the single byte in the packed state.
+const INTERNED_ACCOUNTS: [&str; 3] = ["aurora", "wrap.near", "wrap.testnet"];
+// The first byte of the interned account in the packed state. The other accounts start with
+// their length, which is never above 64.
+const INTERNED_ACCOUNT_TAG: u8 = 0xF0;
+// The gas of the `transfer_tokens_callback` above the transfers: two storage deposits,
+// the `resolve_forward_callback` of every split, the `charge_fee_callback`,
+// the `release_lock_callback` and the execution of the callback itself.
+const TRANSFER_TOKENS_OVERHEAD_GAS: u64 = 45 * TGAS;
+const FINISH_FORWARD_OVERHEAD_GAS: u64 = 15 * TGAS;
+const CALCULATE_FEES_CALLBACK_OVERHEAD_GAS: u64 = 16 * TGAS;
+// The serialized queue should fit into the 256 bytes.
+const MAX_RETRIES: usize = 8;
+const MAX_MEMO_LEN: usize = 64;
+// The max length of the EVM calldata: the selector and eleven 32 bytes words, e.g. a swap
+// with a path of three tokens. It's embedded into the `msg` as hex, so the arguments of
+// the `ft_transfer_call` take 291 bytes with the longest account id, amount and memo plus
+// twice the calldata, 1003 bytes of the 1024 bytes `Vec`. The EVM gas of the calldata is
+// 16 per byte, which is negligible against the gas of the `ft_transfer_call`, so the gas
+// of the call of the target contract is what the gas config should cover.
+const MAX_XCC_PAYLOAD_LEN: usize = 4 + 11 * 32;
 
-#[derive(BorshSerialize, BorshDeserialize)]
-#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Debug))]
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
 pub struct State {
     pub target_address: Address,
     pub target_network: AccountId,
@@ -18,125 +94,2620 @@ pub struct State {
     pub owner_id: AccountId,
 }
 
-impl State {
-    pub fn save<I: IO>(&self, io: &mut I) {
-        io.write_borsh(STATE_STORAGE_KEY, self);
+/// The versioned layout of the state. The new layout is added as the new variant, which
+/// the previous ones are converted to by the `migrate`.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub enum VersionedState {
+    V1(State),
+    V2(PackedState),
+}
+
+impl VersionedState {
+    pub const fn is_latest(&self) -> bool {
+        matches!(self, Self::V2(_))
+    }
+
+    pub const fn into_latest(self) -> State {
+        match self {
+            Self::V1(state) | Self::V2(PackedState(state)) => state,
+        }
+    }
+}
+
+/// The state packed into the compact record to reduce the storage staking: the address is
+/// stored as 20 raw bytes and the accounts are prefixed with one byte of their length or
+/// interned into one byte.
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct PackedState(pub State);
+
+impl BorshSerialize for PackedState {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.target_address.serialize(writer)?;
+        serialize_packed_account(&self.0.target_network, writer)?;
+        serialize_packed_account(&self.0.wnear_contract_id, writer)?;
+        serialize_packed_account(&self.0.fees_contract_id, writer)?;
+        serialize_packed_account(&self.0.owner_id, writer)
+    }
+}
+
+impl BorshDeserialize for PackedState {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(Self(State {
+            target_address: Address::deserialize_reader(reader)?,
+            target_network: deserialize_packed_account(reader)?,
+            wnear_contract_id: deserialize_packed_account(reader)?,
+            fees_contract_id: deserialize_packed_account(reader)?,
+            owner_id: deserialize_packed_account(reader)?,
+        }))
+    }
+}
+
+fn serialize_packed_account<W: io::Write>(
+    account_id: &AccountId,
+    writer: &mut W,
+) -> io::Result<()> {
+    if let Some(idx) = INTERNED_ACCOUNTS
+        .iter()
+        .position(|interned| *interned == account_id.as_str())
+    {
+        let idx = u8::try_from(idx).map_err(|_| invalid_packed_account())?;
+        return writer.write_all(&[INTERNED_ACCOUNT_TAG + idx]);
+    }
+
+    let len = u8::try_from(account_id.as_bytes().len()).map_err(|_| invalid_packed_account())?;
+    writer.write_all(&[len])?;
+    writer.write_all(account_id.as_bytes())
+}
+
+fn deserialize_packed_account<R: io::Read>(reader: &mut R) -> io::Result<AccountId> {
+    let mut tag = [0; 1];
+    reader.read_exact(&mut tag)?;
+
+    if tag[0] >= INTERNED_ACCOUNT_TAG {
+        let idx = usize::from(tag[0] - INTERNED_ACCOUNT_TAG);
+        let interned = INTERNED_ACCOUNTS
+            .get(idx)
+            .ok_or_else(invalid_packed_account)?;
+        return AccountId::new(interned).map_err(|_| invalid_packed_account());
+    }
+
+    let mut buf = [0; 64];
+    let buf = buf
+        .get_mut(..usize::from(tag[0]))
+        .ok_or_else(invalid_packed_account)?;
+    reader.read_exact(buf)?;
+
+    core::str::from_utf8(buf)
+        .ok()
+        .and_then(|account_id| AccountId::new(account_id).ok())
+        .ok_or_else(invalid_packed_account)
+}
+
+fn invalid_packed_account() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Invalid packed account")
+}
+
+impl State {
+    pub fn save<I: IO>(&self, io: &mut I) {
+        io.write_borsh(STATE_STORAGE_KEY, &VersionedState::V2(PackedState(*self)));
+    }
+
+    /// Loads the state in the latest layout. The legacy state is loaded as is until
+    /// the `migrate` is called.
+    pub fn load<I: IO>(io: &I) -> Option<Self> {
+        let Some(data) = io.read_storage(STATE_STORAGE_KEY) else {
+            return Self::load_legacy(io);
+        };
+        let data = data.to_vec();
+        VersionedState::try_from_slice(data.as_slice())
+            .ok()
+            .map(VersionedState::into_latest)
+    }
+
+    /// Loads the state stored without the version by the forwarders deployed before.
+    pub fn load_legacy<I: IO>(io: &I) -> Option<Self> {
+        let data = io.read_storage(LEGACY_STATE_STORAGE_KEY)?.to_vec();
+        Self::try_from_slice(data.as_slice()).ok()
+    }
+
+    /// Rewrites the legacy state or the state of the previous version in the latest layout.
+    /// Returns `false` if there is nothing to migrate.
+    pub fn migrate<I: IO>(io: &mut I) -> bool {
+        let is_latest = io
+            .read_storage(STATE_STORAGE_KEY)
+            .and_then(|value| value.to_value::<VersionedState>().ok())
+            .is_some_and(|state| state.is_latest());
+
+        if is_latest {
+            return false;
+        }
+
+        let Some(state) = Self::load(io) else {
+            return false;
+        };
+
+        state.save(io);
+        io.remove_storage(LEGACY_STATE_STORAGE_KEY);
+        true
+    }
+
+    pub fn is_owner(&self, account_id: AccountId) -> bool {
+        self.owner_id == account_id
+    }
+
+    /// Returns the JSON of the configuration of the forwarder with the version of the contract.
+    pub fn to_json(self, version: &str) -> ArrayString<512> {
+        let mut result = ArrayString::<512>::new();
+
+        result.push_str(r#"{"target_address":""#);
+        result.push_str(address_to_hex(self.target_address).as_str());
+        result.push_str(r#"","target_network":""#);
+        result.push_str(self.target_network.as_str());
+        result.push_str(r#"","wnear_contract_id":""#);
+        result.push_str(self.wnear_contract_id.as_str());
+        result.push_str(r#"","fees_contract_id":""#);
+        result.push_str(self.fees_contract_id.as_str());
+        result.push_str(r#"","owner_id":""#);
+        result.push_str(self.owner_id.as_str());
+        result.push_str(r#"","version":""#);
+        result.push_str(version);
+        result.push_str(r#""}"#);
+
+        result
+    }
+}
+
+/// The gas attached to the calls of the fees contract and the token. The gas of the callbacks
+/// is derived from it, so the whole forwarding fits into the gas limit of the transaction.
+/// It's the minimum gas of the forwarding chain, whose calls also share the unused prepaid gas.
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct GasConfig {
+    /// The gas of the `record_and_calculate_fees` and the `record_and_calculate_native_fees`.
+    pub calculate_fees: u64,
+    /// The gas of the `ft_transfer` of the fee.
+    pub ft_transfer: u64,
+    /// The gas of the `ft_transfer_call` to the target network. It's shared between
+    /// the splits if the tokens are split between several addresses.
+    pub ft_transfer_call: u64,
+}
+
+impl GasConfig {
+    pub const MIN_CALCULATE_FEES: u64 = 2 * TGAS;
+    pub const MAX_CALCULATE_FEES: u64 = 30 * TGAS;
+    pub const MIN_FT_TRANSFER: u64 = 2 * TGAS;
+    pub const MAX_FT_TRANSFER: u64 = 30 * TGAS;
+    pub const MIN_FT_TRANSFER_CALL: u64 = 10 * TGAS;
+    pub const MAX_FT_TRANSFER_CALL: u64 = 140 * TGAS;
+
+    pub fn load<I: IO>(io: &I) -> Option<Self> {
+        io.read_storage(GAS_CONFIG_STORAGE_KEY)
+            .and_then(|value| value.to_value().ok())
+    }
+
+    pub fn save<I: IO>(&self, io: &mut I) {
+        io.write_borsh(GAS_CONFIG_STORAGE_KEY, self);
+    }
+
+    /// Returns `true` if every amount of the gas is within its bounds.
+    pub const fn is_valid(&self) -> bool {
+        self.calculate_fees >= Self::MIN_CALCULATE_FEES
+            && self.calculate_fees <= Self::MAX_CALCULATE_FEES
+            && self.ft_transfer >= Self::MIN_FT_TRANSFER
+            && self.ft_transfer <= Self::MAX_FT_TRANSFER
+            && self.ft_transfer_call >= Self::MIN_FT_TRANSFER_CALL
+            && self.ft_transfer_call <= Self::MAX_FT_TRANSFER_CALL
+    }
+
+    /// The gas of the `transfer_tokens_callback`.
+    pub const fn transfer_tokens(&self) -> u64 {
+        self.ft_transfer_call + self.ft_transfer + TRANSFER_TOKENS_OVERHEAD_GAS
+    }
+
+    /// The gas of the `finish_forward_callback`.
+    pub const fn finish_forward(&self) -> u64 {
+        self.transfer_tokens() + FINISH_FORWARD_OVERHEAD_GAS
+    }
+
+    /// The gas of the `calculate_fees_callback`.
+    pub const fn calculate_fees_callback(&self) -> u64 {
+        self.calculate_fees + self.finish_forward() + CALCULATE_FEES_CALLBACK_OVERHEAD_GAS
+    }
+}
+
+/// The fixed fee of the forwarder, which is charged instead of the fee calculated by the fees
+/// contract, so the forwarding skips the call of the fees contract.
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct StaticFee {
+    /// The fee in basis points of the forwarded amount.
+    pub fee_bps: u16,
+    pub fee_recipient: AccountId,
+}
+
+impl StaticFee {
+    pub fn load<I: IO>(io: &I) -> Option<Self> {
+        io.read_storage(STATIC_FEE_STORAGE_KEY)
+            .and_then(|value| value.to_value().ok())
+    }
+
+    /// Stores the static fee. `None` restores the fees calculated by the fees contract.
+    pub fn save<I: IO>(fee: Option<&Self>, io: &mut I) {
+        Self::write(fee, io, STATIC_FEE_STORAGE_KEY);
+    }
+
+    /// Returns the fee charged if neither the fees contract nor the fallback fees contract
+    /// has calculated the fee, so the forwarding isn't aborted.
+    pub fn load_fallback<I: IO>(io: &I) -> Option<Self> {
+        io.read_storage(FALLBACK_FEE_STORAGE_KEY)
+            .and_then(|value| value.to_value().ok())
+    }
+
+    /// Stores the fallback fee. `None` aborts the forwarding if the fee isn't calculated.
+    pub fn save_fallback<I: IO>(fee: Option<&Self>, io: &mut I) {
+        Self::write(fee, io, FALLBACK_FEE_STORAGE_KEY);
+    }
+
+    fn write<I: IO>(fee: Option<&Self>, io: &mut I, key: &[u8]) {
+        if let Some(fee) = fee {
+            io.write_borsh(key, fee);
+        } else {
+            io.remove_storage(key);
+        }
+    }
+
+    pub const fn is_valid(&self) -> bool {
+        self.fee_bps <= TOTAL_WEIGHT
+    }
+
+    /// Returns the fee of the amount the same way as it's returned by the fees contract.
+    pub const fn fees_result(&self, amount: u128) -> FeesResult {
+        FeesResult {
+            fee: mul_div(amount, self.fee_bps as u128, TOTAL_WEIGHT as u128),
+            fee_recipient: self.fee_recipient,
+            rejected: false,
+            invalid_address: false,
+            percent: None,
+        }
+    }
+}
+
+/// The limit of the forwardings of every token per the time window, so the bots couldn't
+/// drain the gas and the NEAR of the forwarder by the repeated forwardings of the dust.
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct RateLimit {
+    pub max_forwards: u32,
+    pub window_secs: u64,
+}
+
+impl RateLimit {
+    pub fn load<I: IO>(io: &I) -> Option<Self> {
+        io.read_storage(RATE_LIMIT_STORAGE_KEY)
+            .and_then(|value| value.to_value().ok())
+    }
+
+    /// Stores the rate limit. `None` removes the limit.
+    pub fn save<I: IO>(limit: Option<&Self>, io: &mut I) {
+        if let Some(limit) = limit {
+            io.write_borsh(RATE_LIMIT_STORAGE_KEY, limit);
+        } else {
+            io.remove_storage(RATE_LIMIT_STORAGE_KEY);
+        }
+    }
+
+    pub const fn is_valid(&self) -> bool {
+        self.max_forwards > 0 && self.window_secs > 0
+    }
+
+    /// Returns the window with the forwarding counted at the `now` timestamp or `None` if
+    /// the limit of the window has been reached. The expired window is started anew.
+    pub const fn admit(&self, window: Option<RateWindow>, now: u64) -> Option<RateWindow> {
+        let window_nanos = self.window_secs.saturating_mul(NANOS_PER_SECOND);
+        let window = match window {
+            Some(window) if now.saturating_sub(window.started_at) < window_nanos => window,
+            _ => RateWindow {
+                started_at: now,
+                count: 0,
+            },
+        };
+
+        if window.count >= self.max_forwards {
+            return None;
+        }
+
+        Some(RateWindow {
+            started_at: window.started_at,
+            count: window.count + 1,
+        })
+    }
+}
+
+/// The number of the forwardings of the token in the current window of the rate limit.
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct RateWindow {
+    pub started_at: u64,
+    pub count: u32,
+}
+
+impl RateWindow {
+    pub fn load<I: IO>(io: &I, token_id: &AccountId) -> Option<Self> {
+        io.read_storage(&storage_key(RATE_WINDOW_STORAGE_PREFIX, token_id))
+            .and_then(|value| value.to_value().ok())
+    }
+
+    pub fn save<I: IO>(&self, io: &mut I, token_id: &AccountId) {
+        io.write_borsh(&storage_key(RATE_WINDOW_STORAGE_PREFIX, token_id), self);
+    }
+}
+
+/// The fee percent of the token reported by the fees contract, which is applied to the next
+/// forwardings until the cache expires, so they don't wait for the fees contract. The fees
+/// contract reports the percent only if the fee is the pure percentage of the amount, so
+/// the fees with the flat parts, the caps, the tiers or the amount limits are never cached.
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct FeeCache {
+    /// The percent of the fee, where `FEE_PERCENT_DENOMINATOR` is 100%.
+    pub percent: u64,
+    pub fee_recipient: AccountId,
+    /// The height of the block when the fee has been calculated.
+    pub block_height: u64,
+}
+
+impl FeeCache {
+    pub fn load<I: IO>(io: &I, token_id: &AccountId) -> Option<Self> {
+        io.read_storage(&storage_key(FEE_CACHE_STORAGE_PREFIX, token_id))
+            .and_then(|value| value.to_value().ok())
+    }
+
+    pub fn save<I: IO>(&self, io: &mut I, token_id: &AccountId) {
+        io.write_borsh(&storage_key(FEE_CACHE_STORAGE_PREFIX, token_id), self);
+    }
+
+    /// Returns the fee of the amount with the cached fee percent or `None` if the cache
+    /// has expired at the block height. The zero TTL disables the cache. The fee is rounded
+    /// down and is at least one unit, the same way as the fees contract calculates it.
+    pub const fn fees_result(&self, amount: u128, height: u64, ttl: u64) -> Option<FeesResult> {
+        if height.saturating_sub(self.block_height) >= ttl {
+            return None;
+        }
+
+        let fee = match mul_div(amount, self.percent as u128, FEE_PERCENT_DENOMINATOR) {
+            0 => 1,
+            fee => fee,
+        };
+
+        Some(FeesResult {
+            fee: if fee < amount { fee } else { amount },
+            fee_recipient: self.fee_recipient,
+            rejected: false,
+            invalid_address: false,
+            percent: Some(self.percent),
+        })
+    }
+}
+
+/// The schedule of the recurring forwarding of the token, which is triggered by the keepers
+/// at most once per the interval.
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct ForwardSchedule {
+    pub interval_hours: u64,
+    /// The timestamp of the last triggered forwarding or zero if it hasn't been triggered.
+    pub last_triggered_at: u64,
+}
+
+impl ForwardSchedule {
+    pub fn load<I: IO>(io: &I, token_id: &AccountId) -> Option<Self> {
+        io.read_storage(&storage_key(SCHEDULE_STORAGE_PREFIX, token_id))
+            .and_then(|value| value.to_value().ok())
+    }
+
+    /// Stores the schedule of the token. `None` removes it.
+    pub fn save<I: IO>(schedule: Option<&Self>, io: &mut I, token_id: &AccountId) {
+        let key = storage_key(SCHEDULE_STORAGE_PREFIX, token_id);
+
+        if let Some(schedule) = schedule {
+            io.write_borsh(&key, schedule);
+        } else {
+            io.remove_storage(&key);
+        }
+    }
+
+    /// Returns `true` if the interval has elapsed since the last triggered forwarding at
+    /// the `now` timestamp.
+    pub const fn is_due(&self, now: u64) -> bool {
+        now.saturating_sub(self.last_triggered_at)
+            >= self.interval_hours.saturating_mul(NANOS_PER_HOUR)
+    }
+}
+
+/// Returns the fees contract, which calculates the fee if the call of the fees contract of
+/// the forwarder has failed.
+pub fn fallback_fees_contract<I: IO>(io: &I) -> Option<AccountId> {
+    io.read_storage(FALLBACK_FEES_CONTRACT_STORAGE_KEY)
+        .and_then(|value| value.to_value().ok())
+}
+
+/// Stores the fallback fees contract. `None` removes it.
+pub fn set_fallback_fees_contract<I: IO>(io: &mut I, fees_contract_id: Option<&AccountId>) {
+    if let Some(fees_contract_id) = fees_contract_id {
+        io.write_borsh(FALLBACK_FEES_CONTRACT_STORAGE_KEY, fees_contract_id);
+    } else {
+        io.remove_storage(FALLBACK_FEES_CONTRACT_STORAGE_KEY);
+    }
+}
+
+/// Returns the number of the blocks the fee percent of the token is cached for.
+pub fn fee_cache_ttl<I: IO>(io: &I) -> u64 {
+    io.read_storage(FEE_CACHE_TTL_STORAGE_KEY)
+        .and_then(|value| value.to_value().ok())
+        .unwrap_or_default()
+}
+
+/// Stores the number of the blocks the fee percent is cached for. Zero disables the cache.
+pub fn set_fee_cache_ttl<I: IO>(io: &mut I, ttl: u64) {
+    if ttl == 0 {
+        io.remove_storage(FEE_CACHE_TTL_STORAGE_KEY);
+    } else {
+        io.write_borsh(FEE_CACHE_TTL_STORAGE_KEY, &ttl);
+    }
+}
+
+/// Returns the amount of the token, which has been refunded by the target network after
+/// the last forwarding and is waiting to be forwarded again.
+pub fn pending_amount<I: IO>(io: &I, token_id: &AccountId) -> u128 {
+    read_amount(io, PENDING_STORAGE_PREFIX, token_id)
+}
+
+/// Stores the refunded amount of the token. Zero removes the record.
+pub fn set_pending_amount<I: IO>(io: &mut I, token_id: &AccountId, amount: u128) {
+    write_amount(io, PENDING_STORAGE_PREFIX, token_id, amount);
+}
+
+/// Returns the native NEAR refunded by the failed deposits of the last forwarding of
+/// the unwrapped token, which is forwarded by the `forward_near` instead of the token.
+pub fn native_refund_amount<I: IO>(io: &I, token_id: &AccountId) -> u128 {
+    read_amount(io, NATIVE_REFUND_STORAGE_PREFIX, token_id)
+}
+
+/// Stores the native NEAR refunded by the last forwarding of the token. Zero removes
+/// the record.
+pub fn set_native_refund_amount<I: IO>(io: &mut I, token_id: &AccountId, amount: u128) {
+    write_amount(io, NATIVE_REFUND_STORAGE_PREFIX, token_id, amount);
+}
+
+/// Returns `true` if the forwarding of the token is in progress at the `now` timestamp.
+pub fn is_forward_locked<I: IO>(io: &I, token_id: &AccountId, now: u64) -> bool {
+    io.read_storage(&storage_key(FORWARD_LOCK_STORAGE_PREFIX, token_id))
+        .and_then(|value| value.to_value().ok())
+        .is_some_and(|locked_at| is_lock_active(locked_at, now))
+}
+
+/// Stores the timestamp when the forwarding of the token has started. `None` releases the lock.
+pub fn set_forward_lock<I: IO>(io: &mut I, token_id: &AccountId, locked_at: Option<u64>) {
+    let key = storage_key(FORWARD_LOCK_STORAGE_PREFIX, token_id);
+
+    if let Some(locked_at) = locked_at {
+        io.write_borsh(&key, &locked_at);
+    } else {
+        io.remove_storage(&key);
+    }
+}
+
+/// Returns `true` if the lock taken at the `locked_at` timestamp hasn't expired yet.
+pub const fn is_lock_active(locked_at: u64, now: u64) -> bool {
+    now.saturating_sub(locked_at) < FORWARD_LOCK_TIMEOUT
+}
+
+/// Returns the number of the tracked tokens, whose balances are forwarded by the `forward_all`.
+pub fn tracked_tokens_len<I: IO>(io: &I) -> u32 {
+    io.read_storage(TRACKED_TOKENS_LEN_STORAGE_KEY)
+        .and_then(|value| value.to_value().ok())
+        .unwrap_or_default()
+}
+
+/// Returns the tracked token at the index.
+pub fn tracked_token<I: IO>(io: &I, idx: u32) -> Option<AccountId> {
+    io.read_storage(&tracked_token_key(idx))
+        .and_then(|value| value.to_value().ok())
+}
+
+/// Returns the page of the tracked tokens starting from the index.
+pub fn tracked_tokens<I: IO>(io: &I, from_idx: u32) -> AccountList {
+    AccountList(
+        (from_idx..tracked_tokens_len(io))
+            .filter_map(|idx| tracked_token(io, idx))
+            .take(TRACKED_TOKENS_PAGE_LEN)
+            .collect(),
+    )
+}
+
+/// Adds the token to the tracked tokens. Does nothing if the token is already tracked or
+/// the max number of the tracked tokens has been reached.
+pub fn track_token<I: IO>(io: &mut I, token_id: &AccountId) {
+    let index_key = storage_key(TRACKED_TOKEN_INDEX_STORAGE_PREFIX, token_id);
+    let len = tracked_tokens_len(io);
+
+    if len >= MAX_TRACKED_TOKENS || io.storage_has_key(&index_key) {
+        return;
+    }
+
+    io.write_borsh(&index_key, &len);
+    io.write_borsh(&tracked_token_key(len), token_id);
+    io.write_borsh(TRACKED_TOKENS_LEN_STORAGE_KEY, &(len + 1));
+}
+
+/// Removes the token from the tracked tokens. The last tracked token takes its index.
+pub fn untrack_token<I: IO>(io: &mut I, token_id: &AccountId) {
+    let index_key = storage_key(TRACKED_TOKEN_INDEX_STORAGE_PREFIX, token_id);
+    let Some(idx) = io
+        .read_storage(&index_key)
+        .and_then(|value| value.to_value::<u32>().ok())
+    else {
+        return;
+    };
+    let last_idx = tracked_tokens_len(io).saturating_sub(1);
+
+    if idx != last_idx {
+        if let Some(last_token_id) = tracked_token(io, last_idx) {
+            io.write_borsh(&tracked_token_key(idx), &last_token_id);
+            io.write_borsh(
+                &storage_key(TRACKED_TOKEN_INDEX_STORAGE_PREFIX, &last_token_id),
+                &idx,
+            );
+        }
+    }
+
+    io.remove_storage(&tracked_token_key(last_idx));
+    io.remove_storage(&index_key);
+
+    if last_idx == 0 {
+        io.remove_storage(TRACKED_TOKENS_LEN_STORAGE_KEY);
+    } else {
+        io.write_borsh(TRACKED_TOKENS_LEN_STORAGE_KEY, &last_idx);
+    }
+}
+
+fn tracked_token_key(idx: u32) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend(TRACKED_TOKEN_STORAGE_PREFIX.iter().copied());
+    key.extend(idx.to_le_bytes());
+    key
+}
+
+/// Returns the index of the tracked token the next `forward_all` starts from.
+pub fn forward_all_cursor<I: IO>(io: &I) -> u32 {
+    io.read_storage(FORWARD_ALL_CURSOR_STORAGE_KEY)
+        .and_then(|value| value.to_value().ok())
+        .unwrap_or_default()
+}
+
+/// Stores the index of the tracked token the next `forward_all` starts from.
+pub fn set_forward_all_cursor<I: IO>(io: &mut I, idx: u32) {
+    io.write_borsh(FORWARD_ALL_CURSOR_STORAGE_KEY, &idx);
+}
+
+/// Returns the index of the tracked token forwarded at the offset from the cursor, so the
+/// tokens which haven't fit into the gas of the previous `forward_all` go first.
+pub const fn forward_all_index(cursor: u32, offset: u32, len: u32) -> u32 {
+    (cursor % len + offset) % len
+}
+
+/// Returns the minimum amount of the token, which could be forwarded.
+pub fn min_forward_amount<I: IO>(io: &I, token_id: &AccountId) -> u128 {
+    read_amount(io, MIN_FORWARD_AMOUNT_STORAGE_PREFIX, token_id)
+}
+
+/// Stores the minimum amount of the token, which could be forwarded. Zero removes the record.
+pub fn set_min_forward_amount<I: IO>(io: &mut I, token_id: &AccountId, amount: u128) {
+    write_amount(io, MIN_FORWARD_AMOUNT_STORAGE_PREFIX, token_id, amount);
+}
+
+/// Returns the maximum amount of the token, which could be forwarded at once, or zero if
+/// the amount isn't limited.
+pub fn max_forward_amount<I: IO>(io: &I, token_id: &AccountId) -> u128 {
+    read_amount(io, MAX_FORWARD_AMOUNT_STORAGE_PREFIX, token_id)
+}
+
+/// Stores the maximum amount of the token, which could be forwarded at once. Zero removes
+/// the record.
+pub fn set_max_forward_amount<I: IO>(io: &mut I, token_id: &AccountId, amount: u128) {
+    write_amount(io, MAX_FORWARD_AMOUNT_STORAGE_PREFIX, token_id, amount);
+}
+
+/// Returns the amount reduced to the maximum amount, so the rest is forwarded by the next
+/// forwardings. The zero maximum doesn't limit the amount.
+pub const fn capped_amount(amount: u128, max_amount: u128) -> u128 {
+    if max_amount > 0 && amount > max_amount {
+        max_amount
+    } else {
+        amount
+    }
+}
+
+/// Returns the format of the `msg` of the `ft_transfer_call` to the target network.
+pub fn msg_format<I: IO>(io: &I, target_network: &AccountId) -> MsgFormat {
+    io.read_storage(&storage_key(MSG_FORMAT_STORAGE_PREFIX, target_network))
+        .and_then(|value| value.to_value().ok())
+        .unwrap_or_default()
+}
+
+/// Stores the format of the `msg` for the target network. The default format removes the record.
+pub fn set_msg_format<I: IO>(io: &mut I, target_network: &AccountId, format: MsgFormat) {
+    let key = storage_key(MSG_FORMAT_STORAGE_PREFIX, target_network);
+
+    if format == MsgFormat::default() {
+        io.remove_storage(&key);
+    } else {
+        io.write_borsh(&key, &format);
+    }
+}
+
+/// Returns `true` if the target network uses NEAR as its base token, so the wNEAR is unwrapped
+/// and deposited as the base token instead of the ERC-20.
+pub fn is_near_base_token<I: IO>(io: &I, target_network: &AccountId) -> bool {
+    io.read_storage(&storage_key(NEAR_BASE_TOKEN_STORAGE_PREFIX, target_network))
+        .is_some()
+}
+
+/// Stores whether the target network uses NEAR as its base token. `false` removes the record.
+pub fn set_near_base_token<I: IO>(io: &mut I, target_network: &AccountId, is_enabled: bool) {
+    let key = storage_key(NEAR_BASE_TOKEN_STORAGE_PREFIX, target_network);
+
+    if is_enabled {
+        io.write_borsh(&key, &is_enabled);
+    } else {
+        io.remove_storage(&key);
+    }
+}
+
+fn read_amount<I: IO>(io: &I, prefix: &[u8], token_id: &AccountId) -> u128 {
+    io.read_storage(&storage_key(prefix, token_id))
+        .and_then(|value| value.to_value().ok())
+        .unwrap_or_default()
+}
+
+fn write_amount<I: IO>(io: &mut I, prefix: &[u8], token_id: &AccountId, amount: u128) {
+    let key = storage_key(prefix, token_id);
+
+    if amount == 0 {
+        io.remove_storage(&key);
+    } else {
+        io.write_borsh(&key, &amount);
+    }
+}
+
+/// Returns the account of the NFT bridge, which the received NFTs are transferred to.
+pub fn nft_bridge<I: IO>(io: &I) -> Option<AccountId> {
+    io.read_storage(NFT_BRIDGE_STORAGE_KEY)
+        .and_then(|value| value.to_value().ok())
+}
+
+/// Stores the account of the NFT bridge. `None` removes the bridge, so the received NFTs
+/// are returned to the senders.
+pub fn set_nft_bridge<I: IO>(io: &mut I, nft_bridge_id: Option<&AccountId>) {
+    if let Some(nft_bridge_id) = nft_bridge_id {
+        io.write_borsh(NFT_BRIDGE_STORAGE_KEY, nft_bridge_id);
+    } else {
+        io.remove_storage(NFT_BRIDGE_STORAGE_KEY);
+    }
+}
+
+/// Returns the NEAR account, which the tokens are transferred to instead of the target
+/// address on the target network.
+pub fn near_receiver<I: IO>(io: &I) -> Option<AccountId> {
+    io.read_storage(NEAR_RECEIVER_STORAGE_KEY)
+        .and_then(|value| value.to_value().ok())
+}
+
+/// Stores the NEAR account the tokens are transferred to. `None` restores the forwarding to
+/// the target address on the target network.
+pub fn set_near_receiver<I: IO>(io: &mut I, receiver_id: Option<&AccountId>) {
+    if let Some(receiver_id) = receiver_id {
+        io.write_borsh(NEAR_RECEIVER_STORAGE_KEY, receiver_id);
+    } else {
+        io.remove_storage(NEAR_RECEIVER_STORAGE_KEY);
+    }
+}
+
+/// Returns the lock contract of the bridge to Ethereum, which the tokens are transferred to
+/// if the target address is the address on Ethereum.
+pub fn eth_bridge<I: IO>(io: &I) -> Option<AccountId> {
+    io.read_storage(ETH_BRIDGE_STORAGE_KEY)
+        .and_then(|value| value.to_value().ok())
+}
+
+/// Stores the lock contract of the bridge to Ethereum. `None` restores the forwarding to
+/// the target network.
+pub fn set_eth_bridge<I: IO>(io: &mut I, bridge_id: Option<&AccountId>) {
+    if let Some(bridge_id) = bridge_id {
+        io.write_borsh(ETH_BRIDGE_STORAGE_KEY, bridge_id);
+    } else {
+        io.remove_storage(ETH_BRIDGE_STORAGE_KEY);
+    }
+}
+
+/// Returns the NEAR account, which the tokens withdrawn from the target network to
+/// the forwarder are paid out to.
+pub fn withdraw_beneficiary<I: IO>(io: &I) -> Option<AccountId> {
+    io.read_storage(WITHDRAW_BENEFICIARY_STORAGE_KEY)
+        .and_then(|value| value.to_value().ok())
+}
+
+/// Stores the beneficiary of the withdrawals. `None` disables the paying out, so
+/// the withdrawn tokens are kept in the forwarder.
+pub fn set_withdraw_beneficiary<I: IO>(io: &mut I, beneficiary_id: Option<&AccountId>) {
+    if let Some(beneficiary_id) = beneficiary_id {
+        io.write_borsh(WITHDRAW_BENEFICIARY_STORAGE_KEY, beneficiary_id);
+    } else {
+        io.remove_storage(WITHDRAW_BENEFICIARY_STORAGE_KEY);
+    }
+}
+
+/// Returns the nonce of the next change of the target address authorized by the signature,
+/// so the signature couldn't be replayed.
+pub fn address_nonce<I: IO>(io: &I) -> u64 {
+    io.read_storage(ADDRESS_NONCE_STORAGE_KEY)
+        .and_then(|value| value.to_value().ok())
+        .unwrap_or_default()
+}
+
+/// Stores the nonce of the next change of the target address authorized by the signature.
+pub fn set_address_nonce<I: IO>(io: &mut I, nonce: u64) {
+    io.write_borsh(ADDRESS_NONCE_STORAGE_KEY, &nonce);
+}
+
+/// Returns `true` if the forwarding is paused.
+pub fn is_paused<I: IO>(io: &I) -> bool {
+    io.storage_has_key(PAUSED_STORAGE_KEY)
+}
+
+/// Pauses or resumes the forwarding. The paused state is stored as the presence of the key.
+pub fn set_paused<I: IO>(io: &mut I, paused: bool) {
+    if paused {
+        io.write_storage(PAUSED_STORAGE_KEY, &[1]);
+    } else {
+        io.remove_storage(PAUSED_STORAGE_KEY);
+    }
+}
+
+/// The share of the forwarded tokens sent to the address.
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct Split {
+    pub address: Address,
+    /// The weight of the split in basis points.
+    pub weight: u16,
+}
+
+/// The addresses the forwarded tokens are split between instead of the target address.
+#[derive(Default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct Splits(ArrayVec<Split, MAX_SPLITS>);
+
+impl Splits {
+    pub fn load<I: IO>(io: &I) -> Self {
+        io.read_storage(SPLITS_STORAGE_KEY)
+            .and_then(|value| value.to_value().ok())
+            .unwrap_or_default()
+    }
+
+    /// Stores the splits. The empty splits remove the record, so the tokens are forwarded to
+    /// the target address.
+    pub fn save<I: IO>(&self, io: &mut I) {
+        if self.0.is_empty() {
+            io.remove_storage(SPLITS_STORAGE_KEY);
+        } else {
+            io.write_borsh(SPLITS_STORAGE_KEY, self);
+        }
+    }
+
+    /// Returns `true` if the splits are empty or their weights aren't zero and sum up to
+    /// 100% and the addresses aren't zero.
+    pub fn is_valid(&self) -> bool {
+        self.0.is_empty()
+            || self
+                .0
+                .iter()
+                .all(|split| split.weight > 0 && split.address.0 != [0; 20])
+                && self
+                    .0
+                    .iter()
+                    .try_fold(0u16, |sum, split| sum.checked_add(split.weight))
+                    == Some(TOTAL_WEIGHT)
+    }
+
+    /// Returns the addresses and the amounts of the tokens sent to them. The rounding
+    /// remainder goes to the last split. The whole amount is sent to the target address if
+    /// there are no splits.
+    pub fn destinations(
+        &self,
+        target_address: Address,
+        amount: u128,
+    ) -> ArrayVec<(Address, u128), MAX_SPLITS> {
+        let mut result = ArrayVec::new();
+        let Some((last, splits)) = self.0.split_last() else {
+            result.push((target_address, amount));
+            return result;
+        };
+        let mut remaining = amount;
+
+        for split in splits {
+            let share = mul_div(amount, u128::from(split.weight), u128::from(TOTAL_WEIGHT));
+            remaining -= share;
+            result.push((split.address, share));
+        }
+
+        result.push((last.address, remaining));
+        result
+    }
+}
+
+impl BorshSerialize for Splits {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.as_slice().serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Splits {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let len = u32::deserialize_reader(reader)?;
+        let mut splits = ArrayVec::new();
+
+        for _ in 0..len {
+            splits
+                .try_push(Split::deserialize_reader(reader)?)
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+        }
+
+        Ok(Self(splits))
+    }
+}
+
+/// Reads the input into the buffer as the UTF-8 string, so the input longer than the
+/// buffer of the `Vec` could be read.
+pub fn read_input_str<'a, I: IO>(io: &I, buf: &'a mut [u8]) -> Result<&'a str, ContractError> {
+    let input = io.read_input();
+    let buf = buf
+        .get_mut(..input.len())
+        .ok_or(ContractError::TooLongInput)?;
+    input.copy_to_slice(buf);
+
+    core::str::from_utf8(buf).map_err(|_| ContractError::BadUtf8String)
+}
+
+/// Returns the value of the string field of the JSON object. The values with the escaped
+/// characters aren't supported.
+pub fn json_string_field<'a>(json: &'a str, field: &str) -> Option<&'a str> {
+    let mut rest = json;
+
+    while let Some(idx) = rest.find(field) {
+        let (before, after) = rest.split_at(idx);
+        let after = &after[field.len()..];
+
+        if before.ends_with('"') {
+            let value = after
+                .strip_prefix('"')
+                .map(str::trim_start)
+                .and_then(|value| value.strip_prefix(':'))
+                .map(str::trim_start)
+                .and_then(|value| value.strip_prefix('"'));
+
+            if let Some(value) = value {
+                let value = &value[..value.find('"')?];
+                return (!value.contains('\\')).then_some(value);
+            }
+        }
+
+        rest = after;
+    }
+
+    None
+}
+
+/// The reason why the forwarding has failed.
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub enum FailureReason {
+    /// The `ft_transfer_call` to the target network has failed.
+    TransferFailed,
+    /// The target network has refunded the tokens.
+    Refunded,
+    /// The target network has used a part of the tokens and refunded the rest.
+    PartiallyRefunded,
+}
+
+impl FailureReason {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::TransferFailed => "transfer_failed",
+            Self::Refunded => "refunded",
+            Self::PartiallyRefunded => "partially_refunded",
+        }
+    }
+}
+
+/// The format of the `msg` of the `ft_transfer_call`, which is expected by the target network.
+#[derive(Default, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+pub enum MsgFormat {
+    /// The hex of the target address without the `0x` prefix.
+    #[default]
+    Address,
+    /// The hex of the target address and the fee of the forwarding separated by the colon.
+    AddressFee,
+    /// The JSON object with the hex of the target address in the `recipient` field.
+    Json,
+    /// The message of the eth-connector, which deposits ETH as the base asset: the relayer,
+    /// the colon, the hex of the relayer fee as 32 bytes and the hex of the target address.
+    /// The receiver of the transfer is the relayer and the relayer fee is zero, so the whole
+    /// amount is deposited.
+    EthConnector,
+}
+
+/// The failed forwarding, which should be retried.
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct RetryEntry {
+    pub amount: u128,
+    pub reason: FailureReason,
+    /// The block timestamp in nanoseconds when the forwarding has failed.
+    pub timestamp: u64,
+}
+
+/// The cumulative statistics of the forwardings of the token.
+#[derive(Default, Clone, Copy, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct ForwardStats {
+    /// The number of the forwardings.
+    pub count: u64,
+    /// The amount of the tokens accepted by the target network.
+    pub total_forwarded: u128,
+    /// The amount of the tokens charged as the fee.
+    pub total_fees: u128,
+    /// The block timestamp in nanoseconds of the last forwarding.
+    pub last_timestamp: u64,
+}
+
+impl ForwardStats {
+    pub fn load<I: IO>(io: &I, token_id: &AccountId) -> Self {
+        io.read_storage(&storage_key(STATS_STORAGE_PREFIX, token_id))
+            .and_then(|value| value.to_value().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save<I: IO>(&self, io: &mut I, token_id: &AccountId) {
+        io.write_borsh(&storage_key(STATS_STORAGE_PREFIX, token_id), self);
+    }
+
+    /// Adds the forwarded amount and the fee. The splits of the same forwarding are counted
+    /// once, so only the first one is the new forwarding.
+    pub fn record(&mut self, forwarded: u128, fee: u128, timestamp: u64, is_new: bool) {
+        if is_new {
+            self.count = self.count.saturating_add(1);
+        }
+
+        self.total_forwarded = self.total_forwarded.saturating_add(forwarded);
+        self.total_fees = self.total_fees.saturating_add(fee);
+        self.last_timestamp = timestamp;
+    }
+}
+
+/// The failed forwardings of the token. The oldest entry is dropped if the queue is full.
+#[derive(Default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct RetryQueue(ArrayVec<RetryEntry, MAX_RETRIES>);
+
+impl RetryQueue {
+    pub fn load<I: IO>(io: &I, token_id: &AccountId) -> Self {
+        io.read_storage(&storage_key(RETRY_STORAGE_PREFIX, token_id))
+            .and_then(|value| value.to_value().ok())
+            .unwrap_or_default()
+    }
+
+    /// Stores the queue of the token. The empty queue removes the record.
+    pub fn save<I: IO>(&self, io: &mut I, token_id: &AccountId) {
+        let key = storage_key(RETRY_STORAGE_PREFIX, token_id);
+
+        if self.is_empty() {
+            io.remove_storage(&key);
+        } else {
+            io.write_borsh(&key, self);
+        }
+    }
+
+    pub fn push(&mut self, entry: RetryEntry) {
+        if self.0.is_full() {
+            self.0.remove(0);
+        }
+
+        self.0.push(entry);
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl BorshSerialize for RetryQueue {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.as_slice().serialize(writer)
+    }
+}
+
+impl BorshDeserialize for RetryQueue {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let len = u32::deserialize_reader(reader)?;
+        let mut entries = ArrayVec::new();
+
+        for _ in 0..len {
+            entries
+                .try_push(RetryEntry::deserialize_reader(reader)?)
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+        }
+
+        Ok(Self(entries))
+    }
+}
+
+fn storage_key(prefix: &[u8], token_id: &AccountId) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend(prefix.iter().copied());
+    key.extend(token_id.as_bytes().iter().copied());
+    key
+}
+
+/// The arguments of the `record_and_calculate_fees` of the fees contract.
+#[derive(BorshSerialize)]
+pub struct FeesParams<'a> {
+    pub amount: u128,
+    pub token_id: &'a AccountId,
+    pub target_network: &'a AccountId,
+    pub target_address: Address,
+}
+
+/// The arguments of the `estimate_forward`.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct EstimateParams {
+    /// The NEP-141 token or `near` for the native NEAR.
+    pub token_id: AccountId,
+    pub amount: u128,
+}
+
+/// The arguments of the `withdraw`.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct WithdrawParams {
+    pub token_id: AccountId,
+    /// The amount of the token withdrawn from the target network.
+    pub amount: u128,
+}
+
+/// The arguments of the `record_and_calculate_native_fees` and the `calculate_native_fees` of
+/// the fees contract.
+#[derive(BorshSerialize)]
+pub struct NativeFeesParams {
+    pub amount: u128,
+    pub target_address: Address,
+}
+
+/// The result of the `calculate_fees` of the fees contract. The net amount and the applied
+/// rule are not needed by the forwarder and aren't read.
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct FeesResult {
+    pub fee: u128,
+    pub fee_recipient: AccountId,
+    /// `true` if the amount is out of the allowed range of the token or the target address
+    /// is invalid, so the forwarding should be aborted.
+    pub rejected: bool,
+    /// `true` if the target address is invalid.
+    pub invalid_address: bool,
+    /// The percent of the fee if the fee is the pure percentage of the amount, so it could
+    /// be cached.
+    pub percent: Option<u64>,
+}
+
+impl BorshDeserialize for FeesResult {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let fee = u128::deserialize_reader(reader)?;
+        let _net_amount = u128::deserialize_reader(reader)?;
+        let fee_recipient = AccountId::deserialize_reader(reader)?;
+        skip_string(reader)?;
+        // The flags are optional, so the results without them are still valid.
+        let rejected = read_optional_flag(reader)?;
+        let invalid_address = read_optional_flag(reader)?;
+        let percent = read_optional(reader)?;
+
+        Ok(Self {
+            fee,
+            fee_recipient,
+            rejected,
+            invalid_address,
+            percent,
+        })
+    }
+}
+
+/// Reads the borsh serialized bool, which is `false` if the input has ended.
+fn read_optional_flag<R: io::Read>(reader: &mut R) -> io::Result<bool> {
+    let mut flag = [0; 1];
+
+    match reader.read(&mut flag)? {
+        0 => Ok(false),
+        _ => bool::try_from_slice(&flag),
+    }
+}
+
+/// Reads the borsh serialized option, which is `None` if it's missing at the end of the input.
+fn read_optional<R: io::Read, T: BorshDeserialize>(reader: &mut R) -> io::Result<Option<T>> {
+    let mut tag = [0; 1];
+
+    match reader.read(&mut tag)? {
+        0 => Ok(None),
+        _ => match tag[0] {
+            0 => Ok(None),
+            1 => T::deserialize_reader(reader).map(Some),
+            _ => Err(io::Error::from(io::ErrorKind::InvalidData)),
+        },
+    }
+}
+
+/// Skips the borsh serialized string without allocating it.
+fn skip_string<R: io::Read>(reader: &mut R) -> io::Result<()> {
+    let len = u32::deserialize_reader(reader)?;
+    let mut remaining =
+        usize::try_from(len).map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+    let mut buf = [0; 32];
+
+    while remaining > 0 {
+        let len = remaining.min(buf.len());
+        reader.read_exact(&mut buf[..len])?;
+        remaining -= len;
+    }
+
+    Ok(())
+}
+
+/// Returns the amount to forward: the requested amount if it's not zero and doesn't exceed
+/// the balance or the whole balance if the amount isn't requested.
+pub const fn forward_amount(balance: u128, requested: Option<u128>) -> Option<u128> {
+    match requested {
+        Some(amount) if amount == 0 || amount > balance => None,
+        Some(amount) => Some(amount),
+        None => Some(balance),
+    }
+}
+
+impl FeesResult {
+    pub fn from_slice(mut bytes: &[u8]) -> Result<Self, ContractError> {
+        Self::deserialize(&mut bytes).map_err(|_| ContractError::BorshDeserializeError)
+    }
+}
+
+/// The input of the `forward`. The amount and the memo are optional, so the arguments with
+/// the token id only are still valid.
+#[derive(BorshSerialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct ForwardArgs {
+    pub token_id: AccountId,
+    /// The amount to forward. The whole balance is forwarded if it's not set.
+    pub amount: Option<u128>,
+    /// The memo passed to the transfers of the forwarding and its events.
+    pub memo: Option<Memo>,
+}
+
+impl BorshDeserialize for ForwardArgs {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let token_id = AccountId::deserialize_reader(reader)?;
+        let amount = read_optional(reader)?;
+        let memo = read_optional(reader)?;
+
+        Ok(Self {
+            token_id,
+            amount,
+            memo,
+        })
+    }
+}
+
+/// The parameters of the forwarding passed to its callbacks.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct ForwardParams {
+    pub token_id: AccountId,
+    /// The deposit in yoctoNEAR attached to the `forward` above the required one yocto, which
+    /// pays the fee instead of the forwarded tokens.
+    pub near_fee: u128,
+    /// The amount to forward. The whole balance is forwarded if it's not set.
+    pub amount: Option<u128>,
+    /// The account which has transferred the tokens by the `ft_transfer_call`.
+    pub sender_id: Option<AccountId>,
+    /// The memo passed to the transfers of the forwarding and its events.
+    pub memo: Option<Memo>,
+}
+
+/// The memo of the forwarding, which is passed to the transfers and the events, so
+/// the exchanges could correlate the deposits with their records. It's embedded into the JSON
+/// as is, so the quotes, the backslashes and the control characters aren't allowed.
+#[derive(Clone, Copy)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct Memo(ArrayString<MAX_MEMO_LEN>);
+
+impl Memo {
+    pub fn new(memo: &str) -> Option<Self> {
+        if memo
+            .bytes()
+            .any(|c| c == b'"' || c == b'\\' || c.is_ascii_control())
+        {
+            return None;
+        }
+
+        ArrayString::from(memo).ok().map(Self)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl BorshSerialize for Memo {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.as_str().serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Memo {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let len = u32::deserialize_reader(reader)? as usize;
+
+        if len > MAX_MEMO_LEN {
+            return Err(invalid_data());
+        }
+
+        let mut buf = [0; MAX_MEMO_LEN];
+        reader.read_exact(&mut buf[..len])?;
+
+        core::str::from_utf8(&buf[..len])
+            .ok()
+            .and_then(Self::new)
+            .ok_or_else(invalid_data)
+    }
+}
+
+fn invalid_data() -> io::Error {
+    io::Error::from(io::ErrorKind::InvalidData)
+}
+
+/// The EVM calldata, which the engine calls the target address with after the deposit,
+/// e.g. to stake or swap the deposited tokens on arrival.
+#[derive(Default, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct XccPayload(pub ArrayVec<u8, MAX_XCC_PAYLOAD_LEN>);
+
+impl XccPayload {
+    pub fn load<I: IO>(io: &I) -> Option<Self> {
+        io.read_storage(XCC_PAYLOAD_STORAGE_KEY)
+            .and_then(|value| value.to_value().ok())
+    }
+
+    /// Stores the payload. `None` restores the plain deposits.
+    pub fn save<I: IO>(payload: Option<&Self>, io: &mut I) {
+        if let Some(payload) = payload {
+            io.write_borsh(XCC_PAYLOAD_STORAGE_KEY, payload);
+        } else {
+            io.remove_storage(XCC_PAYLOAD_STORAGE_KEY);
+        }
+    }
+}
+
+impl BorshSerialize for XccPayload {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.as_slice().serialize(writer)
+    }
+}
+
+impl BorshDeserialize for XccPayload {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let len = u32::deserialize_reader(reader)? as usize;
+
+        if len > MAX_XCC_PAYLOAD_LEN {
+            return Err(invalid_data());
+        }
+
+        let mut buf = [0; MAX_XCC_PAYLOAD_LEN];
+        reader.read_exact(&mut buf[..len])?;
+
+        ArrayVec::try_from(&buf[..len])
+            .map(Self)
+            .map_err(|_| invalid_data())
+    }
+}
+
+/// The tokens forwarded by the `forward_batch`, unregistered by the `close`, tracked or
+/// refused by the forwarder or the keepers allowed to call the `forward`.
+#[derive(Default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct AccountList(pub ArrayVec<AccountId, MAX_LISTED_ACCOUNTS>);
+
+impl AccountList {
+    /// Returns the keepers allowed to call the `forward`. Anyone could call it if the list
+    /// is empty.
+    pub fn load_keepers<I: IO>(io: &I) -> Self {
+        Self::load(io, KEEPERS_STORAGE_KEY)
+    }
+
+    /// Stores the keepers. The empty list removes the record, so the `forward` becomes
+    /// permissionless.
+    pub fn save_keepers<I: IO>(&self, io: &mut I) -> Result<(), ContractError> {
+        self.save(io, KEEPERS_STORAGE_KEY)
+    }
+
+    /// Returns the blacklisted tokens, which are refused by the `ft_on_transfer` and aren't
+    /// forwarded, e.g. the scam tokens airdropped to the forwarder.
+    pub fn load_blacklist<I: IO>(io: &I) -> Self {
+        Self::load(io, BLACKLIST_STORAGE_KEY)
+    }
+
+    /// Stores the blacklisted tokens. The empty list removes the record.
+    pub fn save_blacklist<I: IO>(&self, io: &mut I) -> Result<(), ContractError> {
+        self.save(io, BLACKLIST_STORAGE_KEY)
+    }
+
+    fn load<I: IO>(io: &I, key: &[u8]) -> Self {
+        io.read_storage(key)
+            .and_then(|value| value.to_value().ok())
+            .unwrap_or_default()
+    }
+
+    fn save<I: IO>(&self, io: &mut I, key: &[u8]) -> Result<(), ContractError> {
+        if self.0.is_empty() {
+            io.remove_storage(key);
+        } else {
+            // The list longer than the buffer couldn't be read back, so its serialization fails.
+            let bytes = crate::types::to_borsh(self)?;
+            io.write_storage(key, &bytes);
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the same account is listed more than once.
+    pub fn has_duplicates(&self) -> bool {
+        self.0
+            .iter()
+            .enumerate()
+            .any(|(idx, account_id)| self.0[..idx].contains(account_id))
+    }
+
+    pub fn contains(&self, account_id: &AccountId) -> bool {
+        self.0.contains(account_id)
+    }
+}
+
+impl BorshSerialize for AccountList {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.as_slice().serialize(writer)
+    }
+}
+
+impl BorshDeserialize for AccountList {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let len = u32::deserialize_reader(reader)?;
+        let mut tokens = ArrayVec::new();
+
+        for _ in 0..len {
+            tokens
+                .try_push(AccountId::deserialize_reader(reader)?)
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+        }
+
+        Ok(Self(tokens))
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct FinishForwardParams {
+    pub amount: u128,
+    pub token_id: AccountId,
+    pub promise_idx: u64,
+    /// `true` if the fee of the token should be cached. The fee paid by the attached NEAR and
+    /// the fee of the native NEAR aren't cached.
+    pub cache_fee: bool,
+    pub memo: Option<Memo>,
+    /// `true` for the native NEAR, whose fee is calculated by the
+    /// `record_and_calculate_native_fees`.
+    pub is_native: bool,
+    /// `true` if the fee has been calculated by the fallback fees contract, so it isn't
+    /// requested again.
+    pub is_fallback: bool,
+}
+
+/// The arguments of the `transfer_tokens_callback`, which transfers the net amount to the
+/// target network and the fee to the fee recipient after their storage registration.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct TransferParams {
+    pub amount: u128,
+    pub fee: u128,
+    pub fee_recipient: AccountId,
+    pub token_id: AccountId,
+    pub memo: Option<Memo>,
+}
+
+/// The arguments of the `sweep` and the `sweep_callback`.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct SweepParams {
+    pub token_id: AccountId,
+    pub receiver_id: AccountId,
+}
+
+/// The arguments of the `unregister_storage`.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct UnregisterStorageParams {
+    pub token_ids: AccountList,
+    /// `true` if the storage should be unregistered even if the forwarder holds the token,
+    /// e.g. the blacklisted one, whose balance is burned.
+    pub force: bool,
+}
+
+/// The arguments of the `set_min_forward_amount`.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct MinForwardAmountParams {
+    pub token_id: AccountId,
+    pub amount: u128,
+}
+
+/// The arguments of the `set_max_forward_amount`.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct MaxForwardAmountParams {
+    pub token_id: AccountId,
+    pub amount: u128,
+}
+
+/// The arguments of the `set_schedule`.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct ScheduleParams {
+    pub token_id: AccountId,
+    /// The minimum number of the hours between the forwardings. Zero removes the schedule.
+    pub interval_hours: u64,
+}
+
+/// The arguments of the `set_msg_format`.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct MsgFormatParams {
+    pub target_network: AccountId,
+    pub format: MsgFormat,
+}
+
+/// The arguments of the `set_near_base_token`.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct NearBaseTokenParams {
+    pub target_network: AccountId,
+    pub is_enabled: bool,
+}
+
+/// The arguments of the `resolve_forward_callback`. The `amount` is the amount transferred
+/// by the `ft_transfer_call` to the target address on the target network.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ResolveForwardParams {
+    pub amount: u128,
+    pub token_id: AccountId,
+    pub target_address: Address,
+    /// `true` for the first split, which resets the records of the previous forwarding.
+    pub is_first: bool,
+    /// `true` if the wNEAR has been unwrapped and deposited as the base token, so the failed
+    /// deposit refunds the native NEAR instead of the wNEAR.
+    pub is_unwrapped: bool,
+    pub memo: Option<Memo>,
+}
+
+/// The arguments of the `charge_fee_callback`, which transfers the fee after the successful
+/// forwarding.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct ChargeFeeParams {
+    pub amount: u128,
+    pub fee: u128,
+    pub fee_recipient: AccountId,
+    pub token_id: AccountId,
+    pub memo: Option<Memo>,
+}
+
+/// Returns the arguments of the `ft_transfer_call` to the target network. The payload turns
+/// the `msg` into the call-with-deposit message of the engine, which calls the target address
+/// with it after the deposit, in any format except the one of the eth-connector.
+pub fn ft_transfer_call_args(
+    receiver_id: &AccountId,
+    amount: u128,
+    address: Address,
+    fee: u128,
+    format: MsgFormat,
+    memo: Option<&Memo>,
+    payload: Option<&XccPayload>,
+) -> Option<Vec<u8>> {
+    let mut address_hex = ArrayString::<40>::new();
+
+    for c in hex::BytesToHexIter::new(address.0.iter().copied()) {
+        address_hex.push(c);
+    }
+
+    let mut result = ArrayString::<VEC_CAPACITY>::new();
+
+    result.try_push_str(r#"{"receiver_id":""#).ok()?;
+    result.try_push_str(receiver_id.as_str()).ok()?;
+    result.try_push_str(r#"","amount":""#).ok()?;
+    result.try_push_str(amount_to_str(amount).as_str()).ok()?;
+
+    if let Some(memo) = memo {
+        result.try_push_str(r#"","memo":""#).ok()?;
+        result.try_push_str(memo.as_str()).ok()?;
+    }
+
+    result.try_push_str(r#"","msg":""#).ok()?;
+
+    match (format, payload) {
+        (MsgFormat::EthConnector, _) => {
+            result.try_push_str(receiver_id.as_str()).ok()?;
+            result.try_push(':').ok()?;
+            result.try_push_str(ZERO_CONNECTOR_FEE_HEX).ok()?;
+            result.try_push_str(&address_hex).ok()?;
+        }
+        (_, Some(payload)) => {
+            result.try_push_str(r#"{\"recipient\":\""#).ok()?;
+            result.try_push_str(&address_hex).ok()?;
+            result.try_push_str(r#"\",\"input\":\""#).ok()?;
+
+            for c in hex::BytesToHexIter::new(payload.0.iter().copied()) {
+                result.try_push(c).ok()?;
+            }
+
+            result.try_push_str(r#"\"}"#).ok()?;
+        }
+        (MsgFormat::Address, None) => result.try_push_str(&address_hex).ok()?,
+        (MsgFormat::AddressFee, None) => {
+            result.try_push_str(&address_hex).ok()?;
+            result.try_push(':').ok()?;
+            result.try_push_str(amount_to_str(fee).as_str()).ok()?;
+        }
+        (MsgFormat::Json, None) => {
+            result.try_push_str(r#"{\"recipient\":\""#).ok()?;
+            result.try_push_str(&address_hex).ok()?;
+            result.try_push_str(r#"\"}"#).ok()?;
+        }
+    }
+
+    result.try_push_str(r#""}"#).ok()?;
+
+    Vec::try_from(result.as_bytes()).ok()
+}
+
+pub fn nft_transfer_call_args(
+    receiver_id: &AccountId,
+    token_id: &str,
+    address: Address,
+) -> Option<Vec<u8>> {
+    let mut result = ArrayString::<256>::new();
+
+    result.try_push_str(r#"{"receiver_id":""#).ok()?;
+    result.try_push_str(receiver_id.as_str()).ok()?;
+    result.try_push_str(r#"","token_id":""#).ok()?;
+    result.try_push_str(token_id).ok()?;
+    result.try_push_str(r#"","msg":""#).ok()?;
+
+    for c in hex::BytesToHexIter::new(address.0.iter().copied()) {
+        result.try_push(c).ok()?;
+    }
+
+    result.try_push_str(r#""}"#).ok()?;
+
+    Vec::try_from(result.as_bytes()).ok()
+}
+
+pub fn nft_transfer_args(receiver_id: &AccountId, token_id: &str) -> Option<Vec<u8>> {
+    let mut result = ArrayString::<256>::new();
+
+    result.try_push_str(r#"{"receiver_id":""#).ok()?;
+    result.try_push_str(receiver_id.as_str()).ok()?;
+    result.try_push_str(r#"","token_id":""#).ok()?;
+    result.try_push_str(token_id).ok()?;
+    result.try_push_str(r#""}"#).ok()?;
+
+    Vec::try_from(result.as_bytes()).ok()
+}
+
+/// The NEP-297 event of the forwarder with the string fields of the data.
+pub fn event_json(event: &str, fields: &[(&str, &str)]) -> Option<ArrayString<512>> {
+    let mut result = ArrayString::<512>::new();
+
+    result
+        .try_push_str(r#"EVENT_JSON:{"standard":"aurora-forwarder","version":"1.0.0","event":""#)
+        .ok()?;
+    result.try_push_str(event).ok()?;
+    result.try_push_str(r#"","data":[{"#).ok()?;
+
+    for (i, (key, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            result.try_push(',').ok()?;
+        }
+
+        result.try_push('"').ok()?;
+        result.try_push_str(key).ok()?;
+        result.try_push_str(r#"":""#).ok()?;
+        result.try_push_str(value).ok()?;
+        result.try_push('"').ok()?;
+    }
+
+    result.try_push_str("}]}").ok()?;
+
+    Some(result)
+}
+
+/// Returns the JSON object with the string fields. The `None` values are serialized as `null`.
+pub fn json_object(fields: &[(&str, Option<&str>)]) -> Option<ArrayString<512>> {
+    let mut result = ArrayString::<512>::new();
+
+    result.try_push('{').ok()?;
+
+    for (i, (key, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            result.try_push(',').ok()?;
+        }
+
+        result.try_push('"').ok()?;
+        result.try_push_str(key).ok()?;
+        result.try_push_str(r#"":"#).ok()?;
+
+        if let Some(value) = value {
+            result.try_push('"').ok()?;
+            result.try_push_str(value).ok()?;
+            result.try_push('"').ok()?;
+        } else {
+            result.try_push_str("null").ok()?;
+        }
+    }
+
+    result.try_push('}').ok()?;
+
+    Some(result)
+}
+
+/// Returns the hex of the address with the `0x` prefix.
+pub fn address_to_hex(address: Address) -> ArrayString<42> {
+    let mut result = ArrayString::<42>::new();
+    result.push_str("0x");
+    hex::BytesToHexIter::new(address.0.iter().copied()).for_each(|c| result.push(c));
+    result
+}
+
+/// The arguments of the `set_target_address_with_proof`: the new target address and
+/// the signature of the `address_proof_message` by the current one.
+#[derive(BorshDeserialize)]
+pub struct TargetAddressProofParams {
+    pub target: TargetAddress,
+    pub signature: [u8; 65],
+}
+
+/// Returns the EIP-191 personal message, which the current target address signs to authorize
+/// the change of the target address of the forwarder to the new one.
+pub fn address_proof_message(
+    forwarder_id: &AccountId,
+    new_address: Address,
+    nonce: u64,
+) -> Option<ArrayString<256>> {
+    let mut message = ArrayString::<192>::new();
+
+    message.try_push_str("Set the target address of ").ok()?;
+    message.try_push_str(forwarder_id.as_str()).ok()?;
+    message.try_push_str(" to ").ok()?;
+    message
+        .try_push_str(address_to_hex(new_address).as_str())
+        .ok()?;
+    message.try_push_str(" with nonce ").ok()?;
+    message
+        .try_push_str(amount_to_str(u128::from(nonce)).as_str())
+        .ok()?;
+
+    let mut result = ArrayString::<256>::new();
+
+    result.try_push_str("\x19Ethereum Signed Message:\n").ok()?;
+    result
+        .try_push_str(amount_to_str(message.len() as u128).as_str())
+        .ok()?;
+    result.try_push_str(&message).ok()?;
+
+    Some(result)
+}
+
+/// The borsh serialized string of the hex of the address with the optional `0x` prefix.
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Eq, Debug))]
+pub struct TargetAddress {
+    pub address: Address,
+    /// The hex of the address in the case it has been passed without the `0x` prefix.
+    pub hex: ArrayString<40>,
+}
+
+impl TargetAddress {
+    /// Returns the lowercase hex of the address, which the EIP-55 checksum is hashed from.
+    pub fn lowercase_hex(&self) -> ArrayString<40> {
+        let mut result = self.hex;
+        result.make_ascii_lowercase();
+        result
+    }
+
+    /// Returns `true` if the hex of the address is in one case or has the valid EIP-55
+    /// checksum. The `hash` is the keccak256 of the lowercase hex.
+    pub fn is_checksum_valid(&self, hash: &[u8; 32]) -> bool {
+        let bytes = self.hex.as_bytes();
+
+        if !bytes.iter().any(u8::is_ascii_uppercase) || !bytes.iter().any(u8::is_ascii_lowercase) {
+            return true;
+        }
+
+        bytes.iter().zip(0..).all(|(c, i)| {
+            let byte = hash[i / 2];
+            let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+
+            !c.is_ascii_alphabetic() || c.is_ascii_uppercase() == (nibble >= 8)
+        })
+    }
+}
+
+impl BorshDeserialize for TargetAddress {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let invalid_data = || io::Error::from(io::ErrorKind::InvalidData);
+        let len = u32::deserialize_reader(reader)?;
+        let mut buf = [0; 42];
+        let buf = usize::try_from(len)
+            .ok()
+            .and_then(|len| buf.get_mut(..len))
+            .ok_or_else(invalid_data)?;
+        reader.read_exact(buf)?;
+
+        let hex = core::str::from_utf8(buf).map_err(|_| invalid_data())?;
+        let hex = hex.strip_prefix("0x").unwrap_or(hex);
+        let address = <[u8; 20]>::from_hex(hex).map_err(|_| invalid_data())?;
+
+        Ok(Self {
+            address: Address(address),
+            hex: ArrayString::from(hex).map_err(|_| invalid_data())?,
+        })
+    }
+}
+
+pub fn ft_transfer_args(receiver_id: &AccountId, amount: u128, memo: Option<&Memo>) -> Vec<u8> {
+    let mut result = ArrayString::<256>::new();
+
+    result.push_str(r#"{"receiver_id":""#);
+    result.push_str(receiver_id.as_str());
+    result.push_str(r#"","amount":""#);
+    result.push_str(amount_to_str(amount).as_str());
+
+    if let Some(memo) = memo {
+        result.push_str(r#"","memo":""#);
+        result.push_str(memo.as_str());
+    }
+
+    result.push_str(r#""}"#);
+
+    Vec::try_from(result.as_bytes()).unwrap_or_default()
+}
+
+pub fn near_withdraw_args(amount: u128) -> Vec<u8> {
+    let mut result = ArrayString::<64>::new();
+
+    result.push_str(r#"{"amount":""#);
+    result.push_str(amount_to_str(amount).as_str());
+    result.push_str(r#""}"#);
+
+    Vec::try_from(result.as_bytes()).unwrap_or_default()
+}
+
+/// Returns the arguments of the `storage_unregister`. The forced unregistration burns
+/// the balance of the forwarder on the token.
+pub fn storage_unregister_args(force: bool) -> Vec<u8> {
+    let args: &[u8] = if force {
+        br#"{"force":true}"#
+    } else {
+        br#"{"force":false}"#
+    };
+
+    Vec::try_from(args).unwrap_or_default()
+}
+
+/// Returns the arguments of the `storage_withdraw`, which withdraws the whole available
+/// storage deposit.
+pub fn storage_withdraw_args() -> Vec<u8> {
+    Vec::try_from(b"{}".as_slice()).unwrap_or_default()
+}
+
+pub fn is_network_supported_args(network_id: &AccountId) -> Vec<u8> {
+    let mut result = ArrayString::<128>::new();
+
+    result.push_str(r#"{"network_id":""#);
+    result.push_str(network_id.as_str());
+    result.push_str(r#""}"#);
+
+    Vec::try_from(result.as_bytes()).unwrap_or_default()
+}
+
+pub fn storage_deposit_args(account_id: &AccountId) -> Vec<u8> {
+    let mut result = ArrayString::<128>::new();
+
+    result.push_str(r#"{"account_id":""#);
+    result.push_str(account_id.as_str());
+    result.push_str(r#"","registration_only":true}"#);
+
+    Vec::try_from(result.as_bytes()).unwrap_or_default()
+}
+
+pub fn ft_balance_args(account_id: &AccountId) -> Vec<u8> {
+    let mut result = ArrayString::<128>::new();
+
+    result.push_str(r#"{"account_id":""#);
+    result.push_str(account_id.as_str());
+    result.push_str(r#""}"#);
+
+    Vec::try_from(result.as_bytes()).unwrap_or_default()
+}
+
+pub fn amount_to_str(mut amount: u128) -> ArrayString<39> {
+    let mut len = 0;
+    let mut buf = ['0'; 39];
+
+    if amount == 0 {
+        buf[0] = core::char::from_digit(0, 10).unwrap();
+        len = 1;
+    } else {
+        while amount > 0 {
+            let digit = amount % 10;
+            buf[len] = core::char::from_digit(digit as u32, 10).unwrap();
+            amount /= 10;
+            len += 1;
+        }
+        buf[..len].reverse();
+    }
+
+    let mut result = ArrayString::<39>::new();
+    buf[..len].iter().for_each(|d| result.push(*d));
+
+    result
+}
+
+/// Returns the net amount and the fee, which could be transferred from the actual balance of
+/// the token. If the balance is lower than their sum, e.g. because the token has deducted
+/// a transfer tax or rebased, both are reduced proportionally.
+pub const fn verified_amounts(balance: u128, amount: u128, fee: u128) -> (u128, u128) {
+    let total = amount.saturating_add(fee);
+
+    if balance >= total {
+        return (amount, fee);
+    }
+
+    let fee = mul_div(fee, balance, total);
+    (balance - fee, fee)
+}
+
+/// Returns the NEAR fee of the attached deposit. The single yocto is the optional guard of
+/// the call rather than the fee, so the deposit without it is taken as the fee as a whole.
+pub const fn near_fee(attached_deposit: u128) -> u128 {
+    if attached_deposit == 1 {
+        0
+    } else {
+        attached_deposit
+    }
+}
+
+/// Returns the part of the fee of the forwarded amount, which corresponds to the part of
+/// the amount used by the target network, so the refunded part isn't charged.
+pub const fn charged_fee(amount: u128, used_amount: u128, fee: u128) -> u128 {
+    if used_amount >= amount {
+        return fee;
+    }
+
+    mul_div(fee, used_amount, amount)
+}
+
+/// Returns `a * b / c` rounded down without the overflow of the intermediate product.
+/// The result must fit into `u128`, which holds if `a` or `b` doesn't exceed `c`.
+const fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    const LOW: u128 = 0xFFFF_FFFF_FFFF_FFFF;
+
+    // The 256 bits product of `a` and `b` as the high and the low halves.
+    let (a_hi, a_lo) = (a >> 64, a & LOW);
+    let (b_hi, b_lo) = (b >> 64, b & LOW);
+    let lo_lo = a_lo * b_lo;
+    let mid = (lo_lo >> 64) + ((a_hi * b_lo) & LOW) + ((a_lo * b_hi) & LOW);
+    let lo = (mid << 64) | (lo_lo & LOW);
+    let hi = a_hi * b_hi + ((a_hi * b_lo) >> 64) + ((a_lo * b_hi) >> 64) + (mid >> 64);
+
+    // The long division of the product by `c` bit by bit.
+    let mut remainder = hi % c;
+    let mut quotient = 0;
+    let mut i = 128;
+
+    while i > 0 {
+        i -= 1;
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((lo >> i) & 1);
+        quotient <<= 1;
+
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+
+    quotient
+}
+
+pub fn vec_to_number<T: FromStr>(vec: &Vec<u8>) -> Result<T, ContractError> {
+    let x = core::str::from_utf8(&vec[..]).map_err(|_| ContractError::BadUtf8String)?;
+    T::from_str(x.trim_matches('"')).map_err(|_| ContractError::BadNumber)
+}
+
+#[test]
+fn test_deserialize_state() {
+    let original = State {
+        target_address: Address([1; 20]),
+        target_network: AccountId::new("target.near").unwrap(),
+        wnear_contract_id: AccountId::new("wnear.near").unwrap(),
+        fees_contract_id: AccountId::new("fees.near").unwrap(),
+        owner_id: AccountId::new("owner.near").unwrap(),
+    };
+
+    let bytes = crate::types::to_borsh(&original).unwrap();
+    let expected = State::try_from_slice(bytes.as_slice()).unwrap();
+
+    assert_eq!(original, expected);
+}
+
+#[test]
+fn test_deserialize_versioned_state() {
+    let original = State {
+        target_address: Address([1; 20]),
+        target_network: AccountId::new("target.near").unwrap(),
+        wnear_contract_id: AccountId::new("wnear.near").unwrap(),
+        fees_contract_id: AccountId::new("fees.near").unwrap(),
+        owner_id: AccountId::new("owner.near").unwrap(),
+    };
+
+    let legacy = crate::types::to_borsh(&original).unwrap();
+    let bytes = crate::types::to_borsh(&VersionedState::V1(original)).unwrap();
+
+    assert_eq!(bytes[0], 0);
+    assert_eq!(&bytes[1..], &legacy[..]);
+    assert_eq!(
+        VersionedState::try_from_slice(bytes.as_slice())
+            .unwrap()
+            .into_latest(),
+        original
+    );
+}
+
+#[test]
+fn test_deserialize_packed_state() {
+    let original = State {
+        target_address: Address([1; 20]),
+        target_network: AccountId::new("aurora").unwrap(),
+        wnear_contract_id: AccountId::new("wrap.near").unwrap(),
+        fees_contract_id: AccountId::new("fees.near").unwrap(),
+        owner_id: AccountId::new("owner.near").unwrap(),
+    };
+
+    let mut bytes = crate::types::to_borsh(&VersionedState::V2(PackedState(original))).unwrap();
+    let legacy = crate::types::to_borsh(&original).unwrap();
+
+    assert_eq!(bytes.len(), 1 + 20 + 1 + 1 + 10 + 11);
+    assert!(bytes.len() < legacy.len());
+    assert_eq!(&bytes[21..24], &[0xF0, 0xF1, 9]);
+
+    let expected = VersionedState::try_from_slice(bytes.as_slice()).unwrap();
+    assert!(expected.is_latest());
+    assert_eq!(expected.into_latest(), original);
+
+    // The unknown interned account is rejected.
+    bytes[21] = 0xFF;
+    assert!(VersionedState::try_from_slice(bytes.as_slice()).is_err());
+}
+
+#[test]
+fn test_state_to_json() {
+    let state = State {
+        target_address: Address([1; 20]),
+        target_network: AccountId::new("target.near").unwrap(),
+        wnear_contract_id: AccountId::new("wnear.near").unwrap(),
+        fees_contract_id: AccountId::new("fees.near").unwrap(),
+        owner_id: AccountId::new("owner.near").unwrap(),
+    };
+
+    assert_eq!(
+        state.to_json("0.1.0").as_str(),
+        r#"{"target_address":"0x0101010101010101010101010101010101010101","target_network":"target.near","wnear_contract_id":"wnear.near","fees_contract_id":"fees.near","owner_id":"owner.near","version":"0.1.0"}"#
+    );
+}
+
+#[test]
+fn test_deserialize_forward_args() {
+    let token_id = AccountId::new("usdt.near").unwrap();
+    let bytes = crate::types::to_borsh(&token_id).unwrap();
+
+    assert_eq!(
+        ForwardArgs::try_from_slice(&bytes).unwrap(),
+        ForwardArgs {
+            token_id,
+            amount: None,
+            memo: None,
+        }
+    );
+
+    let original = ForwardArgs {
+        token_id,
+        amount: Some(500),
+        memo: None,
+    };
+    let bytes = crate::types::to_borsh(&original).unwrap();
+
+    assert_eq!(ForwardArgs::try_from_slice(&bytes).unwrap(), original);
+    // The amount without the memo is still valid.
+    assert_eq!(
+        ForwardArgs::try_from_slice(&bytes[..bytes.len() - 1]).unwrap(),
+        original
+    );
+    assert!(ForwardArgs::try_from_slice(&bytes[..bytes.len() - 2]).is_err());
+
+    let original = ForwardArgs {
+        token_id,
+        amount: None,
+        memo: Memo::new("deposit-42"),
+    };
+    let bytes = crate::types::to_borsh(&original).unwrap();
+
+    assert_eq!(ForwardArgs::try_from_slice(&bytes).unwrap(), original);
+    assert!(ForwardArgs::try_from_slice(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn test_forward_params_roundtrip() {
+    let original = ForwardParams {
+        token_id: AccountId::new("usdt.near").unwrap(),
+        near_fee: 1_000_000,
+        amount: Some(500),
+        sender_id: Some(AccountId::new("alice.near").unwrap()),
+        memo: Memo::new("deposit-42"),
+    };
+    let bytes = crate::types::to_borsh(&original).unwrap();
+
+    assert_eq!(ForwardParams::try_from_slice(&bytes).unwrap(), original);
+}
+
+#[test]
+fn test_memo() {
+    assert_eq!(Memo::new("deposit-42").unwrap().as_str(), "deposit-42");
+    assert!(Memo::new(r#"a"b"#).is_none());
+    assert!(Memo::new(r"a\b").is_none());
+    assert!(Memo::new("a\nb").is_none());
+    assert!(Memo::new(&"a".repeat(MAX_MEMO_LEN)).is_some());
+    assert!(Memo::new(&"a".repeat(MAX_MEMO_LEN + 1)).is_none());
+
+    let bytes = crate::types::to_borsh(&Memo::new("ledger:1").unwrap()).unwrap();
+    assert_eq!(Memo::try_from_slice(&bytes).unwrap().as_str(), "ledger:1");
+    assert!(Memo::try_from_slice(&[1, 0, 0, 0, b'"']).is_err());
+}
+
+#[test]
+fn test_gas_config() {
+    let config = GasConfig {
+        calculate_fees: 4 * TGAS,
+        ft_transfer: 3 * TGAS,
+        ft_transfer_call: 80 * TGAS,
+    };
+
+    assert!(config.is_valid());
+    assert_eq!(config.transfer_tokens(), 128 * TGAS);
+    assert_eq!(config.finish_forward(), 143 * TGAS);
+    assert_eq!(config.calculate_fees_callback(), 163 * TGAS);
+
+    let max = GasConfig {
+        calculate_fees: GasConfig::MAX_CALCULATE_FEES,
+        ft_transfer: GasConfig::MAX_FT_TRANSFER,
+        ft_transfer_call: GasConfig::MAX_FT_TRANSFER_CALL,
+    };
+
+    assert!(max.is_valid());
+    // The forwarding with the max gas still fits into the 300 Tgas of the transaction.
+    assert!(max.calculate_fees_callback() < 280 * TGAS);
+
+    assert!(!GasConfig {
+        ft_transfer_call: GasConfig::MAX_FT_TRANSFER_CALL + 1,
+        ..config
     }
-
-    pub fn load<I: IO>(io: &I) -> Option<Self> {
-        let data = io.read_storage(STATE_STORAGE_KEY)?.to_vec();
-        Self::try_from_slice(data.as_slice()).ok()
+    .is_valid());
+    assert!(!GasConfig {
+        calculate_fees: GasConfig::MIN_CALCULATE_FEES - 1,
+        ..config
     }
+    .is_valid());
+}
 
-    pub fn is_owner(&self, account_id: AccountId) -> bool {
-        self.owner_id == account_id
-    }
+#[test]
+fn test_verified_amounts() {
+    assert_eq!(verified_amounts(1000, 990, 10), (990, 10));
+    assert_eq!(verified_amounts(2000, 990, 10), (990, 10));
+    // The token has deducted 2% of the balance.
+    assert_eq!(verified_amounts(980, 990, 10), (971, 9));
+    assert_eq!(verified_amounts(0, 990, 10), (0, 0));
+    assert_eq!(verified_amounts(500, 500, 0), (500, 0));
+    assert_eq!(verified_amounts(400, 500, 0), (400, 0));
+
+    // The amounts of the token with 24 decimals don't overflow.
+    let unit = 10u128.pow(24);
+    assert_eq!(
+        verified_amounts(98_000 * unit, 99_000 * unit, 1_000 * unit),
+        (97_020 * unit, 980 * unit)
+    );
 }
 
-#[derive(BorshSerialize)]
-pub struct FeesParams<'a> {
-    pub amount: u128,
-    pub token_id: &'a AccountId,
-    pub target_network: &'a AccountId,
-    pub target_address: Address,
+#[test]
+fn test_near_fee() {
+    assert_eq!(near_fee(0), 0);
+    assert_eq!(near_fee(1), 0);
+    assert_eq!(near_fee(2), 2);
+    assert_eq!(near_fee(1_000_000), 1_000_000);
 }
 
-#[derive(BorshDeserialize)]
-pub struct ForwardParams {
-    pub token_id: AccountId,
+#[test]
+fn test_charged_fee() {
+    assert_eq!(charged_fee(1000, 1000, 50), 50);
+    assert_eq!(charged_fee(1000, 600, 50), 30);
+    assert_eq!(charged_fee(1000, 0, 50), 0);
+    assert_eq!(charged_fee(0, 0, 50), 50);
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
-pub struct FinishForwardParams {
-    pub amount: u128,
-    pub token_id: AccountId,
-    pub promise_idx: u64,
+#[test]
+fn test_mul_div() {
+    assert_eq!(mul_div(10, 980, 1000), 9);
+    assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), u128::MAX);
+    assert_eq!(mul_div(u128::MAX, 3, u128::MAX), 3);
+    assert_eq!(mul_div(1 << 100, 1 << 100, 1 << 120), 1 << 80);
+    assert_eq!(mul_div(0, 5, 7), 0);
 }
 
-pub fn ft_transfer_call_args(receiver_id: &AccountId, amount: u128, address: Address) -> Vec<u8> {
-    let mut result = ArrayString::<128>::new();
+#[test]
+fn test_splits() {
+    let split = |byte, weight| Split {
+        address: Address([byte; 20]),
+        weight,
+    };
+    let target_address = Address([9; 20]);
 
-    result.push_str(r#"{"receiver_id":""#);
-    result.push_str(receiver_id.as_str());
-    result.push_str(r#"","amount":""#);
-    result.push_str(amount_to_str(amount).as_str());
-    result.push_str(r#"","msg":""#);
+    let splits = Splits::default();
+    assert!(splits.is_valid());
+    assert_eq!(
+        splits.destinations(target_address, 1000).as_slice(),
+        &[(target_address, 1000)]
+    );
+
+    let splits = Splits([split(1, 9_000), split(2, 1_000)].into_iter().collect());
+    assert!(splits.is_valid());
+    assert_eq!(
+        splits.destinations(target_address, 1001).as_slice(),
+        &[(Address([1; 20]), 900), (Address([2; 20]), 101)]
+    );
+
+    let bytes = crate::types::to_borsh(&splits).unwrap();
+    assert_eq!(Splits::try_from_slice(&bytes).unwrap(), splits);
+
+    assert!(!Splits([split(1, 9_000), split(2, 999)].into_iter().collect()).is_valid());
+    assert!(!Splits([split(1, 10_000), split(2, 0)].into_iter().collect()).is_valid());
+    assert!(!Splits([split(0, 5_000), split(2, 5_000)].into_iter().collect()).is_valid());
+}
+
+#[test]
+fn test_forward_amount() {
+    assert_eq!(forward_amount(1000, None), Some(1000));
+    assert_eq!(forward_amount(1000, Some(400)), Some(400));
+    assert_eq!(forward_amount(1000, Some(1000)), Some(1000));
+    assert_eq!(forward_amount(1000, Some(1001)), None);
+    assert_eq!(forward_amount(1000, Some(0)), None);
+}
+
+#[test]
+fn test_capped_amount() {
+    assert_eq!(capped_amount(1000, 0), 1000);
+    assert_eq!(capped_amount(1000, 400), 400);
+    assert_eq!(capped_amount(1000, 1000), 1000);
+    assert_eq!(capped_amount(300, 400), 300);
+}
+
+#[test]
+fn test_is_lock_active() {
+    assert!(is_lock_active(1_000, 1_000));
+    assert!(is_lock_active(1_000, 1_000 + FORWARD_LOCK_TIMEOUT - 1));
+    assert!(!is_lock_active(1_000, 1_000 + FORWARD_LOCK_TIMEOUT));
+    assert!(is_lock_active(1_000, 0));
+}
+
+#[test]
+fn test_forward_all_index() {
+    assert_eq!(forward_all_index(0, 0, 3), 0);
+    assert_eq!(forward_all_index(2, 0, 3), 2);
+    assert_eq!(forward_all_index(2, 1, 3), 0);
+    assert_eq!(forward_all_index(2, 2, 3), 1);
+    // The cursor beyond the untracked tokens wraps around.
+    assert_eq!(forward_all_index(5, 0, 3), 2);
+    assert_eq!(forward_all_index(5, 1, 3), 0);
+}
+
+#[test]
+fn test_address_proof_message() {
+    let message = address_proof_message(
+        &AccountId::new("fwd.factory.near").unwrap(),
+        Address([0xab; 20]),
+        7,
+    )
+    .unwrap();
+
+    assert_eq!(
+        message.as_str(),
+        "\x19Ethereum Signed Message:\n101Set the target address of fwd.factory.near to 0xabababababababababababababababababababab with nonce 7"
+    );
+}
+
+#[test]
+fn test_json_object() {
+    assert_eq!(
+        json_object(&[("fee", Some("10")), ("rejection", None)])
+            .unwrap()
+            .as_str(),
+        r#"{"fee":"10","rejection":null}"#
+    );
+    assert_eq!(json_object(&[]).unwrap().as_str(), "{}");
+}
 
-    let address_hex_iter = hex::BytesToHexIter::new(address.0.iter().copied());
+#[test]
+fn test_static_fee() {
+    let fee = StaticFee {
+        fee_bps: 25,
+        fee_recipient: AccountId::new("fees.near").unwrap(),
+    };
 
-    for c in address_hex_iter {
-        result.push(c);
+    assert!(fee.is_valid());
+    assert_eq!(fee.fees_result(1_000_000).fee, 2_500);
+    assert_eq!(fee.fees_result(399).fee, 0);
+    assert_eq!(
+        fee.fees_result(u128::MAX).fee,
+        850_705_917_302_346_158_658_436_518_579_420_528
+    );
+    assert!(!StaticFee {
+        fee_bps: 10_001,
+        ..fee
     }
+    .is_valid());
+}
 
-    result.push_str(r#""}"#);
+#[test]
+fn test_fee_cache() {
+    let cache = FeeCache {
+        percent: 30_000,
+        fee_recipient: AccountId::new("fees.near").unwrap(),
+        block_height: 100,
+    };
 
-    Vec::try_from(result.as_bytes()).unwrap_or_default()
+    assert_eq!(cache.fees_result(2_000, 100, 10).unwrap().fee, 60);
+    assert_eq!(cache.fees_result(500, 109, 10).unwrap().fee, 15);
+    // The fee is at least one unit and doesn't exceed the amount.
+    assert_eq!(cache.fees_result(10, 100, 10).unwrap().fee, 1);
+    assert_eq!(cache.fees_result(0, 100, 10).unwrap().fee, 0);
+    assert!(cache.fees_result(500, 110, 10).is_none());
+    assert!(cache.fees_result(500, 100, 0).is_none());
 }
 
-pub fn ft_transfer_args(receiver_id: &AccountId, amount: u128) -> Vec<u8> {
-    let mut result = ArrayString::<128>::new();
+#[test]
+fn test_forward_schedule_is_due() {
+    let schedule = ForwardSchedule {
+        interval_hours: 2,
+        last_triggered_at: 1_000,
+    };
 
-    result.push_str(r#"{"receiver_id":""#);
-    result.push_str(receiver_id.as_str());
-    result.push_str(r#"","amount":""#);
-    result.push_str(amount_to_str(amount).as_str());
-    result.push_str(r#""}"#);
+    assert!(!schedule.is_due(1_000));
+    assert!(!schedule.is_due(1_000 + 2 * NANOS_PER_HOUR - 1));
+    assert!(schedule.is_due(1_000 + 2 * NANOS_PER_HOUR));
+    assert!(ForwardSchedule {
+        interval_hours: 2,
+        last_triggered_at: 0,
+    }
+    .is_due(NANOS_PER_HOUR * 2));
+}
 
-    Vec::try_from(result.as_bytes()).unwrap_or_default()
+#[test]
+fn test_rate_limit_admit() {
+    let limit = RateLimit {
+        max_forwards: 2,
+        window_secs: 60,
+    };
+    let window = |started_at, count| RateWindow { started_at, count };
+
+    assert!(limit.is_valid());
+    assert_eq!(limit.admit(None, 1_000), Some(window(1_000, 1)));
+    assert_eq!(
+        limit.admit(Some(window(1_000, 1)), 2_000),
+        Some(window(1_000, 2))
+    );
+    assert_eq!(limit.admit(Some(window(1_000, 2)), 2_000), None);
+    assert_eq!(
+        limit.admit(Some(window(1_000, 2)), 1_000 + 60 * NANOS_PER_SECOND),
+        Some(window(1_000 + 60 * NANOS_PER_SECOND, 1))
+    );
+    assert!(!RateLimit {
+        max_forwards: 0,
+        ..limit
+    }
+    .is_valid());
 }
 
-pub fn ft_balance_args(account_id: &AccountId) -> Vec<u8> {
-    let mut result = ArrayString::<128>::new();
+#[test]
+fn test_deserialize_account_list() {
+    let token = |id| AccountId::new(id).unwrap();
+    let params = AccountList(
+        [token("usdt.near"), token("usdc.near")]
+            .into_iter()
+            .collect(),
+    );
+    let bytes = crate::types::to_borsh(&params).unwrap();
 
-    result.push_str(r#"{"account_id":""#);
-    result.push_str(account_id.as_str());
-    result.push_str(r#""}"#);
+    assert_eq!(AccountList::try_from_slice(&bytes).unwrap(), params);
+    assert!(!params.has_duplicates());
 
-    Vec::try_from(result.as_bytes()).unwrap_or_default()
+    let params = AccountList(
+        [token("usdt.near"), token("usdc.near"), token("usdt.near")]
+            .into_iter()
+            .collect(),
+    );
+    assert!(params.has_duplicates());
+
+    let params = AccountList(
+        ["a.near", "b.near", "c.near", "d.near"]
+            .into_iter()
+            .map(token)
+            .collect(),
+    );
+    let mut bytes = crate::types::to_borsh(&params).unwrap();
+    // The number of the tokens exceeds the max one.
+    bytes[0] = 5;
+    bytes.extend(crate::types::to_borsh(&token("e.near")).unwrap());
+    assert!(AccountList::try_from_slice(&bytes).is_err());
 }
 
-fn amount_to_str(mut amount: u128) -> ArrayString<39> {
-    let mut len = 0;
-    let mut buf = ['0'; 39];
+#[test]
+fn test_deserialize_fees_result() {
+    let mut bytes = Vec::new();
+    bytes.try_extend_from_slice(&50u128.to_le_bytes()).unwrap();
+    bytes.try_extend_from_slice(&950u128.to_le_bytes()).unwrap();
+    bytes
+        .try_extend_from_slice(&crate::types::to_borsh("fees.near").unwrap())
+        .unwrap();
+    bytes
+        .try_extend_from_slice(&crate::types::to_borsh("global_fee").unwrap())
+        .unwrap();
 
-    if amount == 0 {
-        buf[0] = core::char::from_digit(0, 10).unwrap();
-        len = 1;
-    } else {
-        while amount > 0 {
-            let digit = amount % 10;
-            buf[len] = core::char::from_digit(digit as u32, 10).unwrap();
-            amount /= 10;
-            len += 1;
+    assert_eq!(
+        FeesResult::from_slice(&bytes),
+        Ok(FeesResult {
+            fee: 50,
+            fee_recipient: AccountId::new("fees.near").unwrap(),
+            rejected: false,
+            invalid_address: false,
+            percent: None,
+        })
+    );
+
+    bytes.push(1);
+    assert_eq!(
+        FeesResult::from_slice(&bytes),
+        Ok(FeesResult {
+            fee: 50,
+            fee_recipient: AccountId::new("fees.near").unwrap(),
+            rejected: true,
+            invalid_address: false,
+            percent: None,
+        })
+    );
+
+    bytes.push(1);
+    assert_eq!(
+        FeesResult::from_slice(&bytes),
+        Ok(FeesResult {
+            fee: 50,
+            fee_recipient: AccountId::new("fees.near").unwrap(),
+            rejected: true,
+            invalid_address: true,
+            percent: None,
+        })
+    );
+
+    bytes.push(1);
+    bytes
+        .try_extend_from_slice(&50_000u64.to_le_bytes())
+        .unwrap();
+    assert_eq!(
+        FeesResult::from_slice(&bytes).unwrap().percent,
+        Some(50_000)
+    );
+    assert_eq!(
+        FeesResult::from_slice(&bytes[..20]),
+        Err(ContractError::BorshDeserializeError)
+    );
+}
+
+#[test]
+fn test_storage_key() {
+    let key = storage_key(
+        PENDING_STORAGE_PREFIX,
+        &AccountId::new("usdt.near").unwrap(),
+    );
+    assert_eq!(&key[..], b"FWD_PENDINGusdt.near");
+}
+
+#[test]
+fn test_forward_stats() {
+    let mut stats = ForwardStats::default();
+
+    stats.record(900, 100, 1_000, true);
+    stats.record(50, 0, 1_000, false);
+    stats.record(0, 10, 2_000, true);
+
+    assert_eq!(
+        stats,
+        ForwardStats {
+            count: 2,
+            total_forwarded: 950,
+            total_fees: 110,
+            last_timestamp: 2_000,
         }
-        buf[..len].reverse();
+    );
+
+    let bytes = crate::types::to_borsh(&stats).unwrap();
+    assert_eq!(ForwardStats::try_from_slice(&bytes).unwrap(), stats);
+}
+
+#[test]
+fn test_retry_queue() {
+    let entry = |amount| RetryEntry {
+        amount,
+        reason: FailureReason::Refunded,
+        timestamp: 1_000,
+    };
+    let mut queue = RetryQueue::default();
+    assert!(queue.is_empty());
+
+    for amount in 0..=u128::try_from(MAX_RETRIES).unwrap() {
+        queue.push(entry(amount));
     }
 
-    let mut result = ArrayString::<39>::new();
-    buf[..len].iter().for_each(|d| result.push(*d));
+    // The oldest entry has been dropped.
+    assert_eq!(queue.0.len(), MAX_RETRIES);
+    assert_eq!(queue.0[0], entry(1));
 
-    result
+    let bytes = crate::types::to_borsh(&queue).unwrap();
+    assert_eq!(RetryQueue::try_from_slice(&bytes).unwrap(), queue);
 }
 
-pub fn vec_to_number<T: FromStr>(vec: &Vec<u8>) -> Result<T, ContractError> {
-    let x = core::str::from_utf8(&vec[..]).map_err(|_| ContractError::BadUtf8String)?;
-    T::from_str(x.trim_matches('"')).map_err(|_| ContractError::BadNumber)
+#[test]
+fn test_json_string_field() {
+    let json = r#"{"sender_id":"alice.near","previous_owner_id": "bob.near","token_id" : "1","msg":"{\"token_id\":\"2\"}"}"#;
+    assert_eq!(json_string_field(json, "sender_id"), Some("alice.near"));
+    assert_eq!(
+        json_string_field(json, "previous_owner_id"),
+        Some("bob.near")
+    );
+    assert_eq!(json_string_field(json, "token_id"), Some("1"));
+    assert_eq!(json_string_field(json, "owner_id"), None);
+    assert_eq!(json_string_field(json, "msg"), None);
 }
 
 #[test]
-fn test_deserialize_state() {
-    let original = State {
-        target_address: Address([1; 20]),
-        target_network: AccountId::new("target.near").unwrap(),
-        wnear_contract_id: AccountId::new("wnear.near").unwrap(),
-        fees_contract_id: AccountId::new("fees.near").unwrap(),
-        owner_id: AccountId::new("owner.near").unwrap(),
+fn test_nft_transfer_call_args() {
+    let json = nft_transfer_call_args(
+        &AccountId::new("nft-bridge.near").unwrap(),
+        "42",
+        Address([1; 20]),
+    )
+    .unwrap();
+    assert_eq!(
+        &json[..],
+        br#"{"receiver_id":"nft-bridge.near","token_id":"42","msg":"0101010101010101010101010101010101010101"}"#
+    );
+
+    let token_id = "1".repeat(256);
+    assert!(nft_transfer_call_args(
+        &AccountId::new("nft-bridge.near").unwrap(),
+        &token_id,
+        Address([1; 20]),
+    )
+    .is_none());
+}
+
+#[test]
+fn test_nft_transfer_args() {
+    let json = nft_transfer_args(&AccountId::new("owner.near").unwrap(), "42").unwrap();
+    assert_eq!(
+        &json[..],
+        br#"{"receiver_id":"owner.near","token_id":"42"}"#
+    );
+
+    let token_id = "1".repeat(256);
+    assert!(nft_transfer_args(&AccountId::new("owner.near").unwrap(), &token_id).is_none());
+}
+
+#[test]
+fn test_event_json() {
+    let event = event_json(
+        "nft_returned",
+        &[("token_id", "1"), ("sender_id", "alice.near")],
+    );
+    assert_eq!(
+        event.unwrap().as_str(),
+        r#"EVENT_JSON:{"standard":"aurora-forwarder","version":"1.0.0","event":"nft_returned","data":[{"token_id":"1","sender_id":"alice.near"}]}"#
+    );
+
+    let value = "1".repeat(512);
+    assert!(event_json("nft_returned", &[("token_id", &value)]).is_none());
+}
+
+#[test]
+fn test_deserialize_target_address() {
+    let address = "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf";
+    let expected = TargetAddress {
+        address: Address(<[u8; 20]>::from_hex(&address[2..]).unwrap()),
+        hex: ArrayString::from(&address[2..]).unwrap(),
     };
 
-    let bytes = crate::types::to_borsh(&original).unwrap();
-    let expected = State::try_from_slice(bytes.as_slice()).unwrap();
+    let bytes = crate::types::to_borsh(address).unwrap();
+    assert_eq!(TargetAddress::try_from_slice(&bytes).unwrap(), expected);
+    assert_eq!(address_to_hex(expected.address).as_str(), address);
 
-    assert_eq!(original, expected);
+    let bytes = crate::types::to_borsh(&address[2..]).unwrap();
+    assert_eq!(TargetAddress::try_from_slice(&bytes).unwrap(), expected);
+
+    let bytes = crate::types::to_borsh("0x7e5f").unwrap();
+    assert!(TargetAddress::try_from_slice(&bytes).is_err());
+    let bytes = crate::types::to_borsh(&"7".repeat(43)).unwrap();
+    assert!(TargetAddress::try_from_slice(&bytes).is_err());
+}
+
+#[test]
+fn test_target_address_checksum() {
+    let target_address = |hex: &str| TargetAddress {
+        address: Address(<[u8; 20]>::from_hex(hex).unwrap()),
+        hex: ArrayString::from(hex).unwrap(),
+    };
+    // The keccak256 of the "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".
+    let hash =
+        <[u8; 32]>::from_hex("d385650ce8fdc6db7ee3a091d34814dbc4ce18219ffae52182efff4034d707e5")
+            .unwrap();
+
+    let address = target_address("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+    assert_eq!(
+        address.lowercase_hex().as_str(),
+        "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+    );
+    assert!(address.is_checksum_valid(&hash));
+    assert!(target_address("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").is_checksum_valid(&hash));
+    assert!(target_address("5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED").is_checksum_valid(&hash));
+    // The case of the first letter is mistyped.
+    assert!(!target_address("5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_checksum_valid(&hash));
 }
 
 #[test]
@@ -145,28 +2716,181 @@ fn test_ft_balance_args() {
     assert_eq!(&json[..], br#"{"account_id":"test.near"}"#);
 }
 
+#[test]
+fn test_near_withdraw_args() {
+    let json = near_withdraw_args(1_000_000);
+    assert_eq!(&json[..], br#"{"amount":"1000000"}"#);
+}
+
+#[test]
+fn test_storage_unregister_args() {
+    assert_eq!(
+        storage_unregister_args(false).as_slice(),
+        br#"{"force":false}"#
+    );
+    assert_eq!(
+        storage_unregister_args(true).as_slice(),
+        br#"{"force":true}"#
+    );
+}
+
+#[test]
+fn test_is_network_supported_args() {
+    let json = is_network_supported_args(&AccountId::new("silo.near").unwrap());
+    assert_eq!(&json[..], br#"{"network_id":"silo.near"}"#);
+}
+
+#[test]
+fn test_storage_deposit_args() {
+    let json = storage_deposit_args(&AccountId::new("test.near").unwrap());
+    assert_eq!(
+        &json[..],
+        br#"{"account_id":"test.near","registration_only":true}"#
+    );
+}
+
 #[test]
 fn test_ft_transfer_args() {
-    let json = ft_transfer_args(&AccountId::new("test.near").unwrap(), 12_345_670);
+    let json = ft_transfer_args(&AccountId::new("test.near").unwrap(), 12_345_670, None);
     assert_eq!(
         &json[..],
         br#"{"receiver_id":"test.near","amount":"12345670"}"#
     );
 
-    let json = ft_transfer_args(&AccountId::new("test.near").unwrap(), 0);
+    let json = ft_transfer_args(&AccountId::new("test.near").unwrap(), 0, None);
     assert_eq!(&json[..], br#"{"receiver_id":"test.near","amount":"0"}"#);
+
+    let memo = Memo::new("deposit-42").unwrap();
+    let json = ft_transfer_args(&AccountId::new("test.near").unwrap(), 5, Some(&memo));
+    assert_eq!(
+        &json[..],
+        br#"{"receiver_id":"test.near","amount":"5","memo":"deposit-42"}"#
+    );
 }
 
 #[test]
 fn test_ft_transfer_call_args() {
-    use hex::FromHex;
     let address = <[u8; 20]>::from_hex("7e5f4552091a69125d5dfcb7b8c2659029395bdf").unwrap();
+    let receiver_id = AccountId::new("test.near").unwrap();
     let json = ft_transfer_call_args(
-        &AccountId::new("test.near").unwrap(),
+        &receiver_id,
         12_345_670,
         Address(address),
-    );
+        100,
+        MsgFormat::Address,
+        None,
+        None,
+    )
+    .unwrap();
     assert_eq!(&json[..], br#"{"receiver_id":"test.near","amount":"12345670","msg":"7e5f4552091a69125d5dfcb7b8c2659029395bdf"}"#);
+
+    let json = ft_transfer_call_args(
+        &receiver_id,
+        12_345_670,
+        Address(address),
+        100,
+        MsgFormat::AddressFee,
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(&json[..], br#"{"receiver_id":"test.near","amount":"12345670","msg":"7e5f4552091a69125d5dfcb7b8c2659029395bdf:100"}"#);
+
+    let json = ft_transfer_call_args(
+        &receiver_id,
+        12_345_670,
+        Address(address),
+        100,
+        MsgFormat::Json,
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(&json[..], br#"{"receiver_id":"test.near","amount":"12345670","msg":"{\"recipient\":\"7e5f4552091a69125d5dfcb7b8c2659029395bdf\"}"}"#);
+
+    let json = ft_transfer_call_args(
+        &receiver_id,
+        12_345_670,
+        Address(address),
+        100,
+        MsgFormat::EthConnector,
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(&json[..], br#"{"receiver_id":"test.near","amount":"12345670","msg":"test.near:00000000000000000000000000000000000000000000000000000000000000007e5f4552091a69125d5dfcb7b8c2659029395bdf"}"#);
+
+    let memo = Memo::new("deposit-42").unwrap();
+    let json = ft_transfer_call_args(
+        &receiver_id,
+        12_345_670,
+        Address(address),
+        100,
+        MsgFormat::Address,
+        Some(&memo),
+        None,
+    )
+    .unwrap();
+    assert_eq!(&json[..], br#"{"receiver_id":"test.near","amount":"12345670","memo":"deposit-42","msg":"7e5f4552091a69125d5dfcb7b8c2659029395bdf"}"#);
+
+    let payload = XccPayload(ArrayVec::try_from([0x3a, 0x4b, 0x66, 0xf1].as_slice()).unwrap());
+    let json = ft_transfer_call_args(
+        &receiver_id,
+        12_345_670,
+        Address(address),
+        100,
+        MsgFormat::Address,
+        None,
+        Some(&payload),
+    )
+    .unwrap();
+    assert_eq!(&json[..], br#"{"receiver_id":"test.near","amount":"12345670","msg":"{\"recipient\":\"7e5f4552091a69125d5dfcb7b8c2659029395bdf\",\"input\":\"3a4b66f1\"}"}"#);
+
+    // The eth-connector doesn't call the target address, so the payload is ignored.
+    let json = ft_transfer_call_args(
+        &receiver_id,
+        12_345_670,
+        Address(address),
+        100,
+        MsgFormat::EthConnector,
+        None,
+        Some(&payload),
+    )
+    .unwrap();
+    assert_eq!(&json[..], br#"{"receiver_id":"test.near","amount":"12345670","msg":"test.near:00000000000000000000000000000000000000000000000000000000000000007e5f4552091a69125d5dfcb7b8c2659029395bdf"}"#);
+
+    // The longest payload fits into the arguments with the longest receiver, amount and memo.
+    let payload = XccPayload(ArrayVec::try_from([0xff; MAX_XCC_PAYLOAD_LEN].as_slice()).unwrap());
+    let receiver_id = AccountId::new(&"a".repeat(64)).unwrap();
+    let memo = Memo::new(&"m".repeat(MAX_MEMO_LEN)).unwrap();
+    let json = ft_transfer_call_args(
+        &receiver_id,
+        u128::MAX,
+        Address(address),
+        0,
+        MsgFormat::Json,
+        Some(&memo),
+        Some(&payload),
+    )
+    .unwrap();
+    assert_eq!(json.len(), 291 + 2 * MAX_XCC_PAYLOAD_LEN);
+    assert!(json.len() <= VEC_CAPACITY);
+}
+
+#[test]
+fn test_xcc_payload_borsh() {
+    let payload = XccPayload(ArrayVec::try_from([1, 2, 3].as_slice()).unwrap());
+    let bytes = crate::types::to_borsh(&payload).unwrap();
+    assert_eq!(XccPayload::try_from_slice(&bytes).unwrap(), payload);
+
+    let longest = crate::types::to_borsh(&[0xff_u8; MAX_XCC_PAYLOAD_LEN].as_slice()).unwrap();
+    assert_eq!(
+        XccPayload::try_from_slice(&longest).unwrap().0.len(),
+        MAX_XCC_PAYLOAD_LEN
+    );
+
+    let too_long = crate::types::to_borsh(&[0_u8; MAX_XCC_PAYLOAD_LEN + 1].as_slice()).unwrap();
+    assert!(XccPayload::try_from_slice(&too_long).is_err());
 }
 
 #[test]
@@ -16,10 +16,18 @@ pub trait Env {
     fn predecessor_account_id(&self) -> AccountId;
     /// Height of the current block.
     fn block_height(&self) -> u64;
+    /// Timestamp of the current block in nanoseconds.
+    fn block_timestamp(&self) -> u64;
     /// Amount of NEAR attached to current call
     fn attached_deposit(&self) -> u128;
     /// Account's balance in yoctoNEAR.
     fn account_balance(&self) -> u128;
+    /// Storage used by the account in bytes.
+    fn storage_usage(&self) -> u64;
+    /// Gas attached to the current call.
+    fn prepaid_gas(&self) -> u64;
+    /// Gas burnt by the current call so far.
+    fn used_gas(&self) -> u64;
 
     fn assert_private_call(&self) -> Result<(), ContractError> {
         if self.predecessor_account_id() == self.current_account_id() {
@@ -29,12 +37,19 @@ pub trait Env {
         }
     }
 
-    fn assert_one_yocto(&self) -> Result<(), ContractError> {
-        if self.attached_deposit() == 1 {
-            Ok(())
-        } else {
-            Err(ContractError::OneYoctoAttachError)
-        }
+    /// Returns the deposit attached above the required one yocto.
+    fn attached_deposit_above_one_yocto(&self) -> Result<u128, ContractError> {
+        self.attached_deposit()
+            .checked_sub(1)
+            .ok_or(ContractError::OneYoctoAttachError)
+    }
+
+    /// Returns the deposit attached above the one yocto, which isn't required, e.g. for
+    /// the calls by the delegate actions (NEP-366) signed by the function call access keys,
+    /// which can't attach the deposit. Only the deposit of exactly one yocto is taken as
+    /// the guard, so the deposit without it isn't reduced.
+    fn attached_deposit_above_optional_one_yocto(&self) -> u128 {
+        crate::params::near_fee(self.attached_deposit())
     }
 }
 
@@ -64,6 +79,10 @@ impl Env for Runtime {
         unsafe { exports::block_index() }
     }
 
+    fn block_timestamp(&self) -> u64 {
+        unsafe { exports::block_timestamp() }
+    }
+
     fn attached_deposit(&self) -> u128 {
         unsafe {
             let data = [0u8; core::mem::size_of::<u128>()];
@@ -79,4 +98,16 @@ impl Env for Runtime {
             u128::from_le_bytes(data)
         }
     }
+
+    fn storage_usage(&self) -> u64 {
+        unsafe { exports::storage_usage() }
+    }
+
+    fn prepaid_gas(&self) -> u64 {
+        unsafe { exports::prepaid_gas() }
+    }
+
+    fn used_gas(&self) -> u64 {
+        unsafe { exports::used_gas() }
+    }
 }
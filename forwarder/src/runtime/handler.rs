@@ -1,7 +1,9 @@
 use crate::runtime::io::StorageIntermediate;
 use crate::runtime::sys::exports;
 use crate::runtime::Runtime;
-use crate::types::{PromiseBatchAction, PromiseCreateArgs, PromiseResult, PromiseWithCallbackArgs};
+use crate::types::{
+    GasWeight, PromiseBatchAction, PromiseCreateArgs, PromiseResult, PromiseWithCallbackArgs,
+};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub struct PromiseId(u64);
@@ -42,6 +44,27 @@ pub trait PromiseHandler {
         callback: &PromiseCreateArgs,
     ) -> PromiseId;
 
+    /// Same as `promise_create_call`, but the call receives the share of the unused prepaid
+    /// gas proportional to the weight on top of the attached gas.
+    /// # Safety
+    /// See note on `promise_create_call`.
+    unsafe fn promise_create_call_weighted(
+        &mut self,
+        args: &PromiseCreateArgs,
+        weight: GasWeight,
+    ) -> PromiseId;
+
+    /// Same as `promise_attach_callback`, but the callback receives the share of the unused
+    /// prepaid gas proportional to the weight on top of the attached gas.
+    /// # Safety
+    /// See note on `promise_create_call`.
+    unsafe fn promise_attach_callback_weighted(
+        &mut self,
+        base: PromiseId,
+        callback: &PromiseCreateArgs,
+        weight: GasWeight,
+    ) -> PromiseId;
+
     /// # Safety
     /// See note on `promise_create_call`. Promise batches in particular must be used very
     /// carefully because they can take destructive actions such as deploying new contract
@@ -102,7 +125,7 @@ impl PromiseHandler for Runtime {
         let ids = args
             .iter()
             .map(|args| self.promise_create_call(args))
-            .collect::<arrayvec::ArrayVec<_, 2>>();
+            .collect::<arrayvec::ArrayVec<_, 4>>();
         let id = exports::promise_and(ids.as_ptr() as _, ids.len() as _);
 
         PromiseId::new(id)
@@ -136,6 +159,37 @@ impl PromiseHandler for Runtime {
         PromiseId::new(id)
     }
 
+    unsafe fn promise_create_call_weighted(
+        &mut self,
+        args: &PromiseCreateArgs,
+        weight: GasWeight,
+    ) -> PromiseId {
+        let account_id = args.target_account_id.as_bytes();
+
+        let id = { exports::promise_batch_create(account_id.len() as _, account_id.as_ptr() as _) };
+
+        Self::append_weighted_function_call(id, args, weight);
+
+        PromiseId::new(id)
+    }
+
+    unsafe fn promise_attach_callback_weighted(
+        &mut self,
+        base: PromiseId,
+        callback: &PromiseCreateArgs,
+        weight: GasWeight,
+    ) -> PromiseId {
+        let account_id = callback.target_account_id.as_bytes();
+
+        let id = {
+            exports::promise_batch_then(base.raw(), account_id.len() as _, account_id.as_ptr() as _)
+        };
+
+        Self::append_weighted_function_call(id, callback, weight);
+
+        PromiseId::new(id)
+    }
+
     unsafe fn promise_create_batch<const S: usize>(
         &mut self,
         args: &PromiseBatchAction<S>,
@@ -1,8 +1,10 @@
 use crate::runtime::sys::exports;
-use crate::types::{AccountId, PromiseAction, PromiseBatchAction};
+use crate::types::{
+    AccountId, Address, GasWeight, PromiseAction, PromiseBatchAction, PromiseCreateArgs,
+};
 
 pub use env::Env;
-pub use handler::PromiseHandler;
+pub use handler::{PromiseHandler, PromiseId};
 pub use io::{StorageIntermediate, IO};
 
 mod env;
@@ -46,6 +48,8 @@ impl Runtime {
     pub const EVICT_REGISTER_ID: RegisterIndex = RegisterIndex(3);
     pub const ENV_REGISTER_ID: RegisterIndex = RegisterIndex(4);
     pub const PROMISE_REGISTER_ID: RegisterIndex = RegisterIndex(5);
+    pub const HASH_REGISTER_ID: RegisterIndex = RegisterIndex(6);
+    pub const ECRECOVER_REGISTER_ID: RegisterIndex = RegisterIndex(7);
 
     /// Assumes a valid account ID has been written to `ENV_REGISTER_ID`
     /// by a previous call.
@@ -55,6 +59,27 @@ impl Runtime {
         AccountId::new(str).unwrap_or_default()
     }
 
+    pub(crate) unsafe fn append_weighted_function_call(
+        id: u64,
+        args: &PromiseCreateArgs,
+        weight: GasWeight,
+    ) {
+        let method_name = args.method.as_bytes();
+        let arguments = args.args.as_slice();
+        let amount = args.attached_balance;
+
+        exports::promise_batch_action_function_call_weight(
+            id,
+            method_name.len() as _,
+            method_name.as_ptr() as _,
+            arguments.len() as _,
+            arguments.as_ptr() as _,
+            core::ptr::addr_of!(amount) as _,
+            args.attached_gas,
+            weight.0,
+        );
+    }
+
     pub(crate) unsafe fn append_batch_actions<const S: usize>(
         id: u64,
         args: &PromiseBatchAction<S>,
@@ -78,6 +103,10 @@ impl Runtime {
                         beneficiary_id_str.as_ptr() as _,
                     );
                 }
+                PromiseAction::Transfer { amount } => {
+                    let amount_bytes = amount.to_le_bytes();
+                    exports::promise_batch_action_transfer(id, amount_bytes.as_ptr() as _);
+                }
             }
         }
     }
@@ -90,6 +119,61 @@ pub fn panic_utf8(bytes: &[u8]) -> ! {
     unreachable!()
 }
 
+pub fn log_utf8(bytes: &[u8]) {
+    unsafe {
+        exports::log_utf8(bytes.len() as u64, bytes.as_ptr() as u64);
+    }
+}
+
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hash = [0; 32];
+
+    unsafe {
+        exports::keccak256(
+            bytes.len() as u64,
+            bytes.as_ptr() as u64,
+            Runtime::HASH_REGISTER_ID.0,
+        );
+    }
+
+    Runtime::HASH_REGISTER_ID.copy_to_slice(&mut hash);
+    hash
+}
+
+/// Recovers the address, which has produced the secp256k1 signature of the hash. The last byte
+/// of the signature is the recovery id, which could be either 0/1 or 27/28.
+pub fn ecrecover(hash: &[u8; 32], signature: &[u8; 65]) -> Option<Address> {
+    let v = match signature[64] {
+        v @ (0 | 1) => v,
+        v @ (27 | 28) => v - 27,
+        _ => return None,
+    };
+    let mut public_key = [0; 64];
+
+    unsafe {
+        let result = exports::ecrecover(
+            hash.len() as u64,
+            hash.as_ptr() as u64,
+            64,
+            signature.as_ptr() as u64,
+            u64::from(v),
+            1,
+            Runtime::ECRECOVER_REGISTER_ID.0,
+        );
+
+        if result != 1 {
+            return None;
+        }
+    }
+
+    Runtime::ECRECOVER_REGISTER_ID.copy_to_slice(&mut public_key);
+    let hash = keccak256(&public_key);
+    let mut address = [0; 20];
+    address.copy_from_slice(&hash[12..]);
+
+    Some(Address(address))
+}
+
 pub trait SdkUnwrap<T> {
     fn sdk_unwrap(self) -> T;
 }
@@ -25,7 +25,7 @@ pub mod exports {
         pub(crate) fn account_balance(balance_ptr: u64);
         pub(crate) fn attached_deposit(balance_ptr: u64);
         pub(crate) fn prepaid_gas() -> u64;
-        fn used_gas() -> u64;
+        pub(crate) fn used_gas() -> u64;
         // ############
         // # Math API #
         // ############
@@ -103,6 +103,16 @@ pub mod exports {
             amount_ptr: u64,
             gas: u64,
         );
+        pub(crate) fn promise_batch_action_function_call_weight(
+            promise_index: u64,
+            method_name_len: u64,
+            method_name_ptr: u64,
+            arguments_len: u64,
+            arguments_ptr: u64,
+            amount_ptr: u64,
+            gas: u64,
+            weight: u64,
+        );
         pub(crate) fn promise_batch_action_transfer(promise_index: u64, amount_ptr: u64);
         pub(crate) fn promise_batch_action_stake(
             promise_index: u64,
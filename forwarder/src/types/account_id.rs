@@ -101,6 +101,8 @@ impl PartialEq for AccountId {
     }
 }
 
+impl Eq for AccountId {}
+
 #[test]
 fn test_account_id() {
     let account_id = AccountId::new("test.near").unwrap();
@@ -5,10 +5,15 @@ use crate::error::ContractError;
 pub use account_id::AccountId;
 pub use address::Address;
 pub use promise::{
-    PromiseAction, PromiseBatchAction, PromiseCreateArgs, PromiseResult, PromiseWithCallbackArgs,
+    GasWeight, PromiseAction, PromiseBatchAction, PromiseCreateArgs, PromiseResult,
+    PromiseWithCallbackArgs,
 };
 
-pub type Vec<T> = arrayvec::ArrayVec<T, 256>;
+/// The capacity of the buffers of the input, the storage values and the arguments of the calls.
+/// It holds the arguments of the `ft_transfer_call` with the longest EVM calldata.
+pub const VEC_CAPACITY: usize = 1024;
+
+pub type Vec<T> = arrayvec::ArrayVec<T, VEC_CAPACITY>;
 
 mod account_id;
 mod address;
@@ -8,6 +8,11 @@ pub struct PromiseCreateArgs {
     pub attached_gas: u64,
 }
 
+/// The weight of the call in the distribution of the unused prepaid gas, which the call
+/// receives on top of its attached gas when the current execution finishes.
+#[derive(Clone, Copy)]
+pub struct GasWeight(pub u64);
+
 pub struct PromiseWithCallbackArgs {
     pub base: PromiseCreateArgs,
     pub callback: PromiseCreateArgs,
@@ -21,6 +26,7 @@ pub struct PromiseBatchAction<const S: usize> {
 pub enum PromiseAction {
     AddFullAccessKey { public_key: [u8; 33], nonce: u64 },
     DeleteAccount { beneficiary_id: AccountId },
+    Transfer { amount: u128 },
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -0,0 +1,19 @@
+use near_sdk::serde_json::json;
+use near_workspaces::Contract;
+
+pub trait Fees {
+    async fn set_native_fee(&self, percent: Option<&str>) -> anyhow::Result<()>;
+}
+
+impl Fees for Contract {
+    async fn set_native_fee(&self, percent: Option<&str>) -> anyhow::Result<()> {
+        let result = self
+            .call("set_native_fee")
+            .args_json(json!({ "percent": percent }))
+            .max_gas()
+            .transact()
+            .await?;
+        assert!(result.is_success());
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use near_workspaces::{AccessKey, Account, AccountId, Contract, Worker};
 pub mod aurora;
 pub mod erc20;
 pub mod factory;
+pub mod fees;
 pub mod forwarder;
 pub mod fungible_token;
 
@@ -17,7 +18,7 @@ const FEES_WASM_PATH: &str = "../res/aurora-forwarder-fees.wasm";
 const FACTORY_WASM_PATH: &str = "../res/aurora-forwarder-factory.wasm";
 const WRAP_WASM_PATH: &str = "../res/w_near.wasm";
 const INIT_BALANCE_NEAR: NearToken = NearToken::from_near(50);
-const FORWARDER_MIN_BALANCE: NearToken = NearToken::from_near(2);
+const FORWARDER_MIN_BALANCE: NearToken = NearToken::from_near(3);
 
 pub struct Sandbox {
     worker: Worker<near_workspaces::network::Sandbox>,
@@ -58,6 +59,14 @@ impl Sandbox {
             .as_yoctonear()
     }
 
+    pub async fn storage_usage(&self, account_id: &AccountId) -> u64 {
+        self.worker
+            .view_account(account_id)
+            .await
+            .unwrap()
+            .storage_usage
+    }
+
     pub async fn deploy_ft(
         &self,
         total_supply: u128,
@@ -190,6 +199,21 @@ impl Sandbox {
             .await?;
         assert!(result.is_success());
 
+        // The forwarders are deployed either directly by the root account or by the factory.
+        let factory_id = format!("factory.{}", self.root_account.id());
+        for account_id in [self.root_account.id().as_str(), &factory_id] {
+            let result = fee_account
+                .call(contract.id(), "grant_role")
+                .args_json(json!({
+                    "role": "Factory",
+                    "account_id": account_id
+                }))
+                .max_gas()
+                .transact()
+                .await?;
+            assert!(result.is_success(), "{result:?}");
+        }
+
         Ok(contract)
     }
 
@@ -1,5 +1,6 @@
 use crate::sandbox::aurora::Aurora;
 use crate::sandbox::factory::Factory;
+use crate::sandbox::fees::Fees;
 use crate::sandbox::fungible_token::FungibleToken;
 use crate::sandbox::Sandbox;
 use aurora_forwarder_factory::{DeployParameters, INIT_BALANCE};
@@ -72,6 +73,7 @@ async fn test_forward_native_tokens_with_zero_fee() {
     let alice = sandbox.create_subaccount("alice", BALANCE).await.unwrap();
     let (wrap, _) = sandbox.deploy_wrap_near().await.unwrap();
     let fees = sandbox.deploy_fees(&[]).await.unwrap();
+    fees.set_native_fee(None).await.unwrap();
     let silo = sandbox.deploy_aurora("silo").await.unwrap();
     let erc20 = silo.deploy_erc20(wrap.id()).await.unwrap();
     let factory = sandbox.deploy_factory(fees.id()).await.unwrap();
@@ -118,3 +120,51 @@ async fn test_forward_native_tokens_with_zero_fee() {
         deposit / rounder
     );
 }
+
+#[tokio::test]
+async fn test_forward_near_with_attached_deposit() {
+    let transfer = NearToken::from_near(1);
+    let sandbox = Sandbox::new().await.unwrap();
+    let alice = sandbox.create_subaccount("alice", BALANCE).await.unwrap();
+    let (wrap, _) = sandbox.deploy_wrap_near().await.unwrap();
+    let fees = sandbox.deploy_fees(&[wrap.id()]).await.unwrap();
+    let silo = sandbox.deploy_aurora("silo").await.unwrap();
+    let erc20 = silo.deploy_erc20(wrap.id()).await.unwrap();
+    let factory = sandbox.deploy_factory(fees.id()).await.unwrap();
+
+    let forwarder = factory
+        .create(&[DeployParameters {
+            target_address: super::RECEIVER.to_string(),
+            target_network: silo.id().as_str().parse().unwrap(),
+            wnear_contract_id: wrap.id().as_str().parse().unwrap(),
+        }])
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+
+    wrap.storage_deposit(fees.id()).await.unwrap();
+    wrap.storage_deposit(silo.id()).await.unwrap();
+
+    let result = alice
+        .call(&forwarder, "forward_near")
+        .deposit(transfer)
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(result.is_success());
+
+    let rounder = 10u128.pow(21);
+    let fwd_balance = sandbox.balance(&forwarder).await;
+    assert_eq!(fwd_balance / rounder, INIT_BALANCE.as_millinear());
+
+    let fee = transfer.as_yoctonear() * 5 / 100;
+    let deposit = transfer.as_yoctonear() - fee;
+
+    assert_eq!(wrap.ft_balance_of(fees.id()).await / rounder, fee / rounder);
+    assert_eq!(
+        erc20.balance_of(super::RECEIVER).await / rounder,
+        deposit / rounder
+    );
+}
@@ -19,6 +19,68 @@ pub fn forwarder_prefix(
         .to_lowercase()
 }
 
+/// Checks that the address is 20 bytes of hex with the optional `0x` prefix. The mixed-case
+/// hex should have the valid EIP-55 checksum.
+#[must_use]
+pub fn is_valid_address(address: &str) -> bool {
+    let address = address.strip_prefix("0x").unwrap_or(address);
+
+    if address.len() != 40 || !address.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return false;
+    }
+
+    let is_lowercase = !address.bytes().any(|c| c.is_ascii_uppercase());
+    let is_uppercase = !address.bytes().any(|c| c.is_ascii_lowercase());
+
+    if is_lowercase || is_uppercase {
+        return true;
+    }
+
+    let hash = env::keccak256_array(address.to_ascii_lowercase().as_bytes());
+
+    address.bytes().enumerate().all(|(i, c)| {
+        let byte = hash[i / 2];
+        let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+
+        !c.is_ascii_alphabetic() || c.is_ascii_uppercase() == (nibble >= 8)
+    })
+}
+
+#[test]
+fn test_is_valid_address() {
+    // The examples of the EIP-55.
+    assert!(is_valid_address(
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+    ));
+    assert!(is_valid_address(
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359"
+    ));
+    assert!(is_valid_address("dbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB"));
+    assert!(is_valid_address(
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb"
+    ));
+
+    assert!(is_valid_address(
+        "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+    ));
+    assert!(is_valid_address(
+        "0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED"
+    ));
+
+    assert!(!is_valid_address(
+        "0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+    ));
+    assert!(!is_valid_address(
+        "0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea"
+    ));
+    assert!(!is_valid_address(
+        "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaedaa"
+    ));
+    assert!(!is_valid_address(
+        "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaeg"
+    ));
+}
+
 #[test]
 fn test_creating_forward_prefix() {
     let address = "79271e4c45303443315323e69278ad59502baca1";